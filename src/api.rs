@@ -0,0 +1,210 @@
+// The MIT License (MIT)
+// Copyright © 2021 Aukbit Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+use crate::errors::ScoutyError;
+use crate::health;
+use crate::http::{self, Response};
+use crate::metrics::RUNTIME_STATS;
+use lazy_static::lazy_static;
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    result::Result,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+lazy_static! {
+    // Updated by the monitoring loop every new session, read by the API
+    pub static ref MONITORED: Mutex<Monitored> = Mutex::new(Monitored::default());
+}
+
+/// State of a stash as observed in the last session
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MonitoredStash {
+    pub stash: String,
+    pub name: String,
+    pub is_active: bool,
+    pub era: u32,
+    pub session: u32,
+}
+
+/// State of every stash monitored, ordered by stash
+#[derive(Debug, Default)]
+pub struct Monitored {
+    stashes: BTreeMap<String, MonitoredStash>,
+}
+
+impl Monitored {
+    pub fn record(
+        &mut self,
+        stash: &str,
+        name: &str,
+        is_active: bool,
+        era: u32,
+        session: u32,
+    ) {
+        self.stashes.insert(
+            stash.to_string(),
+            MonitoredStash {
+                stash: stash.to_string(),
+                name: name.to_string(),
+                is_active,
+                era,
+                session,
+            },
+        );
+    }
+
+    pub fn stashes(&self) -> Vec<MonitoredStash> {
+        self.stashes.values().cloned().collect()
+    }
+}
+
+/// Status of the 'scouty' process, last_rpc_success is a unix timestamp in
+/// seconds, or none if no RPC interaction succeeded yet
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Status {
+    pub uptime_seconds: u64,
+    pub last_rpc_success: Option<u64>,
+    pub reconnections: u64,
+}
+
+impl Status {
+    pub fn new(uptime: Duration, last_rpc_success: u64, reconnections: u64) -> Status {
+        Status {
+            uptime_seconds: uptime.as_secs(),
+            last_rpc_success: if last_rpc_success > 0 {
+                Some(last_rpc_success)
+            } else {
+                None
+            },
+            reconnections,
+        }
+    }
+}
+
+fn json<T: Serialize>(value: &T) -> Response {
+    match serde_json::to_string(value) {
+        Ok(body) => Response::ok("application/json", body),
+        Err(_) => Response::service_unavailable(),
+    }
+}
+
+/// Routes the read-only endpoints, /stashes and /status
+fn handle(path: &str, monitored: &Monitored, status: Status) -> Response {
+    match path {
+        "/stashes" => json(&monitored.stashes()),
+        "/status" => json(&status),
+        _ => Response::not_found(),
+    }
+}
+
+/// Starts the HTTP server exposing the monitored state as JSON
+pub fn serve(listen_addr: &str) -> Result<(), ScoutyError> {
+    http::serve(listen_addr, |path| {
+        let status = Status::new(
+            RUNTIME_STATS.uptime(Instant::now()),
+            health::last_rpc_success(),
+            RUNTIME_STATS.reconnections(),
+        );
+        handle(path, &MONITORED.lock().unwrap(), status)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn it_serves_the_monitored_stashes_as_json() {
+        let mut monitored = Monitored::default();
+        monitored.record(
+            "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",
+            "MyValidator01",
+            false,
+            4169,
+            25020,
+        );
+        // the state of the following session replaces the previous one
+        monitored.record(
+            "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",
+            "MyValidator01",
+            true,
+            4170,
+            25021,
+        );
+        monitored.record(
+            "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty",
+            "MyValidator02",
+            false,
+            4170,
+            25021,
+        );
+
+        let response = handle("/stashes", &monitored, Status::new(Duration::ZERO, 0, 0));
+        assert_eq!(response.status, 200);
+        assert_eq!(response.content_type, "application/json");
+        let body: serde_json::Value = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(
+            body,
+            json!([
+                {
+                    "stash": "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty",
+                    "name": "MyValidator02",
+                    "is_active": false,
+                    "era": 4170,
+                    "session": 25021
+                },
+                {
+                    "stash": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",
+                    "name": "MyValidator01",
+                    "is_active": true,
+                    "era": 4170,
+                    "session": 25021
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn it_serves_the_status_as_json() {
+        let monitored = Monitored::default();
+        let response = handle(
+            "/status",
+            &monitored,
+            Status::new(Duration::from_secs(3600), 1_700_000_000, 2),
+        );
+        let body: serde_json::Value = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(
+            body,
+            json!({"uptime_seconds": 3600, "last_rpc_success": 1_700_000_000, "reconnections": 2})
+        );
+
+        let response = handle("/status", &monitored, Status::new(Duration::ZERO, 0, 0));
+        let body: serde_json::Value = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(body["last_rpc_success"], serde_json::Value::Null);
+        assert_eq!(
+            handle("/metrics", &monitored, Status::new(Duration::ZERO, 0, 0)).status,
+            404
+        );
+    }
+}
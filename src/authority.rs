@@ -90,13 +90,13 @@ impl AuthorityRecords {
         block_number: u32,
         authority: Option<AuthorityIndex>,
     ) -> Result<(), ScoutyError> {
-        let config = CONFIG.clone();
+        let config = CONFIG.load_full();
         if self.last_block != block_number {
             if let Some(authority_index) = authority {
                 // Get author stash from authorities set
                 let i: usize = authority_index.try_into().unwrap();
                 if let Some(author_stash) = self.authorities.get(i) {
-                    for stash_str in config.stashes.iter() {
+                    for stash_str in config.stashes.iter().map(|stash| &stash.address) {
                         let stash = AccountId32::from_str(stash_str)?;
                         if author_stash == &stash {
                             let key = format!(
@@ -0,0 +1,162 @@
+// The MIT License (MIT)
+// Copyright © 2021 Aukbit Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+use lazy_static::lazy_static;
+use std::{
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+lazy_static! {
+    // Kept across subscription restarts so that the delay keeps growing while
+    // the substrate node is flapping
+    pub static ref RECONNECT: Mutex<Backoff> = Mutex::new(Backoff::new(seed()));
+}
+
+fn seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(1, |d| d.as_nanos() as u64)
+}
+
+/// Exponential backoff with jitter. The delay doubles on every attempt from the
+/// base up to the max, a random jitter of up to half of the delay is then
+/// subtracted so that retries are spread over time.
+#[derive(Debug)]
+pub struct Backoff {
+    attempt: u32,
+    rng: u64,
+}
+
+impl Backoff {
+    pub fn new(seed: u64) -> Backoff {
+        Backoff {
+            attempt: 0,
+            // xorshift requires a non-zero state
+            rng: seed.max(1),
+        }
+    }
+
+    /// Returns the delay before the next attempt
+    pub fn next_delay(&mut self, base: Duration, max: Duration) -> Duration {
+        let max = max.max(base);
+        let delay = base
+            .checked_mul(2u32.saturating_pow(self.attempt))
+            .map_or(max, |delay| delay.min(max));
+        self.attempt = self.attempt.saturating_add(1);
+        let half = delay / 2;
+        // jitter in whole seconds keeps the delay readable when displayed
+        let jitter_secs = match half.as_secs() {
+            0 => 0,
+            n => self.next_random() % (n + 1),
+        };
+        delay - Duration::from_secs(jitter_secs)
+    }
+
+    /// Returns true once the attempts exceed the maximum defined, if any
+    pub fn is_exhausted(&self, max_attempts: Option<u64>) -> bool {
+        max_attempts.map_or(false, |max| u64::from(self.attempt) > max)
+    }
+
+    /// Starts again from the base delay, e.g. after a successful connection
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+
+    // xorshift64*
+    fn next_random(&mut self) -> u64 {
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        self.rng.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+}
+
+/// Returns the delay before reconnecting after a critical error
+pub fn next_delay(base: Duration, max: Duration) -> Duration {
+    RECONNECT.lock().unwrap().next_delay(base, max)
+}
+
+/// Returns true if the consecutive reconnections exceed the maximum of restarts
+pub fn is_exhausted(max_restarts: Option<u64>) -> bool {
+    RECONNECT.lock().unwrap().is_exhausted(max_restarts)
+}
+
+/// Restarts the reconnection delay from the base
+pub fn reset() {
+    RECONNECT.lock().unwrap().reset();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_grows_exponentially_with_jitter_up_to_the_max() {
+        let base = Duration::from_secs(30 * 60);
+        let max = Duration::from_secs(6 * 3600);
+        let mut backoff = Backoff::new(42);
+        let delays: Vec<Duration> =
+            (0..8).map(|_| backoff.next_delay(base, max)).collect();
+
+        let expected = [30, 60, 120, 240, 360, 360, 360, 360];
+        for (delay, minutes) in delays.iter().zip(expected.iter()) {
+            let full = Duration::from_secs(minutes * 60);
+            assert!(*delay <= full);
+            assert!(*delay >= full / 2);
+        }
+
+        // the same seed gives the same sequence
+        let mut backoff = Backoff::new(42);
+        let same: Vec<Duration> = (0..8).map(|_| backoff.next_delay(base, max)).collect();
+        assert_eq!(delays, same);
+
+        // a reset starts again from the base
+        backoff.reset();
+        let delay = backoff.next_delay(base, max);
+        assert!(delay <= base && delay >= base / 2);
+    }
+
+    #[test]
+    fn it_is_exhausted_once_the_max_attempts_are_exceeded() {
+        let base = Duration::from_secs(60);
+        let mut backoff = Backoff::new(42);
+        for _ in 0..3 {
+            backoff.next_delay(base, base);
+            assert!(!backoff.is_exhausted(Some(3)));
+        }
+        backoff.next_delay(base, base);
+        assert!(backoff.is_exhausted(Some(3)));
+        // unlimited attempts if no max is defined
+        assert!(!backoff.is_exhausted(None));
+
+        backoff.reset();
+        assert!(!backoff.is_exhausted(Some(3)));
+    }
+
+    #[test]
+    fn it_never_exceeds_the_base_if_max_is_lower() {
+        let base = Duration::from_secs(60);
+        let mut backoff = Backoff::new(7);
+        for _ in 0..40 {
+            assert!(backoff.next_delay(base, Duration::ZERO) <= base);
+        }
+    }
+}
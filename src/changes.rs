@@ -0,0 +1,153 @@
+// The MIT License (MIT)
+// Copyright © 2021 Aukbit Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+use crate::config::CONFIG;
+use crate::errors::ScoutyError;
+use lazy_static::lazy_static;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fs, path::Path, sync::Mutex};
+
+lazy_static! {
+    // Kept across subscription restarts so that a restart is not seen as a change,
+    // and loaded from the state file, if any, to also survive a process restart
+    pub static ref LAST_ACTIVE: Mutex<LastActive> = Mutex::new(LastActive::load_or_default(
+        CONFIG.load().state_file_path.as_deref()
+    ));
+}
+
+/// Last state observed of a stash
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StashState {
+    pub era: u32,
+    pub is_active: bool,
+}
+
+/// Last state observed for each stash
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct LastActive {
+    stashes: HashMap<String, StashState>,
+}
+
+impl LastActive {
+    /// Records the state of the stash and verifies if the active state differs
+    /// from the previous one observed. The first state observed is always a change.
+    pub fn has_changed(&mut self, stash: &str, era: u32, is_active: bool) -> bool {
+        self.stashes
+            .insert(stash.to_string(), StashState { era, is_active })
+            .map_or(true, |state| state.is_active != is_active)
+    }
+
+    /// Loads the state from the file, a missing or corrupt file is replaced
+    /// by a fresh state with a warning
+    pub fn load_or_default(path: Option<&str>) -> LastActive {
+        let path = match path {
+            Some(path) => path,
+            None => return LastActive::default(),
+        };
+        if !Path::new(path).exists() {
+            return LastActive::default();
+        }
+        match fs::read_to_string(path)
+            .map_err(ScoutyError::from)
+            .and_then(|content| {
+                serde_json::from_str(&content)
+                    .map_err(|e| ScoutyError::Other(format!("{}", e)))
+            }) {
+            Ok(last_active) => last_active,
+            Err(e) => {
+                warn!("State file {} skipped, starting fresh! {}", path, e);
+                LastActive::default()
+            }
+        }
+    }
+
+    /// Writes the state to a temporary file first, so that the file is never
+    /// left half written
+    pub fn save(&self, path: &str) -> Result<(), ScoutyError> {
+        let content = serde_json::to_string(self)
+            .map_err(|e| ScoutyError::Other(format!("{}", e)))?;
+        let tmp_path = format!("{}.tmp", path);
+        fs::write(&tmp_path, content)?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+}
+
+/// Writes the last state observed to the state file, if defined
+pub fn save_state() {
+    if let Some(path) = &CONFIG.load().state_file_path {
+        if let Err(e) = LAST_ACTIVE.lock().unwrap().save(path) {
+            warn!("State file {} not saved! {}", path, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_notifies_only_the_state_changes() {
+        let mut last_active = LastActive::default();
+        assert!(last_active.has_changed("alice", 1, true));
+        let notified = vec![true, true, false]
+            .into_iter()
+            .filter(|is_active| last_active.has_changed("alice", 1, *is_active))
+            .count();
+        assert_eq!(notified, 1);
+        // stashes are tracked independently
+        assert!(last_active.has_changed("bob", 1, false));
+        assert!(!last_active.has_changed("alice", 2, false));
+    }
+
+    #[test]
+    fn it_round_trips_the_state_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        let path = path.to_str().unwrap();
+
+        let mut last_active = LastActive::default();
+        last_active.has_changed("alice", 10, true);
+        last_active.has_changed("bob", 10, false);
+        last_active.save(path).unwrap();
+
+        let mut loaded = LastActive::load_or_default(Some(path));
+        assert_eq!(loaded, last_active);
+        assert!(!loaded.has_changed("alice", 11, true));
+        assert!(loaded.has_changed("bob", 11, true));
+    }
+
+    #[test]
+    fn it_starts_fresh_with_a_missing_or_corrupt_state_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        let path = path.to_str().unwrap();
+        assert_eq!(
+            LastActive::load_or_default(Some(path)),
+            LastActive::default()
+        );
+
+        fs::write(path, "{\"stashes\": {\"alice\": tru").unwrap();
+        let mut loaded = LastActive::load_or_default(Some(path));
+        assert_eq!(loaded, LastActive::default());
+        assert!(loaded.has_changed("alice", 10, true));
+    }
+}
@@ -0,0 +1,69 @@
+// The MIT License (MIT)
+// Copyright © 2021 Aukbit Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+use std::collections::BTreeMap;
+
+/// Last commission seen for each stash, in parts per billion (Perbill)
+#[derive(Debug, Default)]
+pub struct CommissionRecords {
+    commissions: BTreeMap<String, u32>,
+}
+
+impl CommissionRecords {
+    pub fn new() -> CommissionRecords {
+        CommissionRecords::default()
+    }
+
+    /// Stores the commission fetched for the stash and returns the one previously
+    /// stored if it changed. The first commission stored is never a change.
+    pub fn update(&mut self, stash: &str, commission: u32) -> Option<u32> {
+        match self.commissions.insert(stash.to_string(), commission) {
+            Some(previous) if previous != commission => Some(previous),
+            _ => None,
+        }
+    }
+}
+
+/// Formats a Perbill commission as a percentage (e.g. 50000000 -> 5.00%)
+pub fn format_commission(commission: u32) -> String {
+    format!("{:.2}%", commission as f64 / 10_000_000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_detects_commission_changes() {
+        let mut records = CommissionRecords::new();
+        let stash = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+        assert_eq!(records.update(stash, 50_000_000), None);
+        assert_eq!(records.update(stash, 50_000_000), None);
+        assert_eq!(records.update(stash, 100_000_000), Some(50_000_000));
+        assert_eq!(records.update(stash, 100_000_000), None);
+        // stashes are tracked independently
+        assert_eq!(
+            records.update("5GTD7ZeD823BjpmZBCSzBQp7cvHR1Gunq7oDkurZr9zUev2n", 0),
+            None
+        );
+        assert_eq!(format_commission(50_000_000), "5.00%");
+        assert_eq!(format_commission(1_000_000_000), "100.00%");
+    }
+}
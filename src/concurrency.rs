@@ -0,0 +1,166 @@
+// The MIT License (MIT)
+// Copyright © 2021 Aukbit Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+use crate::errors::ScoutyError;
+use crate::metrics::Metrics;
+use async_std::future;
+use futures::{stream, Future, StreamExt};
+use std::time::{Duration, Instant};
+
+/// Runs `f` for every item with at most `limit` futures in flight, so that the
+/// RPC node is not overwhelmed. The results keep the order of the items.
+pub async fn map_bounded<I, F, Fut, R>(items: I, limit: usize, f: F) -> Vec<R>
+where
+    I: IntoIterator,
+    F: FnMut(I::Item) -> Fut,
+    Fut: Future<Output = R>,
+{
+    stream::iter(items)
+        .map(f)
+        .buffered(limit.max(1))
+        .collect()
+        .await
+}
+
+/// Awaits `fut` for at most `duration`, a zero duration awaits indefinitely.
+/// When the duration elapses first a timeout error is returned, which is
+/// transient so 'scouty' goes through its usual retry path.
+pub async fn with_timeout<Fut, T, E>(
+    duration: Duration,
+    what: &str,
+    fut: Fut,
+) -> Result<T, ScoutyError>
+where
+    Fut: Future<Output = Result<T, E>>,
+    E: Into<ScoutyError>,
+{
+    if duration.as_nanos() == 0 {
+        return fut.await.map_err(Into::into);
+    }
+    match future::timeout(duration, fut).await {
+        Ok(result) => result.map_err(Into::into),
+        Err(_) => Err(ScoutyError::TimeoutError(format!(
+            "{} did not complete within {}",
+            what,
+            humantime::format_duration(duration)
+        ))),
+    }
+}
+
+/// Awaits the RPC call `fut` and records how long it took under its `method`,
+/// whether it succeeded or not
+pub async fn timed<Fut>(metrics: &Metrics, method: &str, fut: Fut) -> Fut::Output
+where
+    Fut: Future,
+{
+    let started_at = Instant::now();
+    let output = fut.await;
+    metrics.observe_rpc_call(method, started_at.elapsed());
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ErrorClass;
+    use async_std::task;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    #[test]
+    fn it_respects_the_concurrency_limit() {
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let results = task::block_on(map_bounded(0..20, 4, |i| {
+            let running = running.clone();
+            let peak = peak.clone();
+            async move {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                task::sleep(Duration::from_millis(10)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                i * 2
+            }
+        }));
+
+        assert_eq!(peak.load(Ordering::SeqCst), 4);
+        assert_eq!(running.load(Ordering::SeqCst), 0);
+        assert_eq!(results, (0..20).map(|i| i * 2).collect::<Vec<i32>>());
+    }
+
+    #[test]
+    fn it_times_out_slow_futures() {
+        let slow = async {
+            task::sleep(Duration::from_secs(5)).await;
+            Ok::<u32, ScoutyError>(1)
+        };
+        let err =
+            task::block_on(with_timeout(Duration::from_millis(50), "Request", slow))
+                .unwrap_err();
+        assert_eq!(err.class(), ErrorClass::Transient);
+        assert_eq!(
+            format!("{}", err),
+            "Timeout error: Request did not complete within 50ms"
+        );
+
+        let fast = async { Ok::<u32, ScoutyError>(1) };
+        let result =
+            task::block_on(with_timeout(Duration::from_secs(1), "Request", fast));
+        assert_eq!(result.unwrap(), 1);
+
+        // A zero duration waits for the future to complete
+        let slow = async {
+            task::sleep(Duration::from_millis(50)).await;
+            Ok::<u32, ScoutyError>(2)
+        };
+        let result =
+            task::block_on(with_timeout(Duration::from_secs(0), "Request", slow));
+        assert_eq!(result.unwrap(), 2);
+    }
+
+    #[test]
+    fn it_records_the_duration_of_the_rpc_calls() {
+        let metrics = Metrics::default();
+        let call = async {
+            task::sleep(Duration::from_millis(20)).await;
+            Err::<u32, ScoutyError>(ScoutyError::Other("mocked failure".into()))
+        };
+        let result = task::block_on(timed(&metrics, "system_chain", call));
+        assert!(result.is_err());
+
+        let histogram = metrics.rpc_call_duration("system_chain").unwrap();
+        assert_eq!(histogram.count(), 1);
+        assert!(metrics.rpc_call_duration("system_name").is_none());
+
+        let output = metrics.render();
+        assert!(output.contains(
+            "scouty_rpc_call_duration_seconds_bucket{method=\"system_chain\",le=\"0.01\"} 0\n"
+        ));
+        assert!(output.contains(
+            "scouty_rpc_call_duration_seconds_bucket{method=\"system_chain\",le=\"+Inf\"} 1\n"
+        ));
+        assert!(output.contains(
+            "scouty_rpc_call_duration_seconds_count{method=\"system_chain\"} 1\n"
+        ));
+    }
+}
@@ -29,41 +29,391 @@
 // the operative system.
 //
 // Set Config struct into a CONFIG lazy_static to avoid multiple processing.
+// The CONFIG is wrapped in an ArcSwap so that it can be reloaded on SIGHUP.
 //
-use clap::{App, Arg};
+use crate::errors::{ConfigError, EXIT_CONFIG_ERROR, EXIT_SUCCESS};
+use crate::hooks::{
+    HOOK_DEMOCRACY_STARTED, HOOK_INIT, HOOK_NEW_ERA, HOOK_NEW_SESSION, HOOK_STASH_SLASHED,
+    HOOK_UNCLAIMED_REWARDS, HOOK_VALIDATOR_CHILLED, HOOK_VALIDATOR_COMMISSION_CHANGED,
+    HOOK_VALIDATOR_OFFLINE, HOOK_VALIDATOR_SLASHED, HOOK_VALIDATOR_STARTS_ACTIVE_NEXT_ERA,
+    HOOK_VALIDATOR_STARTS_INACTIVE_NEXT_ERA,
+};
+use crate::report::escape_html;
+use crate::templates::Templates;
+use crate::vault::Vault;
+use arc_swap::ArcSwap;
+use chrono_tz::Tz;
+use clap::{App, Arg, SubCommand};
 use dotenv;
 use lazy_static::lazy_static;
-use log::info;
-use serde::Deserialize;
-use std::env;
+use log::{error, info, warn, LevelFilter};
+use serde::{
+    de::{
+        value::{MapDeserializer, SeqDeserializer},
+        IntoDeserializer, Visitor,
+    },
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use signal_hook::{consts::SIGHUP, iterator::Signals};
+use std::{
+    collections::HashSet,
+    env,
+    ffi::OsStr,
+    fs,
+    net::SocketAddr,
+    path::Path,
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+use subxt::{sp_core::crypto::Ss58Codec, sp_runtime::AccountId32};
+use url::{Host, Url};
 
 // Set Config struct into a CONFIG lazy_static to avoid multiple processing
 lazy_static! {
-    pub static ref CONFIG: Config = get_config();
+    pub static ref CONFIG: ArcSwap<Config> = ArcSwap::from_pointee(get_config());
+    static ref RELOAD_SOURCE: Mutex<ReloadSource> = Mutex::new(ReloadSource::default());
+}
+
+/// Everything needed to load the configuration again on SIGHUP with the same
+/// precedence as at startup: CLI flags, then env vars and then the config file
+#[derive(Clone, Debug, Default)]
+struct ReloadSource {
+    // config file loaded at startup
+    config_path: Option<String>,
+    // SCOUTY_* env vars defined before loading the config file
+    initial_env: Vec<(String, String)>,
+    // SCOUTY_* env vars set from CLI flags and arguments
+    cli_env: Vec<(String, String)>,
 }
 
 /// provides default value for interval if SCOUTY_INTERVAL env var is not set
-fn default_interval() -> u64 {
-    21600
+fn default_interval() -> Duration {
+    Duration::from_secs(21600)
 }
 
 /// provides default value for error interval if SCOUTY_ERROR_INTERVAL env var is not set
-fn default_error_interval() -> u64 {
+fn default_error_interval() -> Duration {
+    Duration::from_secs(30 * 60)
+}
+
+/// provides default value for the max error interval if SCOUTY_ERROR_INTERVAL_MAX env var is not set
+fn default_error_interval_max() -> Duration {
+    Duration::from_secs(6 * 3600)
+}
+
+fn default_endpoint_promotion_interval() -> Duration {
+    Duration::from_secs(600)
+}
+
+/// provides default value for hook timeout if SCOUTY_HOOK_TIMEOUT_SECONDS env var is not set
+fn default_hook_timeout_seconds() -> u64 {
+    60
+}
+
+/// provides default value for connect timeout if SCOUTY_CONNECT_TIMEOUT_SECONDS env var is not set
+fn default_connect_timeout_seconds() -> u64 {
     30
 }
 
-#[derive(Clone, Deserialize, Debug)]
+/// provides default value for request timeout if SCOUTY_REQUEST_TIMEOUT_SECONDS env var is not set
+fn default_request_timeout_seconds() -> u64 {
+    60
+}
+
+/// provides default value for rewards lookback eras if SCOUTY_REWARDS_LOOKBACK_ERAS env var is not set
+fn default_rewards_lookback_eras() -> u32 {
+    14
+}
+
+fn default_smtp_port() -> u16 {
+    587
+}
+
+/// provides default value for max concurrent queries if SCOUTY_MAX_CONCURRENT_QUERIES env var is not set
+fn default_max_concurrent_queries() -> usize {
+    4
+}
+
+/// provides default value for log file max files if SCOUTY_LOG_FILE_MAX_FILES env var is not set
+fn default_log_file_max_files() -> usize {
+    5
+}
+
+/// Parse a duration like `6h`, `30m` or `1h 30m`. For backwards compatibility a
+/// bare integer is read in the given unit of seconds, e.g. 60 for minutes.
+fn parse_duration(value: &str, unit_secs: u64) -> Result<Duration, String> {
+    let value = value.trim();
+    if let Ok(n) = value.parse::<u64>() {
+        return Ok(Duration::from_secs(n * unit_secs));
+    }
+    humantime::parse_duration(value)
+        .map_err(|e| format!("invalid duration '{}': {}", value, e))
+}
+
+/// Format a duration for user-facing logs, e.g. 21600 seconds as `6h`.
+/// Sub-second precision is dropped to keep the output readable.
+pub fn format_duration(duration: Duration) -> String {
+    humantime::format_duration(Duration::from_secs(duration.as_secs())).to_string()
+}
+
+/// A value given either as a string, like every SCOUTY_* var, or as a native
+/// TOML value, e.g. `interval = 3600` or `enabled_events = ["slash", "chill"]`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StringOr<T> {
+    String(String),
+    Value(T),
+}
+
+/// Deserialize a string or a number as a string
+fn deserialize_string_or_number<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match StringOr::<u64>::deserialize(deserializer)? {
+        StringOr::String(value) => Ok(value),
+        StringOr::Value(value) => Ok(value.to_string()),
+    }
+}
+
+/// Deserialize a comma-separated string or a list of strings
+fn deserialize_string_list<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match StringOr::<Vec<String>>::deserialize(deserializer)? {
+        StringOr::String(value) => Ok(value.split(',').filter_map(non_empty).collect()),
+        StringOr::Value(values) => Ok(values),
+    }
+}
+
+/// Deserialize a duration where bare integers are seconds
+fn deserialize_duration_secs<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = deserialize_string_or_number(deserializer)?;
+    parse_duration(&value, 1).map_err(serde::de::Error::custom)
+}
+
+/// Deserialize a comma-separated list of events, unknown events are rejected
+fn deserialize_event_kinds<'de, D>(
+    deserializer: D,
+) -> Result<HashSet<EventKind>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_string_list(deserializer)?
+        .into_iter()
+        .map(|name| {
+            name.parse::<EventKind>().map_err(|_| {
+                serde::de::Error::custom(format!(
+                    "unknown event '{}', expected one of {}",
+                    name,
+                    EventKind::ALL
+                        .iter()
+                        .map(|kind| kind.name())
+                        .collect::<Vec<&str>>()
+                        .join(", ")
+                ))
+            })
+        })
+        .collect()
+}
+
+/// Deserialize a duration where bare integers are minutes
+fn deserialize_duration_mins<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = deserialize_string_or_number(deserializer)?;
+    parse_duration(&value, 60).map_err(serde::de::Error::custom)
+}
+
+/// Serialize a duration as accepted back by `parse_duration`, e.g. 6h
+fn serialize_duration<S>(value: &Duration, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&humantime::format_duration(*value).to_string())
+}
+
+fn serialize_optional_log_level<S>(
+    value: &Option<LevelFilter>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match value {
+        Some(value) => serializer.serialize_str(&value.to_string().to_lowercase()),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Parse one of the log levels error, warn, info, debug or trace
+fn parse_log_level(value: &str) -> Result<LevelFilter, String> {
+    match value.to_lowercase().as_str() {
+        "error" => Ok(LevelFilter::Error),
+        "warn" => Ok(LevelFilter::Warn),
+        "info" => Ok(LevelFilter::Info),
+        "debug" => Ok(LevelFilter::Debug),
+        "trace" => Ok(LevelFilter::Trace),
+        _ => Err(format!(
+            "invalid log level '{}', expected one of error, warn, info, debug or trace",
+            value
+        )),
+    }
+}
+
+fn deserialize_optional_log_level<'de, D>(
+    deserializer: D,
+) -> Result<Option<LevelFilter>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    parse_log_level(&value)
+        .map(Some)
+        .map_err(serde::de::Error::custom)
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
 pub struct Config {
-    #[serde(default = "default_interval")]
-    pub interval: u64,
-    #[serde(default = "default_error_interval")]
-    pub error_interval: u64,
+    // intervals accept durations like 6h or 30m, bare integers are in seconds
+    #[serde(
+        default = "default_interval",
+        deserialize_with = "deserialize_duration_secs",
+        serialize_with = "serialize_duration"
+    )]
+    pub interval: Duration,
+    // error interval accepts durations like 30m, bare integers are in minutes
+    #[serde(
+        default = "default_error_interval",
+        deserialize_with = "deserialize_duration_mins",
+        serialize_with = "serialize_duration"
+    )]
+    pub error_interval: Duration,
+    // the error interval doubles on every consecutive error up to this max
+    #[serde(
+        default = "default_error_interval_max",
+        deserialize_with = "deserialize_duration_mins",
+        serialize_with = "serialize_duration"
+    )]
+    pub error_interval_max: Duration,
+    // consecutive restarts after critical errors before giving up
+    #[serde(default)]
+    pub max_restarts: Option<u64>,
+    #[serde(default)]
     pub substrate_ws_url: String,
-    pub stashes: Vec<String>,
+    #[serde(default)]
+    pub substrate_ws_urls: Vec<String>,
+    // endpoints sorted by priority, the urls above are kept in the same order
+    #[serde(skip)]
+    pub substrate_ws_endpoints: Vec<Endpoint>,
+    // a connection to a lower priority endpoint periodically checks if a higher
+    // priority one is available again, zero means never
+    #[serde(
+        default = "default_endpoint_promotion_interval",
+        deserialize_with = "deserialize_duration_secs",
+        serialize_with = "serialize_duration"
+    )]
+    pub endpoint_promotion_interval: Duration,
+    // SHA-256 fingerprint of the substrate node TLS certificate
+    #[serde(default)]
+    pub substrate_ws_tls_fingerprint: Option<String>,
+    // proxy for the substrate connection, takes precedence over https_proxy,
+    // http_proxy and all_proxy
+    #[serde(default)]
+    pub substrate_ws_proxy: Option<String>,
+    // name 'scouty' identifies itself with to the substrate connection
+    #[serde(default)]
+    pub rpc_client_name: Option<String>,
+    // connection timeout in seconds, zero means no timeout
+    #[serde(default = "default_connect_timeout_seconds")]
+    pub connect_timeout_seconds: u64,
+    // RPC request timeout in seconds, zero means no timeout
+    #[serde(default = "default_request_timeout_seconds")]
+    pub request_timeout_seconds: u64,
+    // delay before the first connection attempt, e.g. for a local node to start
+    #[serde(default)]
+    pub startup_delay_seconds: u64,
+    // blocks on top of a block before its events are acted on, with head_mode=latest
+    #[serde(default)]
+    pub confirmation_blocks: u32,
+    // head of the chain the on-chain events are subscribed to
+    #[serde(default)]
+    pub head_mode: HeadMode,
+    #[serde(default)]
+    pub chain: Option<ChainPreset>,
+    // overrides the SS58 prefix of the chain preset, e.g. for custom chains
+    #[serde(default)]
+    pub ss58_prefix: Option<u16>,
+    // override the token of the chain, used to format the balances
+    #[serde(default)]
+    pub token_decimals: Option<u8>,
+    #[serde(default)]
+    pub token_symbol: Option<String>,
+    // overrides the block explorer of the chain preset used in the links
+    #[serde(default)]
+    pub explorer_base_url: Option<String>,
+    // IANA timezone the timestamps are rendered in the notifications, UTC if not
+    // defined
+    #[serde(default)]
+    pub timezone: Option<String>,
+    #[serde(default)]
+    pub stashes: Vec<StashConfig>,
+    // file with one stash per line, merged with the stashes defined inline
+    #[serde(default)]
+    pub stashes_path: Option<String>,
+    // safety limit for the number of stashes monitored
+    #[serde(default)]
+    pub max_stashes: Option<usize>,
     #[serde(default)]
     pub is_debug: bool,
+    // only warnings and errors are logged, takes precedence over is_debug
+    #[serde(default)]
+    pub quiet: bool,
+    // log level takes precedence over quiet and is_debug
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_log_level",
+        serialize_with = "serialize_optional_log_level"
+    )]
+    pub log_level: Option<LevelFilter>,
+    #[serde(default)]
+    pub log_format: LogFormat,
+    #[serde(default)]
+    pub no_color: bool,
+    // log lines are written to the file instead of stderr
+    #[serde(default)]
+    pub log_file_path: Option<String>,
+    // size in MB at which the log file is rotated, 0 is never
+    #[serde(default)]
+    pub log_file_max_size_mb: u64,
+    // rotated log files kept, the oldest one is removed
+    #[serde(default = "default_log_file_max_files")]
+    pub log_file_max_files: usize,
     #[serde(default)]
     pub is_short: bool,
+    #[serde(default)]
+    pub is_dry_run: bool,
+    // a single monitoring pass, e.g. when scheduled by cron
+    #[serde(default)]
+    pub run_once: bool,
+    #[serde(default)]
+    pub command: Command,
+    // prints the effective config in the given format and exits
+    #[serde(default, skip_serializing)]
+    pub print_config: Option<ConfigFormat>,
+    // format of the example printed by the config-example command, env if not
+    // defined
+    #[serde(default, skip_serializing)]
+    pub config_example_format: Option<ConfigFormat>,
+    // address given to the decode command
+    #[serde(default, skip_serializing)]
+    pub decode_address: Option<String>,
     // hooks configuration
     #[serde(default)]
     pub hook_init_path: String,
@@ -75,7 +425,7 @@ pub struct Config {
     pub hook_validator_starts_active_next_era_path: String,
     #[serde(default)]
     pub hook_validator_starts_inactive_next_era_path: String,
-    #[serde(default)]
+    #[serde(default, alias = "hook_chilled_path")]
     pub hook_validator_chilled_path: String,
     #[serde(default)]
     pub hook_validator_slashed_path: String,
@@ -83,17 +433,170 @@ pub struct Config {
     pub hook_validator_offline_path: String,
     #[serde(default)]
     pub hook_democracy_started_path: String,
+    #[serde(default)]
+    pub hook_validator_commission_changed_path: String,
+    #[serde(default)]
+    pub hook_slash_path: String,
+    // hook timeout in seconds, zero means no timeout
+    #[serde(default = "default_hook_timeout_seconds")]
+    pub hook_timeout_seconds: u64,
+    // hooks run in the background instead of blocking the monitoring loop
+    #[serde(default)]
+    pub hooks_async: bool,
+    // a hook exiting non-zero stops 'scouty' instead of restarting the subscription
+    #[serde(default)]
+    pub hook_failure_fatal: bool,
+    // static args appended to every hook invocation, split as shell words
+    #[serde(default)]
+    pub hook_extra_args: Option<String>,
+    // disable hooks without clearing their paths
+    #[serde(default)]
+    pub hook_new_session_disabled: bool,
+    #[serde(default)]
+    pub hook_active_next_era_disabled: bool,
+    #[serde(default)]
+    pub hook_inactive_next_era_disabled: bool,
+    // commission changes are checked every new era
+    #[serde(default)]
+    pub commission_monitoring_disabled: bool,
+    // nominators count and total stake shifts are checked every new era, 0 is off
+    #[serde(default)]
+    pub stake_change_threshold_percent: u32,
+    // slashes of the stashes defined are notified bypassing the rate limit
+    #[serde(default)]
+    pub slash_monitoring_disabled: bool,
+    #[serde(default)]
+    pub hook_unclaimed_rewards_path: String,
+    // unclaimed rewards are checked every new era within the lookback eras
+    #[serde(default)]
+    pub rewards_monitoring_disabled: bool,
+    #[serde(default = "default_rewards_lookback_eras")]
+    pub rewards_lookback_eras: u32,
+    // chilled and disabled stashes are notified
+    #[serde(default)]
+    pub chill_monitoring_disabled: bool,
+    // only the events listed are monitored, all of them if empty
+    #[serde(default, deserialize_with = "deserialize_event_kinds")]
+    pub enabled_events: HashSet<EventKind>,
+    // TOML file of named message templates, the built-in defaults are used for
+    // the templates not defined
+    #[serde(default)]
+    pub message_templates_path: Option<String>,
     // matrix configuration
     #[serde(default)]
     pub matrix_user: String,
     #[serde(default)]
     pub matrix_bot_user: String,
     #[serde(default)]
-    pub matrix_bot_password: String,
+    pub matrix_bot_password: Secret,
+    #[serde(default)]
+    pub matrix_bot_password_path: Option<String>,
+    #[serde(default)]
+    pub matrix_bot_password_vault_path: Option<String>,
     #[serde(default)]
     pub matrix_disabled: bool,
     #[serde(default)]
     pub matrix_bot_display_name_disabled: bool,
+    // overrides the matrix.org homeserver
+    #[serde(default)]
+    pub matrix_homeserver_url: Option<String>,
+    #[serde(default)]
+    pub matrix_format: MatrixFormat,
+    // rooms the messages are sent to filtered by event, if empty the messages
+    // are sent to the private room
+    #[serde(default)]
+    pub matrix_rooms: Vec<MatrixRoom>,
+    // telegram configuration
+    #[serde(default)]
+    pub telegram_bot_token: Secret,
+    #[serde(default)]
+    pub telegram_bot_token_vault_path: Option<String>,
+    #[serde(default)]
+    pub telegram_chat_id: String,
+    #[serde(default)]
+    pub telegram_disabled: bool,
+    // heartbeat notification, 0 is off
+    #[serde(default)]
+    pub heartbeat_interval_hours: u64,
+    // include the peer count and sync state of the node in the heartbeat
+    #[serde(default)]
+    pub heartbeat_node_health: bool,
+    // daily summary of the activity of each stash instead of per event only
+    #[serde(default)]
+    pub daily_report_enabled: bool,
+    // hour of the day (0-23) the daily report is sent, in the configured timezone
+    #[serde(default)]
+    pub daily_report_hour: u32,
+    // notifications rate limit, 0 is unlimited
+    #[serde(default)]
+    pub notification_rate_limit_per_minute: u64,
+    // identical consecutive notifications are suppressed within the window
+    #[serde(default)]
+    pub notification_dedup_window_seconds: u64,
+    // text around every notification, e.g. to tell apart multiple instances
+    #[serde(default)]
+    pub notification_prefix: Option<String>,
+    #[serde(default)]
+    pub notification_suffix: Option<String>,
+    // session notifications are only sent when the active state of a stash changes
+    #[serde(default)]
+    pub notify_on_change_only: bool,
+    // the first monitoring cycle after start up only records the state observed
+    #[serde(default)]
+    pub suppress_startup_notifications: bool,
+    // file where the last state observed of each stash is kept across restarts
+    #[serde(default)]
+    pub state_file_path: Option<String>,
+    // smtp configuration
+    #[serde(default)]
+    pub smtp_host: String,
+    #[serde(default = "default_smtp_port")]
+    pub smtp_port: u16,
+    #[serde(default)]
+    pub smtp_user: String,
+    #[serde(default)]
+    pub smtp_password: Secret,
+    #[serde(default)]
+    pub smtp_password_vault_path: Option<String>,
+    #[serde(default)]
+    pub smtp_from: String,
+    #[serde(default)]
+    pub smtp_to: Vec<String>,
+    #[serde(default)]
+    pub smtp_disabled: bool,
+    // webhook configuration
+    #[serde(default)]
+    pub webhook_url: String,
+    #[serde(default)]
+    pub webhook_disabled: bool,
+    // pagerduty configuration
+    #[serde(default)]
+    pub pagerduty_routing_key: Option<Secret>,
+    #[serde(default)]
+    pub pagerduty_routing_key_vault_path: Option<String>,
+    #[serde(default)]
+    pub pagerduty_disabled: bool,
+    // hashicorp vault, the secrets still empty with a vault path defined are read
+    // from the KV v2 engine
+    #[serde(default)]
+    pub vault_addr: Option<String>,
+    #[serde(default)]
+    pub vault_token: Secret,
+    // prometheus metrics
+    #[serde(default)]
+    pub prometheus_listen_addr: Option<String>,
+    // healthcheck liveness probe
+    #[serde(default)]
+    pub healthcheck_listen_addr: Option<String>,
+    // read-only JSON API, a port alone binds the loopback interface
+    #[serde(default)]
+    pub api_listen_addr: Option<String>,
+    // runtime version check
+    #[serde(default)]
+    pub expected_spec_version: Option<u32>,
+    // per-stash chain queries running at the same time
+    #[serde(default = "default_max_concurrent_queries")]
+    pub max_concurrent_queries: usize,
     // chain settings exposure
     #[serde(default)]
     pub expose_network: bool,
@@ -109,141 +612,2686 @@ pub struct Config {
     pub expose_era_points: bool,
     #[serde(default)]
     pub expose_all: bool,
+    // warnings raised while loading, logged once the logger is initialized
+    #[serde(skip)]
+    pub load_warnings: Vec<String>,
 }
 
-/// Inject dotenv and env vars into the Config struct
-fn get_config() -> Config {
-    // Define CLI flags with clap
-    let matches = App::new(env!("CARGO_PKG_NAME"))
-    .version(env!("CARGO_PKG_VERSION"))
-    .author(env!("CARGO_PKG_AUTHORS"))
-    .about(env!("CARGO_PKG_DESCRIPTION"))
-    .arg(
-      Arg::with_name("CHAIN")
-          .index(1)
-          .possible_values(&["westend", "kusama", "polkadot"])
-          .help(
-            "Sets the substrate-based chain for which 'scouty' will try to connect",
-          )
-    )
-    .arg(
-      Arg::with_name("debug")
-        .long("debug")
-        .help("Prints debug information verbosely."))
-    .arg(
-      Arg::with_name("matrix-user")
-        .long("matrix-user")
-        .takes_value(true)
-        .help("Your regular matrix user. e.g. '@your-regular-matrix-account:matrix.org' this user account will receive notifications from your other 'Scouty Bot' matrix account."))
-    .arg(
-          Arg::with_name("matrix-bot-user")
-            .long("matrix-bot-user")
-            .takes_value(true)
-            .help("Your new 'Scouty Bot' matrix user. e.g. '@your-own-scouty-bot-account:matrix.org' this user account will be your 'Scouty Bot' which will be responsible to send messages/notifications to your private 'Scouty Bot' room."))
-    .arg(
-      Arg::with_name("matrix-bot-password")
-        .long("matrix-bot-password")
-        .takes_value(true)
-        .help("Password for the 'Scouty Bot' matrix user sign in."))
-    .arg(
-      Arg::with_name("disable-matrix")
-        .long("disable-matrix")
-        .help(
-          "Disable matrix bot for 'scouty'. (e.g. with this flag active 'scouty' will not send messages/notifications to your private 'Scouty Bot' room) (https://matrix.org/)",
-        ),
-    )
-    .arg(
-      Arg::with_name("disable-matrix-bot-display-name")
-        .long("disable-matrix-bot-display-name")
-        .help(
-          "Disable matrix bot display name update for 'scouty'. (e.g. with this flag active 'scouty' will not change the matrix bot user display name)",
-        ),
-      )
-    .arg(
-      Arg::with_name("short")
-        .long("short")
-        .help("Display only essential information (e.g. with this flag active 'scouty' will hide certain sections in a message)"))
-    .arg(
-      Arg::with_name("error-interval")
-        .long("error-interval")
-        .takes_value(true)
-        .default_value("30")
-        .help("Interval value (in minutes) from which 'scouty' will restart again in case of a critical error."))
-    .arg(
-      Arg::with_name("stashes")
-        .short("s")
-        .long("stashes")
-        .takes_value(true)
-        .help(
-          "Validator stash addresses for which 'scouty' will take a particular eye. If needed specify more than one (e.g. stash_1,stash_2,stash_3).",
-        ),
-    )
-    .arg(
-      Arg::with_name("substrate-ws-url")
-        .short("w")
-        .long("substrate-ws-url")
-        .takes_value(true)
-        .help(
-          "Substrate websocket endpoint for which 'scouty' will try to connect. (e.g. wss://kusama-rpc.polkadot.io) (NOTE: substrate_ws_url takes precedence than <CHAIN> argument)",
-        ),
-    )
-    .arg(
-      Arg::with_name("config-path")
-        .short("c")
-        .long("config-path")
-        .takes_value(true)
-        .value_name("FILE")
-        .default_value(".env")
-        .help(
-          "Sets a custom config file path. The config file contains 'scouty' configuration variables.",
-        ),
-    )
-    .arg(
-      Arg::with_name("expose-network")
-        .long("expose-network")
-        .help(
-          "Expose the network name, token symbol and token decimal under new positional arguments for each hook.",
-        ),
-      )
-    .arg(
-      Arg::with_name("expose-nominators")
-        .long("expose-nominators")
-        .help(
-          "Expose ACTIVE nominator details under new positional arguments for some of the hooks. Note: `scouty` only look after active nominators for each validator stash predefined.",
-        ),
-      )
-    .arg(
-        Arg::with_name("expose-authored-blocks")
-          .long("expose-authored-blocks")
-          .help(
-            "Expose the number of blocks authored by each validator stash predefined.",
-          ),
-        )
-    .arg(
-      Arg::with_name("expose-all-nominators")
-        .long("expose-all-nominators")
-        .help(
-          "Expose ALL nominator details under new positional arguments for some of the hooks. Note: `scouty` only look after all nominators for each validator stash predefined.",
-        ),
-      )
-    .arg(
-      Arg::with_name("expose-para-validator")
-        .long("expose-para-validator")
-        .help(
-          "Expose the para validator details under new positional arguments for some of the hooks.",
-        ),
-      )
-    .arg(
-      Arg::with_name("expose-era-points")
-        .long("expose-era-points")
-        .help(
-          "Expose the era points details under new positional arguments for the `_new_era` hook.",
-        ),
-      )
-    .arg(
-      Arg::with_name("expose-all")
-        .long("expose-all")
-        .help(
+impl Config {
+    /// Deserializes the configuration from SCOUTY_* env vars only, without
+    /// parsing any CLI flags or loading any config file
+    pub fn from_env() -> Result<Config, envy::Error> {
+        Config::from_vars(scouty_env_vars())
+    }
+
+    /// Deserializes the configuration from the given SCOUTY_* vars
+    pub fn from_vars(vars: Vec<(String, String)>) -> Result<Config, envy::Error> {
+        let config = envy::prefixed("SCOUTY_").from_iter::<_, Config>(vars)?;
+        Ok(config
+            .with_folded_substrate_ws_urls()
+            .with_no_color_env()
+            .without_empty_stashes())
+    }
+
+    /// Deserializes the configuration from the table of a TOML config file with
+    /// the given SCOUTY_* vars on top, a var takes precedence over the same key
+    /// defined in the table
+    pub fn from_toml_and_vars(
+        table: toml::value::Table,
+        vars: Vec<(String, String)>,
+    ) -> Result<Config, toml::de::Error> {
+        let vars: Vec<(String, ConfigValue)> = vars
+            .into_iter()
+            .filter_map(|(key, value)| {
+                let field = key.strip_prefix("SCOUTY_")?.to_lowercase();
+                Some((field, ConfigValue::Var(value)))
+            })
+            .collect();
+        let mut values: Vec<(String, ConfigValue)> = table
+            .into_iter()
+            .filter(|(key, _)| !vars.iter().any(|(field, _)| field == key))
+            .map(|(key, value)| (key, ConfigValue::Toml(value)))
+            .collect();
+        values.extend(vars);
+        let config = Config::deserialize(MapDeserializer::<_, toml::de::Error>::new(
+            values.into_iter(),
+        ))?;
+        Ok(config
+            .with_folded_substrate_ws_urls()
+            .with_no_color_env()
+            .without_empty_stashes())
+    }
+
+    /// Read the secrets defined by a file path, the file content takes precedence
+    /// over the secret defined inline. Secrets still empty are read from the
+    /// $CREDENTIALS_DIRECTORY set by systemd, if available.
+    fn with_secret_files(self) -> Result<Config, ConfigError> {
+        let credentials_directory = env::var_os("CREDENTIALS_DIRECTORY");
+        self.with_secret_files_from(credentials_directory.as_deref().map(Path::new))
+    }
+
+    /// Read the secrets defined by a file path, the secrets still empty are read
+    /// from the credentials directory given, if any
+    fn with_secret_files_from(
+        mut self,
+        credentials_directory: Option<&Path>,
+    ) -> Result<Config, ConfigError> {
+        if let Some(path) = &self.matrix_bot_password_path {
+            if !self.matrix_bot_password.is_empty() {
+                self.load_warnings.push(format!(
+                    "Both matrix bot password and matrix bot password path are defined, the password from {} is used",
+                    path
+                ));
+            }
+            self.matrix_bot_password = read_secret_file(path)?;
+        }
+        // Fallback to the systemd credentials directory (LoadCredential=)
+        if let Some(credentials_directory) = credentials_directory {
+            if self.matrix_bot_password.is_empty() {
+                let path = credentials_directory.join("matrix_bot_password");
+                if path.exists() {
+                    self.matrix_bot_password = read_secret_file(&path.to_string_lossy())?;
+                }
+            }
+            if self.telegram_bot_token.is_empty() {
+                let path = credentials_directory.join("telegram_bot_token");
+                if path.exists() {
+                    self.telegram_bot_token = read_secret_file(&path.to_string_lossy())?;
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    /// Read the secrets still empty from the HashiCorp Vault KV v2 engine, for the
+    /// ones with a vault path defined. A secret that can not be read is an error,
+    /// instead of starting with the secret empty.
+    fn with_vault_secrets(mut self) -> Result<Config, ConfigError> {
+        // The validate command never opens a network connection
+        if self.command == Command::Validate {
+            return Ok(self);
+        }
+        if self.matrix_bot_password.is_empty() {
+            if let Some(path) = &self.matrix_bot_password_vault_path {
+                self.matrix_bot_password =
+                    self.read_vault_secret(path, "matrix_bot_password")?;
+            }
+        }
+        if self.telegram_bot_token.is_empty() {
+            if let Some(path) = &self.telegram_bot_token_vault_path {
+                self.telegram_bot_token =
+                    self.read_vault_secret(path, "telegram_bot_token")?;
+            }
+        }
+        if self.smtp_password.is_empty() {
+            if let Some(path) = &self.smtp_password_vault_path {
+                self.smtp_password = self.read_vault_secret(path, "smtp_password")?;
+            }
+        }
+        if self
+            .pagerduty_routing_key
+            .as_ref()
+            .map_or(true, |key| key.is_empty())
+        {
+            if let Some(path) = &self.pagerduty_routing_key_vault_path {
+                self.pagerduty_routing_key =
+                    Some(self.read_vault_secret(path, "pagerduty_routing_key")?);
+            }
+        }
+        Ok(self)
+    }
+
+    /// The vault address and token fallback to the VAULT_ADDR and VAULT_TOKEN env
+    /// vars used by the vault CLI
+    fn read_vault_secret(
+        &self,
+        path: &str,
+        default_key: &str,
+    ) -> Result<Secret, ConfigError> {
+        let addr = self
+            .vault_addr
+            .clone()
+            .or_else(|| env::var("VAULT_ADDR").ok())
+            .unwrap_or_default();
+        if addr.is_empty() {
+            return Err(ConfigError::Other(format!(
+                "Vault path {} is defined but not the vault address, please define it with the --vault-addr flag or the SCOUTY_VAULT_ADDR env var",
+                path
+            )));
+        }
+        let token = if self.vault_token.is_empty() {
+            Secret::from(env::var("VAULT_TOKEN").unwrap_or_default())
+        } else {
+            self.vault_token.clone()
+        };
+        if token.is_empty() {
+            return Err(ConfigError::Other(format!(
+                "Vault path {} is defined but not the vault token, please define it with the --vault-token flag or the SCOUTY_VAULT_TOKEN env var",
+                path
+            )));
+        }
+        async_std::task::block_on(Vault::new(&addr, token).read(path, default_key))
+            .map_err(|e| {
+                ConfigError::Other(format!(
+                    "Vault secret {} could not be read: {}",
+                    path, e
+                ))
+            })
+    }
+
+    /// Colors are also disabled by a non-empty NO_COLOR env var (https://no-color.org/)
+    fn with_no_color_env(self) -> Config {
+        let no_color = env::var_os("NO_COLOR");
+        self.with_no_color_from(no_color.as_deref())
+    }
+
+    /// Colors are disabled by the NO_COLOR value given, if not empty
+    fn with_no_color_from(mut self, no_color: Option<&OsStr>) -> Config {
+        if no_color.map_or(false, |value| !value.is_empty()) {
+            self.no_color = true;
+        }
+        self
+    }
+
+    /// Read the stashes defined by a file path and merge them after the stashes
+    /// defined inline, duplicated addresses are only kept once
+    fn with_stashes_file(mut self) -> Result<Config, ConfigError> {
+        if let Some(path) = &self.stashes_path {
+            let content = fs::read_to_string(path).map_err(|e| {
+                ConfigError::Other(format!(
+                    "Stashes file {} could not be read: {}",
+                    path, e
+                ))
+            })?;
+            self.stashes = merge_stashes(&self.stashes, &parse_stashes_file(&content));
+        }
+        Ok(self)
+    }
+
+    /// Warn if any of the config files loaded, the overlay files included, is
+    /// accessible by group or others
+    fn with_config_file_permissions_checked(mut self, config_files: &[String]) -> Config {
+        for config_file in config_files {
+            if let Some(warning) = check_config_file_permissions(config_file) {
+                self.load_warnings.push(warning);
+            }
+        }
+        self
+    }
+
+    /// Warn if both the <CHAIN> argument and an explicit substrate websocket url
+    /// are given, the url takes precedence over the chain preset endpoint
+    fn with_chain_ws_url_checked(
+        mut self,
+        chain: Option<&str>,
+        substrate_ws_url: Option<&str>,
+    ) -> Config {
+        if let (Some(chain), Some(substrate_ws_url)) = (
+            chain.and_then(|chain| chain.parse::<ChainPreset>().ok()),
+            substrate_ws_url,
+        ) {
+            self.load_warnings.push(format!(
+                "Both <CHAIN> {} and --substrate-ws-url are defined, {} is used instead of the {} endpoint {}",
+                chain,
+                substrate_ws_url,
+                chain,
+                chain.ws_url()
+            ));
+        }
+        self
+    }
+
+    /// Expand the $VAR and ${VAR} references in every hook path from the process
+    /// environment, including the hook paths defined per stash
+    fn with_hook_paths_expanded(mut self) -> Config {
+        for path in [
+            &mut self.hook_init_path,
+            &mut self.hook_new_session_path,
+            &mut self.hook_new_era_path,
+            &mut self.hook_validator_starts_active_next_era_path,
+            &mut self.hook_validator_starts_inactive_next_era_path,
+            &mut self.hook_validator_chilled_path,
+            &mut self.hook_validator_slashed_path,
+            &mut self.hook_validator_offline_path,
+            &mut self.hook_democracy_started_path,
+            &mut self.hook_validator_commission_changed_path,
+            &mut self.hook_slash_path,
+            &mut self.hook_unclaimed_rewards_path,
+        ] {
+            *path = expand_env_vars(path);
+        }
+        for stash in self.stashes.iter_mut() {
+            if let Some(path) = &stash.hook_active_next_era_path {
+                stash.hook_active_next_era_path = Some(expand_env_vars(path));
+            }
+        }
+        self
+    }
+
+    /// An empty SCOUTY_STASHES is the same as no stashes defined
+    fn without_empty_stashes(mut self) -> Config {
+        self.stashes.retain(|stash| !stash.address.is_empty());
+        self
+    }
+
+    /// Validate every substrate websocket url before any connection is attempted
+    fn with_substrate_ws_urls_validated(self) -> Result<Config, ConfigError> {
+        for url in self.substrate_ws_urls.iter() {
+            validate_ws_url(url)?;
+        }
+        Ok(self)
+    }
+
+    /// Finish loading the configuration deserialized, the same way at startup and
+    /// on reload: secrets and stashes read from files or vault, hook paths
+    /// expanded, endpoints validated and the config files permissions checked
+    fn finish_loading(self, config_files: &[String]) -> Result<Config, ConfigError> {
+        Ok(self
+            .with_secret_files()?
+            .with_vault_secrets()?
+            .with_stashes_file()?
+            .with_hook_paths_expanded()
+            .with_substrate_ws_urls_validated()?
+            .with_config_file_permissions_checked(config_files))
+    }
+
+    fn with_folded_substrate_ws_urls(mut self) -> Config {
+        self.substrate_ws_endpoints = sort_endpoints(&fold_substrate_ws_urls(
+            &self.substrate_ws_url,
+            &self.substrate_ws_urls,
+        ));
+        self.substrate_ws_urls = self
+            .substrate_ws_endpoints
+            .iter()
+            .map(|endpoint| endpoint.url.clone())
+            .collect();
+        self
+    }
+
+    /// Returns the effective config either as 'SCOUTY_' env vars or as TOML.
+    /// Secrets are redacted and values not defined are omitted.
+    pub fn print(&self, format: ConfigFormat) -> Result<String, ConfigError> {
+        match format {
+            ConfigFormat::Toml => {
+                toml::to_string(self).map_err(|e| ConfigError::Other(e.to_string()))
+            }
+            ConfigFormat::Env => {
+                let value = toml::Value::try_from(self)
+                    .map_err(|e| ConfigError::Other(e.to_string()))?;
+                let mut lines: Vec<String> = Vec::new();
+                if let toml::Value::Table(table) = value {
+                    for (key, value) in table.iter() {
+                        let value = match value {
+                            toml::Value::Array(values) => values
+                                .iter()
+                                .filter_map(toml_scalar_to_string)
+                                .collect::<Vec<String>>()
+                                .join(","),
+                            _ => toml_scalar_to_string(value).unwrap_or_default(),
+                        };
+                        lines.push(format!("SCOUTY_{}={}", key.to_uppercase(), value));
+                    }
+                }
+                Ok(lines.join("\n"))
+            }
+        }
+    }
+
+    /// Verifies the configuration values that can only be checked after loading,
+    /// the first problem found is returned
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        for warning in self.load_warnings.iter() {
+            warn!("{}", warning);
+        }
+        for warning in self.check_hooks() {
+            warn!("{}", warning);
+        }
+        match self.validation_errors().into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns every problem found by the `validate` command, including the hooks
+    /// that do not exist or are not executable
+    pub fn lint(&self) -> Vec<String> {
+        self.check_hooks()
+            .into_iter()
+            .chain(self.validation_errors().iter().map(|e| e.to_string()))
+            .collect()
+    }
+
+    /// Returns every configuration value that is not valid
+    fn validation_errors(&self) -> Vec<ConfigError> {
+        let mut errors: Vec<ConfigError> = Vec::new();
+        if let Some(prometheus_listen_addr) = &self.prometheus_listen_addr {
+            if prometheus_listen_addr.parse::<SocketAddr>().is_err() {
+                errors.push(ConfigError::Other(format!(
+                    "Invalid prometheus listen address '{}' (e.g. 0.0.0.0:9090)",
+                    prometheus_listen_addr
+                )));
+            }
+        }
+        if let Some(healthcheck_listen_addr) = &self.healthcheck_listen_addr {
+            if healthcheck_listen_addr.parse::<SocketAddr>().is_err() {
+                errors.push(ConfigError::Other(format!(
+                    "Invalid healthcheck listen address '{}' (e.g. 0.0.0.0:8080)",
+                    healthcheck_listen_addr
+                )));
+            }
+        }
+        if let Some(api_listen_addr) = self.api_bind_addr() {
+            if api_listen_addr.parse::<SocketAddr>().is_err() {
+                errors.push(ConfigError::Other(format!(
+                    "Invalid API listen address '{}' (e.g. 127.0.0.1:8081)",
+                    api_listen_addr
+                )));
+            }
+        }
+        if let Some(timezone) = &self.timezone {
+            if timezone.parse::<Tz>().is_err() {
+                errors.push(ConfigError::Other(format!(
+                    "Unknown timezone '{}', expected an IANA name (e.g. Europe/Lisbon)",
+                    timezone
+                )));
+            }
+        }
+        if self.daily_report_hour > 23 {
+            errors.push(ConfigError::Other(format!(
+                "Invalid daily report hour '{}', expected 0 to 23",
+                self.daily_report_hour
+            )));
+        }
+        if self.max_concurrent_queries == 0 {
+            errors.push(ConfigError::Other(
+                "Invalid max concurrent queries '0', it must be greater than zero".to_string(),
+            ));
+        }
+        if let Err(e) = Templates::load(self.message_templates_path.as_deref()) {
+            errors.push(e);
+        }
+        if let Some(hook_extra_args) = &self.hook_extra_args {
+            if let Err(e) = shell_words::split(hook_extra_args) {
+                errors.push(ConfigError::Other(format!(
+                    "Invalid hook extra args '{}': {}",
+                    hook_extra_args, e
+                )));
+            }
+        }
+        // The daemon does nothing useful without stashes, the view command may
+        // still print the session
+        if self.stashes.is_empty()
+            && matches!(self.command, Command::Daemon | Command::Validate)
+        {
+            errors.push(ConfigError::Other(
+                "no stashes defined, please define them with the --stashes or --stashes-path flags or the SCOUTY_STASHES or SCOUTY_STASHES_PATH env vars".to_string(),
+            ));
+        }
+        if self.substrate_ws_urls.is_empty()
+            && matches!(self.command, Command::Daemon | Command::View | Command::Validate)
+        {
+            errors.push(ConfigError::Other(
+                "no substrate websocket url defined, please define it with the --substrate-ws-url flag or the SCOUTY_SUBSTRATE_WS_URLS env var".to_string(),
+            ));
+        }
+        for room in self.matrix_rooms.iter() {
+            if !room.room_id.starts_with('!') {
+                errors.push(ConfigError::Other(format!(
+                    "Matrix room id {} is not valid, expected e.g. !yourRoomId:matrix.org",
+                    room.room_id
+                )));
+            }
+            if let Some(event) = room
+                .events
+                .iter()
+                .find(|event| !MATRIX_EVENTS.contains(&event.as_str()))
+            {
+                errors.push(ConfigError::Other(format!(
+                    "Matrix room {} event {} is not valid, expected one of {}",
+                    room.room_id,
+                    event,
+                    MATRIX_EVENTS.join(", ")
+                )));
+            }
+        }
+        if !self.matrix_disabled {
+            for (name, user_id) in [
+                ("user", &self.matrix_user),
+                ("bot user", &self.matrix_bot_user),
+            ] {
+                if !user_id.is_empty() && !is_matrix_user_id(user_id) {
+                    errors.push(ConfigError::Other(format!(
+                        "Matrix {} '{}' is not valid, expected @localpart:server e.g. @your-own-scouty-bot-account:matrix.org",
+                        name, user_id
+                    )));
+                }
+            }
+        }
+        if let Some(max_stashes) = self.max_stashes {
+            if self.stashes.len() > max_stashes {
+                errors.push(ConfigError::Other(format!(
+                    "{} stashes defined, more than the maximum of {} allowed by --max-stashes or SCOUTY_MAX_STASHES",
+                    self.stashes.len(),
+                    max_stashes
+                )));
+            }
+        }
+        let prefix = self.ss58_prefix();
+        if prefix.is_none() {
+            info!("SS58 prefix unknown for a custom chain, stash prefix check skipped (define it with --ss58-prefix or SCOUTY_SS58_PREFIX)");
+        }
+        if let Err(e) = validate_stashes(&self.stashes, prefix) {
+            errors.push(e);
+        }
+        errors
+    }
+
+    /// Returns the hour of the day the daily report is sent, if enabled
+    pub fn daily_report_hour(&self) -> Option<u32> {
+        if !self.daily_report_enabled {
+            return None;
+        }
+        Some(self.daily_report_hour)
+    }
+
+    /// Returns the timezone the notification timestamps are rendered in, UTC by
+    /// default or if the timezone defined is unknown
+    pub fn timezone(&self) -> Tz {
+        self.timezone
+            .as_deref()
+            .and_then(|timezone| timezone.parse::<Tz>().ok())
+            .unwrap_or(Tz::UTC)
+    }
+
+    /// Returns the SS58 prefix defined, which takes precedence over the prefix
+    /// of the chain preset. Custom chains have no prefix unless defined.
+    pub fn ss58_prefix(&self) -> Option<u16> {
+        self.ss58_prefix.or_else(|| self.chain.map(|chain| chain.ss58_prefix()))
+    }
+
+    /// Returns the client name defined, by default the crate name and version
+    /// (e.g. scouty/0.3.1)
+    pub fn rpc_client_name(&self) -> String {
+        self.rpc_client_name
+            .as_deref()
+            .and_then(non_empty)
+            .unwrap_or_else(|| {
+                format!("{}/{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))
+            })
+    }
+
+    /// Returns the maximum time to establish a connection to the substrate node
+    pub fn connect_timeout(&self) -> Duration {
+        Duration::from_secs(self.connect_timeout_seconds)
+    }
+
+    /// Returns the maximum time an individual RPC request may take
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.request_timeout_seconds)
+    }
+
+    /// Returns the time to wait before the first connection attempt
+    pub fn startup_delay(&self) -> Duration {
+        Duration::from_secs(self.startup_delay_seconds)
+    }
+
+    /// Verifies that every hook script defined exists and is executable. Hooks
+    /// are optional, so problems are only returned as warnings.
+    pub fn check_hooks(&self) -> Vec<String> {
+        let mut warnings: Vec<String> = Vec::new();
+        for (name, path) in self.hooks() {
+            if path.is_empty() || self.is_hook_disabled(name) {
+                continue;
+            }
+            match fs::metadata(path) {
+                Ok(metadata) => {
+                    if !is_executable(&metadata) {
+                        warnings.push(format!(
+                            "Hook script - {} - filename ({}) is not executable",
+                            name, path
+                        ));
+                    }
+                }
+                Err(_) => warnings.push(format!(
+                    "Hook script - {} - filename ({}) not found",
+                    name, path
+                )),
+            }
+        }
+        warnings
+    }
+
+    /// Returns the name and the script path of every hook
+    pub fn hooks(&self) -> Vec<(&'static str, &str)> {
+        let mut hooks = vec![
+            (HOOK_INIT, self.hook_init_path.as_str()),
+            (HOOK_NEW_SESSION, self.hook_new_session_path.as_str()),
+            (HOOK_NEW_ERA, self.hook_new_era_path.as_str()),
+            (
+                HOOK_VALIDATOR_STARTS_ACTIVE_NEXT_ERA,
+                self.hook_validator_starts_active_next_era_path.as_str(),
+            ),
+            (
+                HOOK_VALIDATOR_STARTS_INACTIVE_NEXT_ERA,
+                self.hook_validator_starts_inactive_next_era_path.as_str(),
+            ),
+            (
+                HOOK_VALIDATOR_SLASHED,
+                self.hook_validator_slashed_path.as_str(),
+            ),
+            (
+                HOOK_VALIDATOR_CHILLED,
+                self.hook_validator_chilled_path.as_str(),
+            ),
+            (
+                HOOK_VALIDATOR_OFFLINE,
+                self.hook_validator_offline_path.as_str(),
+            ),
+            (
+                HOOK_DEMOCRACY_STARTED,
+                self.hook_democracy_started_path.as_str(),
+            ),
+            (
+                HOOK_VALIDATOR_COMMISSION_CHANGED,
+                self.hook_validator_commission_changed_path.as_str(),
+            ),
+            (HOOK_STASH_SLASHED, self.hook_slash_path.as_str()),
+            (
+                HOOK_UNCLAIMED_REWARDS,
+                self.hook_unclaimed_rewards_path.as_str(),
+            ),
+        ];
+        // Stashes may override the active next era hook
+        for stash in self.stashes.iter() {
+            if let Some(path) = &stash.hook_active_next_era_path {
+                hooks.push((HOOK_VALIDATOR_STARTS_ACTIVE_NEXT_ERA, path.as_str()));
+            }
+        }
+        hooks
+    }
+
+    /// Returns the address the API is served on. A port alone (e.g. 8081 or :8081)
+    /// binds the loopback interface, so that the API is not exposed by default.
+    pub fn api_bind_addr(&self) -> Option<String> {
+        self.api_listen_addr.as_deref().map(|addr| {
+            let port = addr.strip_prefix(':').unwrap_or(addr);
+            if port.parse::<u16>().is_ok() {
+                format!("127.0.0.1:{}", port)
+            } else {
+                addr.to_string()
+            }
+        })
+    }
+
+    /// Returns the notification message wrapped with the prefix and suffix
+    /// defined, empty values are ignored
+    pub fn wrap_notification(&self, message: &str) -> String {
+        wrap_text(
+            self.notification_prefix.as_deref(),
+            message,
+            self.notification_suffix.as_deref(),
+        )
+    }
+
+    /// Same as wrap_notification for the html formatted message, the prefix and
+    /// suffix are escaped
+    pub fn wrap_formatted_notification(&self, formatted_message: &str) -> String {
+        wrap_text(
+            self.notification_prefix.as_deref().map(escape_html).as_deref(),
+            formatted_message,
+            self.notification_suffix.as_deref().map(escape_html).as_deref(),
+        )
+    }
+
+    /// Returns the extra args appended to every hook invocation, quoted words
+    /// (e.g. "a b") are kept as a single arg
+    pub fn hook_extra_args(&self) -> Vec<String> {
+        self.hook_extra_args
+            .as_deref()
+            .and_then(|args| shell_words::split(args).ok())
+            .unwrap_or_default()
+    }
+
+    /// Returns true if 'scouty' exits when the hook exits non-zero
+    pub fn is_hook_failure_fatal(&self, _name: &str) -> bool {
+        self.hook_failure_fatal
+    }
+
+    /// Returns true if the hook has been disabled, independently of its path
+    pub fn is_hook_disabled(&self, name: &str) -> bool {
+        let is_disabled = match name {
+            HOOK_NEW_SESSION => self.hook_new_session_disabled,
+            HOOK_VALIDATOR_STARTS_ACTIVE_NEXT_ERA => self.hook_active_next_era_disabled,
+            HOOK_VALIDATOR_STARTS_INACTIVE_NEXT_ERA => {
+                self.hook_inactive_next_era_disabled
+            }
+            HOOK_VALIDATOR_COMMISSION_CHANGED => self.commission_monitoring_disabled,
+            HOOK_STASH_SLASHED => self.slash_monitoring_disabled,
+            HOOK_UNCLAIMED_REWARDS => self.rewards_monitoring_disabled,
+            HOOK_VALIDATOR_CHILLED => self.chill_monitoring_disabled,
+            _ => false,
+        };
+        is_disabled
+            || EventKind::ALL
+                .iter()
+                .any(|kind| kind.hook() == name && !self.is_event_allowlisted(*kind))
+    }
+
+    /// Verifies if the event is in the enabled events, all events are if none
+    /// is listed
+    pub fn is_event_allowlisted(&self, kind: EventKind) -> bool {
+        self.enabled_events.is_empty() || self.enabled_events.contains(&kind)
+    }
+
+    /// Verifies if the event is monitored, it must be allowlisted and neither
+    /// its monitor nor its hook disabled. A disable flag always wins.
+    pub fn is_event_enabled(&self, kind: EventKind) -> bool {
+        !self.is_hook_disabled(kind.hook())
+    }
+
+    /// Returns the active next era hook path defined for the stash, falls back to
+    /// the global one if the stash has no override
+    pub fn hook_active_next_era_path(&self, address: &str) -> &str {
+        self.stashes
+            .iter()
+            .find(|s| s.address == address)
+            .and_then(|s| s.hook_active_next_era_path.as_deref())
+            .unwrap_or(&self.hook_validator_starts_active_next_era_path)
+    }
+
+    /// Returns the block explorer base url, the one defined takes precedence over
+    /// the default of the chain preset or of the network connected to. Custom
+    /// chains have no default.
+    pub fn explorer_base_url(&self, network: &str) -> Option<String> {
+        if let Some(explorer_base_url) = &self.explorer_base_url {
+            let explorer_base_url = explorer_base_url.trim().trim_end_matches('/');
+            if !explorer_base_url.is_empty() {
+                return Some(explorer_base_url.to_string());
+            }
+        }
+        self.chain
+            .or_else(|| network.to_lowercase().parse().ok())
+            .map(|chain| chain.explorer_url().to_string())
+    }
+
+    /// Returns true if the address is one of the stashes defined
+    pub fn is_stash(&self, address: &str) -> bool {
+        self.stashes.iter().any(|s| s.address == address)
+    }
+
+    /// Telegram notifications are only sent if not disabled and both the bot
+    /// token and the chat id are defined
+    pub fn is_telegram_enabled(&self) -> bool {
+        !self.telegram_disabled
+            && !self.telegram_bot_token.is_empty()
+            && !self.telegram_chat_id.is_empty()
+    }
+
+    /// Email notifications are only sent if not disabled and both the smtp host
+    /// and the recipients are defined
+    pub fn is_smtp_enabled(&self) -> bool {
+        !self.smtp_disabled && !self.smtp_host.is_empty() && !self.smtp_to.is_empty()
+    }
+
+    /// PagerDuty incidents are only triggered if not disabled and the routing key
+    /// of the integration is defined
+    pub fn is_pagerduty_enabled(&self) -> bool {
+        !self.pagerduty_disabled
+            && self
+                .pagerduty_routing_key
+                .as_ref()
+                .map_or(false, |key| !key.is_empty())
+    }
+
+    /// Returns the log level defined, if not defined `quiet` is equivalent to
+    /// the warn level and `is_debug` to the debug level
+    pub fn log_level(&self) -> LevelFilter {
+        match self.log_level {
+            Some(log_level) => log_level,
+            None if self.quiet => LevelFilter::Warn,
+            None if self.is_debug => LevelFilter::Debug,
+            None => LevelFilter::Info,
+        }
+    }
+
+    /// Compares the runtime spec version of the connected chain with the one
+    /// expected, if defined, and returns the warning to be raised on a mismatch
+    pub fn check_spec_version(&self, spec_version: u32) -> Option<String> {
+        match self.expected_spec_version {
+            Some(expected) if expected != spec_version => Some(format!(
+                "Runtime spec version {} differs from the expected spec version {}. 'scouty' metadata might be outdated!",
+                spec_version, expected
+            )),
+            _ => None,
+        }
+    }
+
+    /// Describes what 'scouty' will do with the resolved configuration. Secrets
+    /// are never displayed.
+    pub fn resolved_plan(&self) -> String {
+        let mut plan: Vec<String> = Vec::new();
+        plan.push(format!(
+            "Chain: {}",
+            self.chain
+                .map_or("-".to_string(), |chain| chain.to_string())
+        ));
+        plan.push(format!(
+            "Substrate endpoints: {}",
+            self.substrate_ws_urls.join(", ")
+        ));
+        if let Some(substrate_ws_proxy) = &self.substrate_ws_proxy {
+            plan.push(format!("Substrate proxy: {}", substrate_ws_proxy));
+        }
+        plan.push(format!("Stashes ({}):", self.stashes.len()));
+        for stash in self.stashes.iter() {
+            match &stash.name {
+                Some(name) => plan.push(format!("  {} ({})", stash.address, name)),
+                None => plan.push(format!("  {}", stash.address)),
+            }
+        }
+        plan.push("Hooks:".to_string());
+        for (name, path) in self.hooks() {
+            let status = if path.is_empty() {
+                "not defined".to_string()
+            } else if self.is_hook_disabled(name) {
+                format!("{} (disabled)", path)
+            } else if Path::new(path).exists() {
+                path.to_string()
+            } else {
+                format!("{} (not found)", path)
+            };
+            plan.push(format!("  {} -> {}", name, status));
+        }
+        if self.matrix_disabled {
+            plan.push("Matrix: disabled".to_string());
+        } else {
+            plan.push(format!(
+                "Matrix: enabled (user: {}, bot user: {}, bot password: {})",
+                self.matrix_user,
+                self.matrix_bot_user,
+                if self.matrix_bot_password.is_empty() {
+                    "not defined"
+                } else {
+                    "***"
+                }
+            ));
+        }
+        if self.is_telegram_enabled() {
+            plan.push(format!(
+                "Telegram: enabled (chat id: {}, bot token: ***)",
+                self.telegram_chat_id
+            ));
+        } else {
+            plan.push("Telegram: disabled".to_string());
+        }
+        if self.heartbeat_interval_hours > 0 {
+            plan.push(format!(
+                "Heartbeat: every {} hour(s){}",
+                self.heartbeat_interval_hours,
+                if self.heartbeat_node_health {
+                    " with node health"
+                } else {
+                    ""
+                }
+            ));
+        }
+        if let Some(hour) = self.daily_report_hour() {
+            plan.push(format!(
+                "Daily report: at {:02}:00 {}",
+                hour,
+                self.timezone()
+            ));
+        }
+        if self.notification_rate_limit_per_minute > 0 {
+            plan.push(format!(
+                "Notifications rate limit: {} per minute",
+                self.notification_rate_limit_per_minute
+            ));
+        }
+        if self.is_smtp_enabled() {
+            plan.push(format!(
+                "SMTP: enabled (host: {}:{}, from: {}, to: {})",
+                self.smtp_host,
+                self.smtp_port,
+                self.smtp_from,
+                self.smtp_to.join(", ")
+            ));
+        } else {
+            plan.push("SMTP: disabled".to_string());
+        }
+        if self.webhook_disabled || self.webhook_url.is_empty() {
+            plan.push("Webhook: disabled".to_string());
+        } else {
+            plan.push(format!("Webhook: enabled (url: {})", self.webhook_url));
+        }
+        if self.is_pagerduty_enabled() {
+            plan.push("PagerDuty: enabled".to_string());
+        } else {
+            plan.push("PagerDuty: disabled".to_string());
+        }
+        plan.join("\n")
+    }
+}
+
+/// A validator stash defined as `address`, `address:Label` or
+/// `address:Label:/path/to/hook.sh`. The label is used in notifications instead
+/// of the raw SS58 address and the hook path overrides the global active next
+/// era hook for this stash only, e.g. `address::/path/to/hook.sh` without label.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StashConfig {
+    pub address: String,
+    pub name: Option<String>,
+    pub hook_active_next_era_path: Option<String>,
+}
+
+/// Joins the prefix, text and suffix with a space, skipping empty parts
+fn wrap_text(prefix: Option<&str>, text: &str, suffix: Option<&str>) -> String {
+    [prefix.unwrap_or_default(), text, suffix.unwrap_or_default()]
+        .iter()
+        .filter(|part| !part.is_empty())
+        .cloned()
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/// Expand $VAR and ${VAR} references from the process environment, `$$` is kept
+/// as a literal `$`. References to undefined variables are left as they are.
+pub fn expand_env_vars(value: &str) -> String {
+    expand_vars_with(value, |name| env::var(name).ok())
+}
+
+fn expand_vars_with<F>(value: &str, lookup: F) -> String
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(i) = rest.find('$') {
+        expanded.push_str(&rest[..i]);
+        let after = &rest[i + 1..];
+        if let Some(after) = after.strip_prefix('$') {
+            expanded.push('$');
+            rest = after;
+            continue;
+        }
+        if let Some(braced) = after.strip_prefix('{') {
+            if let Some(end) = braced.find('}') {
+                let name = &braced[..end];
+                match lookup(name) {
+                    Some(expansion) => expanded.push_str(&expansion),
+                    None => expanded.push_str(&format!("${{{}}}", name)),
+                }
+                rest = &braced[end + 1..];
+                continue;
+            }
+        } else {
+            let end = after
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(after.len());
+            if end > 0 {
+                let name = &after[..end];
+                match lookup(name) {
+                    Some(expansion) => expanded.push_str(&expansion),
+                    None => expanded.push_str(&format!("${}", name)),
+                }
+                rest = &after[end..];
+                continue;
+            }
+        }
+        expanded.push('$');
+        rest = after;
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+/// Verifies if the value is a matrix user id, e.g. @your-account:matrix.org
+fn is_matrix_user_id(value: &str) -> bool {
+    match value.strip_prefix('@').and_then(|id| id.split_once(':')) {
+        Some((localpart, server)) => {
+            !localpart.is_empty()
+                && !server.is_empty()
+                && !value.contains(char::is_whitespace)
+        }
+        None => false,
+    }
+}
+
+/// Returns None for empty values
+fn non_empty(value: &str) -> Option<String> {
+    if value.trim().is_empty() {
+        None
+    } else {
+        Some(value.trim().to_string())
+    }
+}
+
+impl StashConfig {
+    pub fn parse(value: &str) -> StashConfig {
+        match value.trim().split_once(':') {
+            Some((address, rest)) => {
+                let (name, hook_active_next_era_path) = match rest.split_once(':') {
+                    Some((name, path)) => (non_empty(name), non_empty(path)),
+                    None => (non_empty(rest), None),
+                };
+                StashConfig {
+                    address: address.trim().to_string(),
+                    name,
+                    hook_active_next_era_path,
+                }
+            }
+            None => StashConfig {
+                address: value.trim().to_string(),
+                name: None,
+                hook_active_next_era_path: None,
+            },
+        }
+    }
+
+    /// Returns the label if defined, otherwise the stash address
+    pub fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.address)
+    }
+}
+
+impl Serialize for StashConfig {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match (&self.name, &self.hook_active_next_era_path) {
+            (name, Some(path)) => serializer.serialize_str(&format!(
+                "{}:{}:{}",
+                self.address,
+                name.as_deref().unwrap_or_default(),
+                path
+            )),
+            (Some(name), None) => {
+                serializer.serialize_str(&format!("{}:{}", self.address, name))
+            }
+            (None, None) => serializer.serialize_str(&self.address),
+        }
+    }
+}
+
+/// A stash defined as a TOML table, e.g. `[[stashes]]` with an `address`, `name`
+/// and `hook_active_next_era_path`
+#[derive(Deserialize)]
+struct StashTable {
+    address: String,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    hook_active_next_era_path: Option<String>,
+}
+
+impl<'de> Deserialize<'de> for StashConfig {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match StringOr::<StashTable>::deserialize(deserializer)? {
+            StringOr::String(value) => Ok(StashConfig::parse(&value)),
+            StringOr::Value(table) => Ok(StashConfig {
+                address: table.address.trim().to_string(),
+                name: table.name.as_deref().and_then(non_empty),
+                hook_active_next_era_path: table
+                    .hook_active_next_era_path
+                    .as_deref()
+                    .and_then(non_empty),
+            }),
+        }
+    }
+}
+
+/// Events the matrix rooms can be filtered by, one per report section plus the
+/// error and test messages
+pub const MATRIX_EVENTS: [&str; 14] = [
+    "init",
+    "session",
+    "slash",
+    "chill",
+    "offline",
+    "democracy",
+    "heartbeat",
+    "commission",
+    "stake",
+    "stash_slashed",
+    "rewards",
+    "daily",
+    "error",
+    "test",
+];
+
+/// Matrix room defined as `room_id=event|event`, e.g.
+/// `!oncall:matrix.org=slash|chill`. A room without events receives them all.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MatrixRoom {
+    pub room_id: String,
+    pub events: Vec<String>,
+}
+
+impl MatrixRoom {
+    pub fn parse(value: &str) -> MatrixRoom {
+        match value.trim().split_once('=') {
+            Some((room_id, events)) => MatrixRoom {
+                room_id: room_id.trim().to_string(),
+                events: events.split('|').filter_map(non_empty).collect(),
+            },
+            None => MatrixRoom {
+                room_id: value.trim().to_string(),
+                events: Vec::new(),
+            },
+        }
+    }
+
+    /// Returns true if messages of the event are sent to the room
+    pub fn accepts(&self, event: &str) -> bool {
+        self.events.is_empty() || self.events.iter().any(|e| e == event)
+    }
+}
+
+impl Serialize for MatrixRoom {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if self.events.is_empty() {
+            serializer.serialize_str(&self.room_id)
+        } else {
+            serializer
+                .serialize_str(&format!("{}={}", self.room_id, self.events.join("|")))
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for MatrixRoom {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        Ok(MatrixRoom::parse(&value))
+    }
+}
+
+/// Events that can be enabled through the enabled events allowlist, each one
+/// named after the hook it runs
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    NewSession,
+    NewEra,
+    ActiveNextEra,
+    InactiveNextEra,
+    Slash,
+    StashSlashed,
+    Chill,
+    Offline,
+    Democracy,
+    Commission,
+    Rewards,
+}
+
+impl EventKind {
+    pub const ALL: [EventKind; 11] = [
+        Self::NewSession,
+        Self::NewEra,
+        Self::ActiveNextEra,
+        Self::InactiveNextEra,
+        Self::Slash,
+        Self::StashSlashed,
+        Self::Chill,
+        Self::Offline,
+        Self::Democracy,
+        Self::Commission,
+        Self::Rewards,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::NewSession => "new_session",
+            Self::NewEra => "new_era",
+            Self::ActiveNextEra => "active_next_era",
+            Self::InactiveNextEra => "inactive_next_era",
+            Self::Slash => "slash",
+            Self::StashSlashed => "stash_slashed",
+            Self::Chill => "chill",
+            Self::Offline => "offline",
+            Self::Democracy => "democracy",
+            Self::Commission => "commission",
+            Self::Rewards => "rewards",
+        }
+    }
+
+    /// Returns the name of the hook run on the event
+    pub fn hook(&self) -> &'static str {
+        match self {
+            Self::NewSession => HOOK_NEW_SESSION,
+            Self::NewEra => HOOK_NEW_ERA,
+            Self::ActiveNextEra => HOOK_VALIDATOR_STARTS_ACTIVE_NEXT_ERA,
+            Self::InactiveNextEra => HOOK_VALIDATOR_STARTS_INACTIVE_NEXT_ERA,
+            Self::Slash => HOOK_VALIDATOR_SLASHED,
+            Self::StashSlashed => HOOK_STASH_SLASHED,
+            Self::Chill => HOOK_VALIDATOR_CHILLED,
+            Self::Offline => HOOK_VALIDATOR_OFFLINE,
+            Self::Democracy => HOOK_DEMOCRACY_STARTED,
+            Self::Commission => HOOK_VALIDATOR_COMMISSION_CHANGED,
+            Self::Rewards => HOOK_UNCLAIMED_REWARDS,
+        }
+    }
+}
+
+impl std::str::FromStr for EventKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|kind| kind.name() == s)
+            .ok_or(())
+    }
+}
+
+/// Wraps configuration values that must never be displayed, e.g. passwords or
+/// tokens. `Debug` always prints the value masked, use `expose` to access it.
+#[derive(Clone, Default, Deserialize, PartialEq)]
+#[serde(transparent)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl From<String> for Secret {
+    fn from(value: String) -> Self {
+        Secret(value)
+    }
+}
+
+/// Secrets are always serialized redacted, e.g. when printing the config
+impl Serialize for Secret {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if self.is_empty() {
+            serializer.serialize_str("")
+        } else {
+            serializer.serialize_str("***")
+        }
+    }
+}
+
+impl std::fmt::Debug for Secret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "\"***\"")
+    }
+}
+
+/// Substrate-based chains for which 'scouty' has a preset under the <CHAIN> argument
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChainPreset {
+    Westend,
+    Kusama,
+    Polkadot,
+    Paseo,
+}
+
+impl ChainPreset {
+    /// Every chain preset available, in the order they are listed
+    pub const ALL: [ChainPreset; 4] = [
+        ChainPreset::Westend,
+        ChainPreset::Kusama,
+        ChainPreset::Polkadot,
+        ChainPreset::Paseo,
+    ];
+
+    /// Returns the default substrate websocket endpoint
+    pub fn ws_url(&self) -> &'static str {
+        match self {
+            Self::Westend => "wss://westend-rpc.polkadot.io:443",
+            Self::Kusama => "wss://kusama-rpc.polkadot.io:443",
+            Self::Polkadot => "wss://rpc.polkadot.io:443",
+            Self::Paseo => "wss://paseo-rpc.dwellir.com:443",
+        }
+    }
+
+    /// Returns the default block explorer base url
+    pub fn explorer_url(&self) -> &'static str {
+        match self {
+            Self::Westend => "https://westend.subscan.io",
+            Self::Kusama => "https://kusama.subscan.io",
+            Self::Polkadot => "https://polkadot.subscan.io",
+            Self::Paseo => "https://paseo.subscan.io",
+        }
+    }
+
+    /// Returns the default token symbol
+    pub fn token_symbol(&self) -> &'static str {
+        match self {
+            Self::Westend => "WND",
+            Self::Kusama => "KSM",
+            Self::Polkadot => "DOT",
+            Self::Paseo => "PAS",
+        }
+    }
+
+    /// Returns the default number of decimals of the token
+    pub fn token_decimals(&self) -> u8 {
+        match self {
+            Self::Westend => 12,
+            Self::Kusama => 12,
+            Self::Polkadot => 10,
+            Self::Paseo => 10,
+        }
+    }
+
+    /// Returns the SS58 address prefix
+    pub fn ss58_prefix(&self) -> u16 {
+        match self {
+            Self::Westend => 42,
+            Self::Kusama => 2,
+            Self::Polkadot => 0,
+            Self::Paseo => 0,
+        }
+    }
+}
+
+impl std::str::FromStr for ChainPreset {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "westend" => Ok(Self::Westend),
+            "kusama" => Ok(Self::Kusama),
+            "polkadot" => Ok(Self::Polkadot),
+            "paseo" => Ok(Self::Paseo),
+            _ => Err(()),
+        }
+    }
+}
+
+impl std::fmt::Display for ChainPreset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Westend => write!(f, "westend"),
+            Self::Kusama => write!(f, "kusama"),
+            Self::Polkadot => write!(f, "polkadot"),
+            Self::Paseo => write!(f, "paseo"),
+        }
+    }
+}
+
+/// Returns one line per chain preset with its endpoint, SS58 prefix and token, as
+/// printed by the `list-chains` command
+pub fn list_chains() -> String {
+    ChainPreset::ALL
+        .iter()
+        .map(|chain| {
+            format!(
+                "{:<10} {:<36} ss58 prefix {:<3} token {} ({} decimals)",
+                chain.to_string(),
+                chain.ws_url(),
+                chain.ss58_prefix(),
+                chain.token_symbol(),
+                chain.token_decimals()
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Returns the public key and the SS58 prefix the address is encoded with, and
+/// whether the prefix matches the one of the chain if known, as printed by the
+/// `decode` command
+pub fn decode_address(address: &str, prefix: Option<u16>) -> Result<String, ConfigError> {
+    let (account, address_prefix) = decode_ss58(address).ok_or_else(|| {
+        ConfigError::Other(format!("'{}' is not a valid SS58 address", address))
+    })?;
+    let public_key: &[u8] = account.as_ref();
+    let chain_desc = match prefix {
+        Some(prefix) if prefix == address_prefix => {
+            format!("matches the chain prefix {}", prefix)
+        }
+        Some(prefix) => format!("does not match the chain prefix {}", prefix),
+        None => "chain prefix unknown".to_string(),
+    };
+    Ok(format!(
+        "public key 0x{}\nss58 prefix {} ({})",
+        hex::encode(public_key),
+        address_prefix,
+        chain_desc
+    ))
+}
+
+/// Subcommands given in the command line, 'scouty' runs as a daemon by default
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Command {
+    Daemon,
+    View,
+    #[serde(rename = "test-notifications")]
+    TestNotifications,
+    #[serde(rename = "list-chains")]
+    ListChains,
+    #[serde(rename = "config-example")]
+    ConfigExample,
+    Validate,
+    Decode,
+}
+
+impl Default for Command {
+    fn default() -> Self {
+        Self::Daemon
+    }
+}
+
+impl std::str::FromStr for Command {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "daemon" => Ok(Self::Daemon),
+            "view" => Ok(Self::View),
+            "test-notifications" => Ok(Self::TestNotifications),
+            "list-chains" => Ok(Self::ListChains),
+            "config-example" => Ok(Self::ConfigExample),
+            "validate" => Ok(Self::Validate),
+            "decode" => Ok(Self::Decode),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Output format of the log lines
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+impl Default for LogFormat {
+    fn default() -> Self {
+        Self::Text
+    }
+}
+
+/// Format of the messages sent to the matrix room
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatrixFormat {
+    Plain,
+    Html,
+}
+
+impl Default for MatrixFormat {
+    fn default() -> Self {
+        Self::Html
+    }
+}
+
+/// Head of the chain 'scouty' follows, the latest blocks are seen sooner but
+/// may be reverted by a reorg, the finalized ones are seen later but are final
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HeadMode {
+    Latest,
+    Finalized,
+}
+
+impl Default for HeadMode {
+    fn default() -> Self {
+        Self::Finalized
+    }
+}
+
+impl std::fmt::Display for HeadMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Latest => write!(f, "latest"),
+            Self::Finalized => write!(f, "finalized"),
+        }
+    }
+}
+
+/// Output format of the effective config given by the `--print-config` flag
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigFormat {
+    Env,
+    Toml,
+}
+
+/// Checks the owner-execute bit of the file permissions
+#[cfg(unix)]
+fn is_executable(metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.is_file() && metadata.permissions().mode() & 0o100 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(metadata: &fs::Metadata) -> bool {
+    metadata.is_file()
+}
+
+/// Checks that the config file is not accessible by group or others, since it
+/// may contain secrets, the same way SSH checks private key files
+#[cfg(unix)]
+fn check_config_file_permissions(config_path: &str) -> Option<String> {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = fs::metadata(config_path).ok()?.permissions().mode();
+    if mode & 0o077 != 0 {
+        return Some(format!(
+            "Config file {} is accessible by others (mode {:o}), it may contain secrets, please restrict it with 'chmod 600 {}'",
+            config_path,
+            mode & 0o777,
+            config_path
+        ));
+    }
+    None
+}
+
+#[cfg(not(unix))]
+fn check_config_file_permissions(_config_path: &str) -> Option<String> {
+    None
+}
+
+/// Check that the substrate websocket url is a ws:// or wss:// url with a host,
+/// IPv6 hosts must be enclosed in brackets (e.g. ws://[::1]:9944)
+pub fn validate_ws_url(substrate_ws_url: &str) -> Result<(), ConfigError> {
+    let url = Url::parse(substrate_ws_url).map_err(|e| {
+        ConfigError::Other(format!(
+            "Invalid substrate websocket url '{}': {}",
+            substrate_ws_url, e
+        ))
+    })?;
+    if url.scheme() != "ws" && url.scheme() != "wss" {
+        return Err(ConfigError::Other(format!(
+            "Invalid substrate websocket url '{}': scheme must be ws or wss",
+            substrate_ws_url
+        )));
+    }
+    match url.host() {
+        Some(Host::Domain(domain)) if !domain.is_empty() => Ok(()),
+        Some(Host::Ipv4(_)) | Some(Host::Ipv6(_)) => Ok(()),
+        _ => Err(ConfigError::Other(format!(
+            "Invalid substrate websocket url '{}': host is missing",
+            substrate_ws_url
+        ))),
+    }
+}
+
+/// Fold the single substrate websocket url into the list of endpoints as the first
+/// element, so that it is always the first endpoint 'scouty' tries to connect to
+fn fold_substrate_ws_urls(
+    substrate_ws_url: &str,
+    substrate_ws_urls: &[String],
+) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    for url in std::iter::once(&substrate_ws_url.to_string()).chain(substrate_ws_urls) {
+        let url = url.trim();
+        if !url.is_empty() && !urls.iter().any(|u| u == url) {
+            urls.push(url.to_string());
+        }
+    }
+    urls
+}
+
+/// Substrate websocket endpoint given as <url>|<priority> (e.g. wss://mynode:9944|10),
+/// the endpoints without a priority have priority 0
+#[derive(Clone, Debug, PartialEq)]
+pub struct Endpoint {
+    pub url: String,
+    pub priority: u32,
+}
+
+impl std::str::FromStr for Endpoint {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (url, priority) = match s.rsplit_once('|') {
+            Some((url, priority)) => {
+                (url.trim(), priority.trim().parse::<u32>().map_err(|_| ())?)
+            }
+            None => (s, 0),
+        };
+        if url.is_empty() {
+            return Err(());
+        }
+        Ok(Endpoint {
+            url: url.to_string(),
+            priority,
+        })
+    }
+}
+
+/// Sort the endpoints by priority, the highest first, keeping the order given for
+/// the ones with the same priority. An endpoint with a priority not valid is kept
+/// as is, so that its url is reported as not valid.
+fn sort_endpoints(substrate_ws_urls: &[String]) -> Vec<Endpoint> {
+    let mut endpoints: Vec<Endpoint> = Vec::new();
+    for url in substrate_ws_urls.iter() {
+        let endpoint = url.parse::<Endpoint>().unwrap_or(Endpoint {
+            url: url.to_string(),
+            priority: 0,
+        });
+        if !endpoints.iter().any(|e| e.url == endpoint.url) {
+            endpoints.push(endpoint);
+        }
+    }
+    endpoints.sort_by(|a, b| b.priority.cmp(&a.priority));
+    endpoints
+}
+
+/// Load the configuration variables defined in a dotenv file and its overlay
+/// files into the environment. Like dotenv, variables already defined are never
+/// overridden, so that CLI args and OS env vars keep taking precedence over the
+/// file values. The table of a TOML file is returned instead, to be deserialized
+/// into the `Config` with the env vars on top. Returns the files loaded and the
+/// TOML table, none if no file is available.
+fn load_config_file(config_path: &str) -> Result<ConfigFiles, ConfigError> {
+    let config_files = read_config_files(config_path, hostname().as_deref())?;
+    for (key, value) in config_files.vars.iter() {
+        if env::var(key).is_err() {
+            env::set_var(key, value);
+        }
+    }
+    Ok(config_files)
+}
+
+/// Returns the host name of the machine, if available
+fn hostname() -> Option<String> {
+    hostname::get()
+        .ok()
+        .and_then(|name| name.into_string().ok())
+}
+
+/// Returns the overlay files of the config file in the order they are applied,
+/// first the host specific one and then the local one, e.g. `.env.<hostname>` and
+/// `.env.local`, or `scouty.<hostname>.toml` and `scouty.local.toml`
+fn overlay_paths(config_path: &str, hostname: Option<&str>) -> Vec<String> {
+    let mut suffixes: Vec<&str> = Vec::new();
+    if let Some(hostname) = hostname.filter(|hostname| !hostname.is_empty()) {
+        suffixes.push(hostname);
+    }
+    suffixes.push("local");
+    suffixes
+        .into_iter()
+        .map(|suffix| match config_path.strip_suffix(".toml") {
+            Some(stem) => format!("{}.{}.toml", stem, suffix),
+            None => format!("{}.{}", config_path, suffix),
+        })
+        .collect()
+}
+
+/// The config files read, with the variables defined in the dotenv files and the
+/// table defined in the TOML files
+#[derive(Clone, Debug, Default)]
+struct ConfigFiles {
+    paths: Vec<String>,
+    vars: Vec<(String, String)>,
+    table: toml::value::Table,
+}
+
+/// Read the variables defined in the config file and then in its overlay files,
+/// a variable or TOML key defined in a later file overrides the one defined
+/// before
+fn read_config_files(
+    config_path: &str,
+    hostname: Option<&str>,
+) -> Result<ConfigFiles, ConfigError> {
+    let mut config_files = ConfigFiles::default();
+    let paths = std::iter::once(config_path.to_string())
+        .chain(overlay_paths(config_path, hostname));
+    for path in paths {
+        if let Some(config_file) = read_config_file(&path)? {
+            for (key, value) in config_file.vars {
+                config_files.vars.retain(|(k, _)| k != &key);
+                config_files.vars.push((key, value));
+            }
+            config_files.table.extend(config_file.table);
+            config_files.paths.push(path);
+        }
+    }
+    Ok(config_files)
+}
+
+/// Read the table defined in a `.toml` config file or the variables defined in a
+/// `.env` one, returns None if the file does not exist
+fn read_config_file(config_path: &str) -> Result<Option<ConfigFiles>, ConfigError> {
+    if Path::new(config_path)
+        .extension()
+        .map_or(false, |ext| ext == "toml")
+    {
+        let content = match fs::read_to_string(config_path) {
+            Ok(content) => content,
+            Err(_) => return Ok(None),
+        };
+        return Ok(Some(ConfigFiles {
+            paths: vec![config_path.to_string()],
+            table: parse_toml_config(&content)?,
+            ..Default::default()
+        }));
+    }
+    match dotenv::from_filename_iter(config_path) {
+        Ok(iter) => {
+            let mut vars: Vec<(String, String)> = Vec::new();
+            for item in iter {
+                vars.push(item.map_err(|e| ConfigError::Other(format!("{}", e)))?);
+            }
+            Ok(Some(ConfigFiles {
+                paths: vec![config_path.to_string()],
+                vars,
+                ..Default::default()
+            }))
+        }
+        Err(_) => Ok(None),
+    }
+}
+
+/// Parse one stash per line, optionally as `address:Label`. Blank lines and
+/// lines starting with '#' are ignored.
+fn parse_stashes_file(content: &str) -> Vec<StashConfig> {
+    content
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(StashConfig::parse)
+        .collect()
+}
+
+/// Merge the stashes keeping the first occurrence of each address, a label is
+/// taken from a later occurrence only if none was seen before
+fn merge_stashes(stashes: &[StashConfig], others: &[StashConfig]) -> Vec<StashConfig> {
+    let mut merged: Vec<StashConfig> = Vec::new();
+    for stash in stashes.iter().chain(others) {
+        match merged.iter_mut().find(|s| s.address == stash.address) {
+            Some(existing) => {
+                if existing.name.is_none() {
+                    existing.name = stash.name.clone();
+                }
+                if existing.hook_active_next_era_path.is_none() {
+                    existing.hook_active_next_era_path =
+                        stash.hook_active_next_era_path.clone();
+                }
+            }
+            None => merged.push(stash.clone()),
+        }
+    }
+    merged
+}
+
+/// Read a secret from file, trailing whitespace and new lines are trimmed
+fn read_secret_file(path: &str) -> Result<Secret, ConfigError> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(Secret::from(content.trim_end().to_string())),
+        Err(e) => Err(ConfigError::Other(format!(
+            "Secret file {} could not be read: {}",
+            path, e
+        ))),
+    }
+}
+
+/// Returns the vars defined with the env prefix given renamed with the default
+/// 'SCOUTY_' prefix, so that they are deserialized as usual
+fn namespaced_env_vars(
+    env_prefix: &str,
+    vars: Vec<(String, String)>,
+) -> Vec<(String, String)> {
+    let env_prefix = if env_prefix.ends_with('_') {
+        env_prefix.to_string()
+    } else {
+        format!("{}_", env_prefix)
+    };
+    vars.into_iter()
+        .filter_map(|(key, value)| {
+            let name = key.strip_prefix(&env_prefix)?;
+            Some((format!("SCOUTY_{}", name), value))
+        })
+        .collect()
+}
+
+/// Replace the SCOUTY_* env vars by the ones defined with the env prefix given
+fn with_env_prefix(env_prefix: &str) {
+    let vars = namespaced_env_vars(
+        env_prefix,
+        env::vars_os()
+            .filter_map(|(key, value)| {
+                Some((key.into_string().ok()?, value.into_string().ok()?))
+            })
+            .collect(),
+    );
+    for (key, _) in scouty_env_vars() {
+        env::remove_var(key);
+    }
+    for (key, value) in vars {
+        env::set_var(key, value);
+    }
+}
+
+/// Returns the SCOUTY_* env vars currently defined
+fn scouty_env_vars() -> Vec<(String, String)> {
+    env::vars_os()
+        .filter_map(|(key, value)| {
+            Some((key.into_string().ok()?, value.into_string().ok()?))
+        })
+        .filter(|(key, _)| key.starts_with("SCOUTY_"))
+        .collect()
+}
+
+/// Names the SCOUTY_* var that failed to deserialize and the value expected, the
+/// var is found by deserializing each one on its own
+fn describe_env_error<E>(error: &E, vars: &[(String, String)]) -> String
+where
+    E: std::fmt::Display,
+{
+    let failed = vars.iter().find(|(key, value)| {
+        key.starts_with("SCOUTY_")
+            && envy::prefixed("SCOUTY_")
+                .from_iter::<_, Config>(vec![(key.to_string(), value.to_string())])
+                .is_err()
+    });
+    let (key, value) = match failed {
+        Some(var) => var,
+        None => return format!("{}", error),
+    };
+    // The type expected is the one of the default value, if the field has one
+    let field = key.trim_start_matches("SCOUTY_").to_lowercase();
+    let expected = envy::prefixed("SCOUTY_")
+        .from_iter::<_, Config>(Vec::<(String, String)>::new())
+        .ok()
+        .and_then(|defaults| toml::Value::try_from(defaults).ok())
+        .and_then(|defaults| match defaults.get(&field) {
+            Some(toml::Value::Boolean(_)) => Some("true or false".to_string()),
+            Some(toml::Value::Integer(default)) => {
+                Some(format!("a non-negative integer (e.g. {})", default))
+            }
+            Some(toml::Value::Array(_)) => Some("a comma separated list".to_string()),
+            Some(toml::Value::String(default)) if !default.is_empty() => {
+                Some(format!("a value like '{}'", default))
+            }
+            _ => None,
+        });
+    match expected {
+        Some(expected) => format!(
+            "{}='{}' is not valid, expected {} ({})",
+            key, value, expected, error
+        ),
+        None => format!("{}='{}' is not valid ({})", key, value, error),
+    }
+}
+
+/// Merge the reload source with the config file vars, keeping the precedence used
+/// at startup: CLI flags, then env vars and then the config file
+fn reload_vars(
+    source: &ReloadSource,
+    file_vars: Vec<(String, String)>,
+) -> Vec<(String, String)> {
+    let mut vars: Vec<(String, String)> = source.initial_env.clone();
+    for (key, value) in file_vars {
+        if !vars.iter().any(|(k, _)| k == &key) {
+            vars.push((key, value));
+        }
+    }
+    for (key, value) in source.cli_env.iter() {
+        vars.retain(|(k, _)| k != key);
+        vars.push((key.to_string(), value.to_string()));
+    }
+    vars
+}
+
+/// Load the configuration again from the reload source. A change to the substrate
+/// endpoints is ignored since it requires a restart.
+fn reload_from(source: &ReloadSource, current: &Config) -> Result<Config, ConfigError> {
+    let config_files = match &source.config_path {
+        Some(config_path) => read_config_files(config_path, hostname().as_deref())?,
+        None => ConfigFiles::default(),
+    };
+    let vars = reload_vars(source, config_files.vars);
+    let config = if config_files.table.is_empty() {
+        Config::from_vars(vars.clone()).map_err(|e| describe_env_error(&e, &vars))
+    } else {
+        Config::from_toml_and_vars(config_files.table, vars.clone())
+            .map_err(|e| describe_env_error(&e, &vars))
+    };
+    let mut config = config
+        .map_err(ConfigError::Other)?
+        .finish_loading(&config_files.paths)?;
+    config.validate()?;
+    if config.substrate_ws_urls != current.substrate_ws_urls
+        || config.substrate_ws_tls_fingerprint != current.substrate_ws_tls_fingerprint
+        || config.substrate_ws_proxy != current.substrate_ws_proxy
+        || config.chain != current.chain
+    {
+        info!("Substrate endpoint changes are only applied after a restart");
+        config.substrate_ws_url = current.substrate_ws_url.clone();
+        config.substrate_ws_urls = current.substrate_ws_urls.clone();
+        config.substrate_ws_tls_fingerprint =
+            current.substrate_ws_tls_fingerprint.clone();
+        config.substrate_ws_proxy = current.substrate_ws_proxy.clone();
+        config.chain = current.chain;
+    }
+    Ok(config)
+}
+
+/// Reload the configuration from the config file and env vars and swap it
+/// atomically, so that a new stash list or hook paths are used without a restart
+pub fn reload_config() -> Result<(), ConfigError> {
+    let source = RELOAD_SOURCE.lock().unwrap().clone();
+    let config = reload_from(&source, &CONFIG.load())?;
+    CONFIG.store(Arc::new(config));
+    info!("Configuration reloaded");
+    Ok(())
+}
+
+/// Reload the configuration every time a SIGHUP signal is received
+pub fn reload_on_sighup() -> Result<(), std::io::Error> {
+    let mut signals = Signals::new(&[SIGHUP])?;
+    thread::spawn(move || {
+        for _ in signals.forever() {
+            info!("SIGHUP received, reloading configuration");
+            if let Err(e) = reload_config() {
+                error!("Configuration not reloaded: {}", e);
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Parse a TOML config file into its table, which is deserialized into the
+/// `Config` as it is. A value that does not deserialize is reported with its TOML
+/// key and line.
+fn parse_toml_config(content: &str) -> Result<toml::value::Table, ConfigError> {
+    let table: toml::value::Table = toml::from_str(content)?;
+    for (key, value) in table.iter() {
+        let mut entry = toml::value::Table::new();
+        entry.insert(key.to_string(), value.clone());
+        if let Err(e) = Config::from_toml_and_vars(entry, Vec::new()) {
+            return Err(ConfigError::Other(format!(
+                "invalid TOML value for key '{}' at line {}: {}",
+                key,
+                toml_key_line(content, key),
+                e
+            )));
+        }
+    }
+    Ok(table)
+}
+
+/// A config value deserialized either from a TOML value as it is, or from the
+/// string of a SCOUTY_* var the same way envy does
+enum ConfigValue {
+    Toml(toml::Value),
+    Var(String),
+}
+
+impl<'de> IntoDeserializer<'de, toml::de::Error> for ConfigValue {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// Forward each deserialize method to the TOML value or to the var string
+macro_rules! forward_to_config_value {
+    ($($method:ident($($arg:ident: $ty:ty),*)),*) => {
+        $(
+            fn $method<V>(
+                self,
+                $($arg: $ty,)*
+                visitor: V,
+            ) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                match self {
+                    ConfigValue::Toml(value) => value.$method($($arg,)* visitor),
+                    ConfigValue::Var(value) => VarValue(value).$method($($arg,)* visitor),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for ConfigValue {
+    type Error = toml::de::Error;
+
+    forward_to_config_value! {
+        deserialize_any(), deserialize_bool(), deserialize_i8(), deserialize_i16(),
+        deserialize_i32(), deserialize_i64(), deserialize_u8(), deserialize_u16(),
+        deserialize_u32(), deserialize_u64(), deserialize_f32(), deserialize_f64(),
+        deserialize_char(), deserialize_str(), deserialize_string(),
+        deserialize_bytes(), deserialize_byte_buf(), deserialize_option(),
+        deserialize_unit(), deserialize_unit_struct(name: &'static str),
+        deserialize_newtype_struct(name: &'static str), deserialize_seq(),
+        deserialize_tuple(len: usize),
+        deserialize_tuple_struct(name: &'static str, len: usize), deserialize_map(),
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]),
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]),
+        deserialize_identifier(), deserialize_ignored_any()
+    }
+}
+
+/// The string of a SCOUTY_* var, numbers and booleans are parsed from it, a list
+/// is split by comma and an optional value is always defined
+struct VarValue(String);
+
+impl<'de> IntoDeserializer<'de, toml::de::Error> for VarValue {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// Parse the var string into the type expected and visit it
+macro_rules! deserialize_parsed_var {
+    ($($method:ident => $visit:ident: $ty:ty),*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                match self.0.parse::<$ty>() {
+                    Ok(value) => visitor.$visit(value),
+                    Err(e) => Err(serde::de::Error::custom(format!(
+                        "{} while parsing value '{}'",
+                        e, self.0
+                    ))),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for VarValue {
+    type Error = toml::de::Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_string(self.0)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let values: Vec<VarValue> = if self.0.is_empty() {
+            Vec::new()
+        } else {
+            self.0
+                .split(',')
+                .map(|value| VarValue(value.trim().to_string()))
+                .collect()
+        };
+        SeqDeserializer::new(values.into_iter()).deserialize_seq(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_enum(
+            <String as IntoDeserializer<'de, toml::de::Error>>::into_deserializer(self.0),
+        )
+    }
+
+    deserialize_parsed_var! {
+        deserialize_bool => visit_bool: bool,
+        deserialize_i8 => visit_i8: i8,
+        deserialize_i16 => visit_i16: i16,
+        deserialize_i32 => visit_i32: i32,
+        deserialize_i64 => visit_i64: i64,
+        deserialize_u8 => visit_u8: u8,
+        deserialize_u16 => visit_u16: u16,
+        deserialize_u32 => visit_u32: u32,
+        deserialize_u64 => visit_u64: u64,
+        deserialize_f32 => visit_f32: f32,
+        deserialize_f64 => visit_f64: f64
+    }
+
+    serde::forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct tuple tuple_struct map
+        struct identifier ignored_any
+    }
+}
+
+pub fn toml_scalar_to_string(value: &toml::Value) -> Option<String> {
+    match value {
+        toml::Value::String(v) => Some(v.to_string()),
+        toml::Value::Integer(v) => Some(v.to_string()),
+        toml::Value::Float(v) => Some(v.to_string()),
+        toml::Value::Boolean(v) => Some(v.to_string()),
+        _ => None,
+    }
+}
+
+/// Returns the line where the TOML key or table is defined, '?' if not found
+fn toml_key_line(content: &str, key: &str) -> String {
+    content
+        .lines()
+        .position(|line| {
+            let line = line.trim_start();
+            line.starts_with(&format!("[{}]", key))
+                || line
+                    .strip_prefix(key)
+                    .map_or(false, |rest| rest.trim_start().starts_with('='))
+        })
+        .map_or("?".to_string(), |i| (i + 1).to_string())
+}
+
+/// Decode the SS58 address, returns the account and the prefix it is encoded with
+pub fn decode_ss58(address: &str) -> Option<(AccountId32, u16)> {
+    AccountId32::from_ss58check_with_version(address)
+        .ok()
+        .map(|(account, version)| (account, version.prefix()))
+}
+
+/// Decode each stash as an SS58 address and reject the ones that are malformed.
+/// If the chain prefix is known, addresses encoded with a different prefix are
+/// only reported as a warning since they still decode to a valid account.
+pub fn validate_stashes(
+    stashes: &[StashConfig],
+    prefix: Option<u16>,
+) -> Result<(), ConfigError> {
+    let mut malformed: Vec<String> = Vec::new();
+    for (i, stash) in stashes.iter().map(|stash| &stash.address).enumerate() {
+        match decode_ss58(stash) {
+            Some((_, stash_prefix)) => {
+                if let Some(prefix) = prefix {
+                    if stash_prefix != prefix {
+                        warn!(
+                            "Stash '{}' (index {}) is encoded with SS58 prefix {} but the selected chain expects prefix {}",
+                            stash,
+                            i,
+                            stash_prefix,
+                            prefix
+                        );
+                    }
+                }
+            }
+            None => malformed.push(format!("'{}' (index {})", stash, i)),
+        }
+    }
+    if !malformed.is_empty() {
+        return Err(ConfigError::InvalidStashes(malformed.join(", ")));
+    }
+    Ok(())
+}
+
+/// Define CLI flags with clap
+fn cli() -> App<'static, 'static> {
+    App::new(env!("CARGO_PKG_NAME"))
+    .version(env!("CARGO_PKG_VERSION"))
+    .author(env!("CARGO_PKG_AUTHORS"))
+    .about(env!("CARGO_PKG_DESCRIPTION"))
+    .arg(
+      Arg::with_name("CHAIN")
+          .index(1)
+          .possible_values(&["westend", "kusama", "polkadot", "paseo"])
+          .help(
+            "Sets the substrate-based chain for which 'scouty' will try to connect",
+          )
+    )
+    .arg(
+      Arg::with_name("debug")
+        .long("debug")
+        .help("Prints debug information verbosely."))
+    .arg(
+      Arg::with_name("quiet")
+        .long("quiet")
+        .short("q")
+        .conflicts_with("debug")
+        .help("Prints only warnings and errors. It takes precedence over SCOUTY_IS_DEBUG, but not over --log-level."))
+    .arg(
+      Arg::with_name("log-level")
+        .long("log-level")
+        .takes_value(true)
+        .possible_values(&["error", "warn", "info", "debug", "trace"])
+        .help("Maximum level of the log lines. If defined it takes precedence over the --debug flag. [default: info]"))
+    .arg(
+      Arg::with_name("log-format")
+        .long("log-format")
+        .takes_value(true)
+        .possible_values(&["text", "json"])
+        .help("Format of the log lines. With 'json' each line is a JSON object with the fields 'ts', 'level', 'target' and 'msg'. [default: text]"))
+    .arg(
+      Arg::with_name("no-color")
+        .long("no-color")
+        .help("Disable colors in the log lines. Colors are also disabled if the NO_COLOR environment variable is defined or if the output is not a terminal."))
+    .arg(
+      Arg::with_name("log-file-path")
+        .long("log-file-path")
+        .takes_value(true)
+        .value_name("FILE")
+        .help("File the log lines are appended to instead of stderr. (e.g. /var/log/scouty/scouty.log)"))
+    .arg(
+      Arg::with_name("log-file-max-size-mb")
+        .long("log-file-max-size-mb")
+        .takes_value(true)
+        .value_name("MB")
+        .help("Size in MB at which the log file is rotated, the rotated files are named <FILE>.1 (the most recent) to <FILE>.<N>. [default: 0 (never)]"))
+    .arg(
+      Arg::with_name("log-file-max-files")
+        .long("log-file-max-files")
+        .takes_value(true)
+        .value_name("N")
+        .help("Number of rotated log files kept, the oldest one is removed on rotation. With 0 the log file is truncated instead. [default: 5]"))
+    .arg(
+      Arg::with_name("matrix-user")
+        .long("matrix-user")
+        .takes_value(true)
+        .help("Your regular matrix user. e.g. '@your-regular-matrix-account:matrix.org' this user account will receive notifications from your other 'Scouty Bot' matrix account."))
+    .arg(
+          Arg::with_name("matrix-bot-user")
+            .long("matrix-bot-user")
+            .takes_value(true)
+            .help("Your new 'Scouty Bot' matrix user. e.g. '@your-own-scouty-bot-account:matrix.org' this user account will be your 'Scouty Bot' which will be responsible to send messages/notifications to your private 'Scouty Bot' room."))
+    .arg(
+      Arg::with_name("matrix-homeserver-url")
+        .long("matrix-homeserver-url")
+        .takes_value(true)
+        .value_name("URL")
+        .help("Base url of a self-hosted matrix homeserver on which the 'Scouty Bot' user is registered. (e.g. https://matrix.mycompany.com) [default: https://matrix.org]"))
+    .arg(
+      Arg::with_name("matrix-format")
+        .long("matrix-format")
+        .takes_value(true)
+        .possible_values(&["plain", "html"])
+        .help("Format of the messages sent to the matrix room. With 'html' stash names are bold and link to the block explorer, with 'plain' only the text is sent. [default: html]"))
+    .arg(
+      Arg::with_name("matrix-rooms")
+        .long("matrix-rooms")
+        .takes_value(true)
+        .value_name("ROOMS")
+        .help("Matrix rooms the messages are sent to instead of the private room, each one optionally filtered by the events it receives (e.g. '!oncall:matrix.org=slash|chill,!general:matrix.org=session'). Events: init, session, slash, chill, offline, democracy, heartbeat, commission, stake, stash_slashed, rewards, daily, error and test. A room without events receives them all."))
+    .arg(
+      Arg::with_name("matrix-bot-password")
+        .long("matrix-bot-password")
+        .takes_value(true)
+        .help("Password for the 'Scouty Bot' matrix user sign in."))
+    .arg(
+      Arg::with_name("matrix-bot-password-path")
+        .long("matrix-bot-password-path")
+        .takes_value(true)
+        .value_name("FILE")
+        .help("Sets the path for the file containing the password for the 'Scouty Bot' matrix user sign in. (NOTE: matrix-bot-password-path takes precedence than matrix-bot-password)"))
+    .arg(
+      Arg::with_name("disable-matrix")
+        .long("disable-matrix")
+        .help(
+          "Disable matrix bot for 'scouty'. (e.g. with this flag active 'scouty' will not send messages/notifications to your private 'Scouty Bot' room) (https://matrix.org/)",
+        ),
+    )
+    .arg(
+      Arg::with_name("disable-matrix-bot-display-name")
+        .long("disable-matrix-bot-display-name")
+        .help(
+          "Disable matrix bot display name update for 'scouty'. (e.g. with this flag active 'scouty' will not change the matrix bot user display name)",
+        ),
+      )
+    .arg(
+      Arg::with_name("telegram-bot-token")
+        .long("telegram-bot-token")
+        .takes_value(true)
+        .help("Token of your 'Scouty Bot' telegram bot. e.g. '123456789:AAE...' the token given by the telegram @BotFather when the bot was created."))
+    .arg(
+      Arg::with_name("telegram-chat-id")
+        .long("telegram-chat-id")
+        .takes_value(true)
+        .help("Telegram chat id to which your 'Scouty Bot' telegram bot will send messages/notifications. e.g. '123456789'"))
+    .arg(
+      Arg::with_name("disable-telegram")
+        .long("disable-telegram")
+        .help(
+          "Disable telegram bot for 'scouty'. (e.g. with this flag active 'scouty' will not send messages/notifications to your telegram chat) (https://telegram.org/)",
+        ),
+    )
+    .arg(
+      Arg::with_name("heartbeat-interval-hours")
+        .long("heartbeat-interval-hours")
+        .takes_value(true)
+        .value_name("HOURS")
+        .help("Interval in hours at which 'scouty' sends a heartbeat message with the current era and session, so that silence is never ambiguous. [default: 0 (off)]"))
+    .arg(
+      Arg::with_name("heartbeat-node-health")
+        .long("heartbeat-node-health")
+        .help("Include the peer count and the sync state of the substrate node in the heartbeat message, if the node supports the system_health RPC."))
+    .arg(
+      Arg::with_name("daily-report-enabled")
+        .long("daily-report-enabled")
+        .help("Sends a daily report summarizing the active sessions, the era points and the incidents of each stash in the last 24 hours."))
+    .arg(
+      Arg::with_name("daily-report-hour")
+        .long("daily-report-hour")
+        .takes_value(true)
+        .value_name("HOUR")
+        .help("Hour of the day (0-23) at which the daily report is sent, in the timezone defined by --timezone. [default: 0]"))
+    .arg(
+      Arg::with_name("notification-rate-limit-per-minute")
+        .long("notification-rate-limit-per-minute")
+        .takes_value(true)
+        .value_name("LIMIT")
+        .help("Maximum number of notifications sent per minute across all backends, notifications above the limit are suppressed. [default: 0 (unlimited)]"))
+    .arg(
+      Arg::with_name("notification-dedup-window-seconds")
+        .long("notification-dedup-window-seconds")
+        .takes_value(true)
+        .value_name("SECONDS")
+        .help("Window in seconds during which a notification identical to the previous one sent for the same stashes is suppressed. [default: 0 (off)]"))
+    .arg(
+      Arg::with_name("notification-prefix")
+        .long("notification-prefix")
+        .takes_value(true)
+        .value_name("TEXT")
+        .help("Text added at the start of every notification, e.g. to tell which 'scouty' instance sent it. (e.g. --notification-prefix '[kusama-01]')"))
+    .arg(
+      Arg::with_name("notification-suffix")
+        .long("notification-suffix")
+        .takes_value(true)
+        .value_name("TEXT")
+        .help("Text added at the end of every notification."))
+    .arg(
+      Arg::with_name("notify-on-change-only")
+        .long("notify-on-change-only")
+        .help("Sends the session notifications and runs the new session hook only for the Validator stashes whose active state changed since the previous session. The era hooks still run as usual."))
+    .arg(
+      Arg::with_name("suppress-startup-notifications")
+        .long("suppress-startup-notifications")
+        .help("Skips the notifications of the very first monitoring cycle after 'scouty' starts. The state observed is still recorded, so only subsequent changes are notified."))
+    .arg(
+      Arg::with_name("state-file-path")
+        .long("state-file-path")
+        .takes_value(true)
+        .value_name("FILE")
+        .help("Sets the path for a JSON file where 'scouty' keeps the last state observed of each Validator stash, so that a restart is not seen as a state change. A missing or corrupt file is ignored."))
+    .arg(
+      Arg::with_name("smtp-host")
+        .long("smtp-host")
+        .takes_value(true)
+        .help("SMTP server host through which 'scouty' will send email notifications. e.g. 'smtp.example.com' If not defined email notifications are disabled."))
+    .arg(
+      Arg::with_name("smtp-port")
+        .long("smtp-port")
+        .takes_value(true)
+        .help("SMTP server port, the connection is upgraded with STARTTLS. [default: 587]"))
+    .arg(
+      Arg::with_name("smtp-user")
+        .long("smtp-user")
+        .takes_value(true)
+        .help("User to authenticate on the SMTP server."))
+    .arg(
+      Arg::with_name("smtp-password")
+        .long("smtp-password")
+        .takes_value(true)
+        .help("Password to authenticate on the SMTP server."))
+    .arg(
+      Arg::with_name("smtp-from")
+        .long("smtp-from")
+        .takes_value(true)
+        .help("Sender address of the email notifications. e.g. 'Scouty <scouty@example.com>'"))
+    .arg(
+      Arg::with_name("smtp-to")
+        .long("smtp-to")
+        .takes_value(true)
+        .help("Recipient addresses of the email notifications, separated by commas. e.g. 'alice@example.com,bob@example.com'"))
+    .arg(
+      Arg::with_name("disable-smtp")
+        .long("disable-smtp")
+        .help(
+          "Disable email notifications for 'scouty'. (e.g. with this flag active 'scouty' will not send messages/notifications by email)",
+        ),
+    )
+    .arg(
+      Arg::with_name("webhook-url")
+        .long("webhook-url")
+        .takes_value(true)
+        .help("Webhook url to which 'scouty' will POST a JSON notification on each new session and for validators that start active or inactive next era. e.g. 'https://example.com/scouty'"))
+    .arg(
+      Arg::with_name("disable-webhook")
+        .long("disable-webhook")
+        .help(
+          "Disable webhook notifications for 'scouty'. (e.g. with this flag active 'scouty' will not POST notifications to the webhook url)",
+        ),
+    )
+    .arg(
+      Arg::with_name("pagerduty-routing-key")
+        .long("pagerduty-routing-key")
+        .takes_value(true)
+        .value_name("ROUTING_KEY")
+        .help("Routing key of the PagerDuty Events API v2 integration through which 'scouty' triggers an incident when a validator is slashed, chilled or inactive next era. The incident is resolved once the validator is active next era again."))
+    .arg(
+      Arg::with_name("disable-pagerduty")
+        .long("disable-pagerduty")
+        .help("Disable PagerDuty incidents for 'scouty' without clearing the routing key."))
+    .arg(
+      Arg::with_name("vault-addr")
+        .long("vault-addr")
+        .takes_value(true)
+        .value_name("URL")
+        .help("Address of the HashiCorp Vault server from which 'scouty' reads at startup the secrets still empty that have a vault path defined. (e.g. https://vault.example.com:8200) [default: $VAULT_ADDR]"))
+    .arg(
+      Arg::with_name("vault-token")
+        .long("vault-token")
+        .takes_value(true)
+        .help("Token to authenticate on the HashiCorp Vault server. [default: $VAULT_TOKEN]"))
+    .arg(
+      Arg::with_name("matrix-bot-password-vault-path")
+        .long("matrix-bot-password-vault-path")
+        .takes_value(true)
+        .value_name("PATH")
+        .help("Vault KV v2 path of the password for the 'Scouty Bot' matrix user sign in, as <mount>/<path>#<key> (e.g. secret/scouty#matrix_bot_password). The key defaults to matrix_bot_password."))
+    .arg(
+      Arg::with_name("telegram-bot-token-vault-path")
+        .long("telegram-bot-token-vault-path")
+        .takes_value(true)
+        .value_name("PATH")
+        .help("Vault KV v2 path of the telegram bot token, as <mount>/<path>#<key>. The key defaults to telegram_bot_token."))
+    .arg(
+      Arg::with_name("smtp-password-vault-path")
+        .long("smtp-password-vault-path")
+        .takes_value(true)
+        .value_name("PATH")
+        .help("Vault KV v2 path of the SMTP password, as <mount>/<path>#<key>. The key defaults to smtp_password."))
+    .arg(
+      Arg::with_name("pagerduty-routing-key-vault-path")
+        .long("pagerduty-routing-key-vault-path")
+        .takes_value(true)
+        .value_name("PATH")
+        .help("Vault KV v2 path of the PagerDuty routing key, as <mount>/<path>#<key>. The key defaults to pagerduty_routing_key."))
+    .arg(
+      Arg::with_name("substrate-ws-tls-fingerprint")
+        .long("substrate-ws-tls-fingerprint")
+        .takes_value(true)
+        .help("SHA-256 fingerprint (hex) of the TLS certificate of the substrate node. If defined 'scouty' aborts the connection to a wss:// endpoint that presents a different certificate, the certificate authority is not checked so self-signed certificates are accepted. (e.g. as given by 'openssl x509 -noout -fingerprint -sha256')"))
+    .arg(
+      Arg::with_name("substrate-ws-proxy")
+        .long("substrate-ws-proxy")
+        .takes_value(true)
+        .value_name("PROXY_URL")
+        .help("HTTP (CONNECT) or SOCKS5 proxy through which 'scouty' connects to the substrate node. e.g. 'http://proxy.example.com:3128' or 'socks5://proxy.example.com:1080' If not defined the https_proxy (wss://), http_proxy (ws://) or all_proxy environment variables are used, unless the host is listed in no_proxy."))
+    .arg(
+      Arg::with_name("rpc-client-name")
+        .long("rpc-client-name")
+        .takes_value(true)
+        .value_name("NAME")
+        .help("Name that 'scouty' identifies itself with when connecting to the substrate node, sent as the User-Agent to HTTP proxies and logged on every connection. [default: scouty/<version>]"))
+    .arg(
+      Arg::with_name("connect-timeout-seconds")
+        .long("connect-timeout-seconds")
+        .takes_value(true)
+        .value_name("SECONDS")
+        .help("Maximum time (in seconds) to establish a connection to the substrate node before trying the next one. A value of 0 means no timeout. [default: 30]"))
+    .arg(
+      Arg::with_name("request-timeout-seconds")
+        .long("request-timeout-seconds")
+        .takes_value(true)
+        .value_name("SECONDS")
+        .help("Maximum time (in seconds) an individual RPC request may take before 'scouty' restarts. A value of 0 means no timeout. [default: 60]"))
+    .arg(
+      Arg::with_name("startup-delay-seconds")
+        .long("startup-delay-seconds")
+        .takes_value(true)
+        .value_name("SECONDS")
+        .help("Time (in seconds) 'scouty' waits before the first connection attempt, e.g. to let a local node started at the same time get ready. [default: 0]"))
+    .arg(
+      Arg::with_name("confirmation-blocks")
+        .long("confirmation-blocks")
+        .takes_value(true)
+        .value_name("BLOCKS")
+        .help("Number of blocks that must be seen on top of a block before the hooks and notifications of its events are fired, the events of a block replaced by a reorg in the meantime are dropped. It only matters with --follow-latest, since finalized blocks are never reverted. (NOTE: events still pending are dropped if the subscription restarts) [default: 0]"))
+    .arg(
+      Arg::with_name("follow-latest")
+        .long("follow-latest")
+        .conflicts_with("follow-finalized")
+        .help("Follows the latest blocks of the chain, the events are seen sooner but may be reverted by a reorg. (NOTE: combine it with --confirmation-blocks for some safety)"))
+    .arg(
+      Arg::with_name("follow-finalized")
+        .long("follow-finalized")
+        .help("Follows the finalized blocks of the chain, the events are seen later but are final. This is the default."))
+    .arg(
+      Arg::with_name("prometheus-listen-addr")
+        .long("prometheus-listen-addr")
+        .takes_value(true)
+        .value_name("ADDRESS")
+        .help("Address on which 'scouty' exposes Prometheus metrics at /metrics. (e.g. 0.0.0.0:9090) If not defined the metrics server is not started."))
+    .arg(
+      Arg::with_name("healthcheck-listen-addr")
+        .long("healthcheck-listen-addr")
+        .takes_value(true)
+        .value_name("ADDRESS")
+        .help("Address on which 'scouty' exposes a liveness probe at /health. (e.g. 0.0.0.0:8080) It responds 200 if the last RPC interaction succeeded within twice the interval, or 503 otherwise. If not defined the healthcheck server is not started."))
+    .arg(
+      Arg::with_name("api-listen-addr")
+        .long("api-listen-addr")
+        .takes_value(true)
+        .value_name("ADDRESS")
+        .help("Address on which 'scouty' exposes a read-only JSON API with the monitored stashes at /stashes and its own status at /status. A port alone binds the loopback interface (e.g. 8081 is served on 127.0.0.1:8081), define the host to expose it further. If not defined the API server is not started."))
+    .arg(
+      Arg::with_name("max-concurrent-queries")
+        .long("max-concurrent-queries")
+        .takes_value(true)
+        .value_name("NUMBER")
+        .help("Maximum number of per-stash queries that 'scouty' sends to the substrate node at the same time. [default: 4]"))
+    .arg(
+      Arg::with_name("expected-spec-version")
+        .long("expected-spec-version")
+        .takes_value(true)
+        .value_name("SPEC_VERSION")
+        .help("Runtime spec version known to be supported by 'scouty'. If defined 'scouty' logs a warning at startup when the connected chain runs a different spec version. (e.g. 9130)"))
+    .arg(
+      Arg::with_name("print-config")
+        .long("print-config")
+        .takes_value(true)
+        .value_name("FORMAT")
+        .possible_values(&["env", "toml"])
+        .help("Prints the effective configuration, after combining CLI flags, env vars and config files, in the given format and exits. Secrets are redacted."))
+    .arg(
+      Arg::with_name("once")
+        .long("once")
+        .help("Runs a single monitoring pass for all the stashes defined, firing the init hook and notification and the session hooks and notifications of the current session, and exits with a non-zero code on error (e.g. with this flag active 'scouty' can be scheduled by cron instead of running as a daemon)"))
+    .arg(
+      Arg::with_name("dry-run")
+        .long("dry-run")
+        .help("Prints the resolved configuration and exits without connecting to the substrate node (e.g. with this flag active 'scouty' will only show which chain, endpoints, stashes, hooks and matrix settings would be used)"))
+    .arg(
+      Arg::with_name("short")
+        .long("short")
+        .help("Display only essential information (e.g. with this flag active 'scouty' will hide certain sections in a message)"))
+    .arg(
+      Arg::with_name("error-interval")
+        .long("error-interval")
+        .takes_value(true)
+        .default_value("30")
+        .help("Interval value from which 'scouty' will restart again in case of a critical error. Accepts durations like '30m' or '1h', bare integers are in minutes."))
+    .arg(
+      Arg::with_name("error-interval-max")
+        .long("error-interval-max")
+        .takes_value(true)
+        .help("Maximum interval from which 'scouty' will restart again in case of consecutive critical errors. The error interval doubles on every consecutive error, with a random jitter, up to this maximum. Accepts durations like '6h', bare integers are in minutes. [default: 6h]"))
+    .arg(
+      Arg::with_name("max-restarts")
+        .long("max-restarts")
+        .takes_value(true)
+        .value_name("NUMBER")
+        .help("Maximum number of consecutive restarts after critical errors. Once exceeded 'scouty' exits with a non-zero code, so that a service manager like systemd takes over. If not defined 'scouty' restarts indefinitely."))
+    .arg(
+      Arg::with_name("stashes")
+        .short("s")
+        .long("stashes")
+        .takes_value(true)
+        .help(
+          "Validator stash addresses for which 'scouty' will take a particular eye. If needed specify more than one (e.g. stash_1,stash_2,stash_3). Optionally give each stash a label to be displayed in notifications (e.g. stash_1:MyValidator01,stash_2) and a hook path that overrides the one defined by --hook-validator-starts-active-next-era-path for that stash only (e.g. stash_1:MyValidator01:/opt/scouty-cli/hooks/_my_validator_01.sh,stash_2::/opt/scouty-cli/hooks/_stash_2.sh).",
+        ),
+    )
+    .arg(
+      Arg::with_name("stashes-path")
+        .long("stashes-path")
+        .takes_value(true)
+        .value_name("FILE")
+        .help(
+          "Sets the path for a file with one Validator stash address per line, optionally labeled (e.g. stash_1:MyValidator01). Blank lines and lines starting with '#' are ignored. The stashes are merged with the ones defined by --stashes.",
+        ),
+    )
+    .arg(
+      Arg::with_name("max-stashes")
+        .long("max-stashes")
+        .takes_value(true)
+        .value_name("NUMBER")
+        .help("Maximum number of stashes that 'scouty' accepts to monitor. If defined 'scouty' refuses to start when more stashes are defined, as a safety measure against flooding the substrate node with queries."))
+    .arg(
+      Arg::with_name("substrate-ws-url")
+        .short("w")
+        .long("substrate-ws-url")
+        .takes_value(true)
+        .help(
+          "Substrate websocket endpoint for which 'scouty' will try to connect. (e.g. wss://kusama-rpc.polkadot.io) (NOTE: substrate_ws_url takes precedence than <CHAIN> argument)",
+        ),
+    )
+    .arg(
+      Arg::with_name("substrate-ws-urls")
+        .long("substrate-ws-urls")
+        .takes_value(true)
+        .help(
+          "Fallback substrate websocket endpoints for which 'scouty' will try to connect in order, rotating to the next one on connection failure. If needed specify more than one (e.g. url_1,url_2,url_3). Each endpoint may carry a priority as <url>|<priority> (e.g. wss://mynode:9944|10,wss://rpc.polkadot.io:443|1), the higher priority endpoints are tried first.",
+        ),
+    )
+    .arg(
+      Arg::with_name("endpoint-promotion-interval")
+        .long("endpoint-promotion-interval")
+        .takes_value(true)
+        .value_name("DURATION")
+        .help("Interval from which 'scouty', while connected to a lower priority substrate endpoint, checks if a higher priority one is available again to reconnect to it. Accepts durations like '10m', bare integers are in seconds, zero means never. [default: 10m]"))
+    .arg(
+      Arg::with_name("ss58-prefix")
+        .long("ss58-prefix")
+        .takes_value(true)
+        .value_name("PREFIX")
+        .help("SS58 address prefix used to check and display the stash addresses. It takes precedence over the prefix of the <CHAIN> argument, useful for custom chains. (e.g. 42)"))
+    .arg(
+      Arg::with_name("token-decimals")
+        .long("token-decimals")
+        .takes_value(true)
+        .value_name("DECIMALS")
+        .help("Number of decimals of the chain token used to format the balances. If not defined the decimals are read from the chain properties. (e.g. 12)"))
+    .arg(
+      Arg::with_name("token-symbol")
+        .long("token-symbol")
+        .takes_value(true)
+        .value_name("SYMBOL")
+        .help("Symbol of the chain token used to format the balances. If not defined the symbol is read from the chain properties. (e.g. KSM)"))
+    .arg(
+      Arg::with_name("explorer-base-url")
+        .long("explorer-base-url")
+        .takes_value(true)
+        .value_name("URL")
+        .help("Base url of the block explorer linked in the notifications. (e.g. https://kusama.subscan.io) [default: the Subscan url of the chain, custom chains have no links]"))
+    .arg(
+      Arg::with_name("timezone")
+        .long("timezone")
+        .takes_value(true)
+        .value_name("TIMEZONE")
+        .help("IANA timezone the timestamps in the notifications are rendered in. (e.g. Europe/Lisbon) [default: UTC]"))
+    .arg(
+      Arg::with_name("config-path")
+        .short("c")
+        .long("config-path")
+        .takes_value(true)
+        .value_name("FILE")
+        .default_value(".env")
+        .help(
+          "Sets a custom config file path. The config file contains 'scouty' configuration variables. Files with a '.toml' extension are loaded as TOML, otherwise as dotenv.",
+        ),
+    )
+    .arg(
+      Arg::with_name("env-prefix")
+        .long("env-prefix")
+        .takes_value(true)
+        .value_name("PREFIX")
+        .help("Prefix of the env vars from which 'scouty' reads its configuration, so that multiple instances can share the same environment (e.g. SCOUTY_A_). Env vars with the 'SCOUTY_' prefix are then ignored, config files keep using it. [default: SCOUTY_]"))
+    .arg(
+      Arg::with_name("expose-network")
+        .long("expose-network")
+        .help(
+          "Expose the network name, token symbol and token decimal under new positional arguments for each hook.",
+        ),
+      )
+    .arg(
+      Arg::with_name("expose-nominators")
+        .long("expose-nominators")
+        .help(
+          "Expose ACTIVE nominator details under new positional arguments for some of the hooks. Note: `scouty` only look after active nominators for each validator stash predefined.",
+        ),
+      )
+    .arg(
+        Arg::with_name("expose-authored-blocks")
+          .long("expose-authored-blocks")
+          .help(
+            "Expose the number of blocks authored by each validator stash predefined.",
+          ),
+        )
+    .arg(
+      Arg::with_name("expose-all-nominators")
+        .long("expose-all-nominators")
+        .help(
+          "Expose ALL nominator details under new positional arguments for some of the hooks. Note: `scouty` only look after all nominators for each validator stash predefined.",
+        ),
+      )
+    .arg(
+      Arg::with_name("expose-para-validator")
+        .long("expose-para-validator")
+        .help(
+          "Expose the para validator details under new positional arguments for some of the hooks.",
+        ),
+      )
+    .arg(
+      Arg::with_name("expose-era-points")
+        .long("expose-era-points")
+        .help(
+          "Expose the era points details under new positional arguments for the `_new_era` hook.",
+        ),
+      )
+    .arg(
+      Arg::with_name("expose-all")
+        .long("expose-all")
+        .help(
           "Expose all positional arguments for some of the hooks. Note: Each hook bash script describes which data is available through the positional arguments.",
         ),
       )
@@ -292,6 +3340,37 @@ fn get_config() -> Config {
           "Sets the path for the script that is called on the last session of an era, if the stash is ACTIVE and keys are NOT QUEUED for the next Session/Era.",
         ),
     )
+    .arg(
+      Arg::with_name("hook-timeout-seconds")
+        .long("hook-timeout-seconds")
+        .takes_value(true)
+        .help("Maximum time (in seconds) a hook script is allowed to run before 'scouty' kills it and carries on. A value of 0 means no timeout. [default: 60]"))
+    .arg(
+      Arg::with_name("hooks-async")
+        .long("hooks-async")
+        .help("Run the hook scripts in the background instead of waiting for them to finish, their completion or failure is logged once they exit. (NOTE: the output of async hooks is not included in the notifications)"))
+    .arg(
+      Arg::with_name("hook-failure-fatal")
+        .long("hook-failure-fatal")
+        .help("Exit with a non-zero code when a hook script exits non-zero, instead of logging it and restarting the subscription. (NOTE: the failure of async hooks is only logged)"))
+    .arg(
+      Arg::with_name("hook-extra-args")
+        .long("hook-extra-args")
+        .takes_value(true)
+        .value_name("ARGS")
+        .help("Static args appended to every hook invocation, e.g. to tell apart multiple 'scouty' instances sharing the same scripts. The args are split as shell words, so quote any arg containing spaces. (e.g. --hook-extra-args '--region eu --label \"a b\"')"))
+    .arg(
+      Arg::with_name("disable-hook-new-session")
+        .long("disable-hook-new-session")
+        .help("Disable the new session hook without clearing its path. (e.g. with this flag active 'scouty' will not call the script defined by hook-new-session-path)"))
+    .arg(
+      Arg::with_name("disable-hook-active-next-era")
+        .long("disable-hook-active-next-era")
+        .help("Disable the validator starts active next era hook without clearing its path. (e.g. with this flag active 'scouty' will not call the script defined by hook-validator-starts-active-next-era-path)"))
+    .arg(
+      Arg::with_name("disable-hook-inactive-next-era")
+        .long("disable-hook-inactive-next-era")
+        .help("Disable the validator starts inactive next era hook without clearing its path. (e.g. with this flag active 'scouty' will not call the script defined by hook-validator-starts-inactive-next-era-path)"))
     .arg(
       Arg::with_name("hook-validator-slashed-path")
         .long("hook-validator-slashed-path")
@@ -307,7 +3386,7 @@ fn get_config() -> Config {
         .takes_value(true)
         .value_name("FILE")
         .help(
-          "Sets the path for the script that is called every time one of the Validator stashes defined is chilled.",
+          "Sets the path for the script that is called every time one of the Validator stashes defined is chilled or disabled. Whether the chill was voluntary, forced or the stash was disabled is exported as SCOUTY_CHILL_KIND.",
         ),
     )
     .arg(
@@ -319,198 +3398,2764 @@ fn get_config() -> Config {
           "Sets the path for the script that is called every time one of the Validator stashes defined is offline at the end of a session.",
         ),
     )
-    .get_matches();
+    .arg(
+      Arg::with_name("hook-validator-commission-changed-path")
+        .long("hook-validator-commission-changed-path")
+        .takes_value(true)
+        .value_name("FILE")
+        .help(
+          "Sets the path for the script that is called every time one of the Validator stashes defined changes its commission. The old and new commissions are exported in parts per billion as SCOUTY_OLD_COMMISSION and SCOUTY_NEW_COMMISSION.",
+        ),
+    )
+    .arg(
+      Arg::with_name("disable-commission-monitoring")
+        .long("disable-commission-monitoring")
+        .help(
+          "Disable commission monitoring for 'scouty'. (e.g. with this flag active 'scouty' will not check the commission of the Validator stashes defined every new era)",
+        ),
+    )
+    .arg(
+      Arg::with_name("stake-change-threshold-percent")
+        .long("stake-change-threshold-percent")
+        .takes_value(true)
+        .value_name("PERCENT")
+        .help("Notify when the nominators count or the total stake backing one of the active Validator stashes defined changes by more than this percent from one era to the next. [default: 0 (off)]"))
+    .arg(
+      Arg::with_name("message-templates-path")
+        .long("message-templates-path")
+        .takes_value(true)
+        .value_name("FILE")
+        .help(
+          "Sets the path for a TOML file of named message templates (new_session, active_next_era, inactive_next_era) with variables like {{stash_name}}, {{era}} or {{session}}. If not defined the built-in messages are used.",
+        ),
+    )
+    .arg(
+      Arg::with_name("disable-chill-monitoring")
+        .long("disable-chill-monitoring")
+        .help(
+          "Disable chill monitoring for 'scouty'. (e.g. with this flag active 'scouty' will not notify when one of the Validator stashes defined is chilled or disabled)",
+        ),
+    )
+    .arg(
+      Arg::with_name("enabled-events")
+        .long("enabled-events")
+        .takes_value(true)
+        .value_name("EVENTS")
+        .help("Comma-separated list of the only events 'scouty' monitors, e.g. 'new_session,slash,inactive_next_era'. Possible events: new_session, new_era, active_next_era, inactive_next_era, slash, stash_slashed, chill, offline, democracy, commission, rewards. The disable flags still apply to the events listed. [default: all events]"))
+    .arg(
+      Arg::with_name("hook-slash-path")
+        .long("hook-slash-path")
+        .takes_value(true)
+        .value_name("FILE")
+        .help(
+          "Sets the path for the script that is called every time one of the Validator stashes defined is slashed. The slashed amount and the active era are passed as arguments.",
+        ),
+    )
+    .arg(
+      Arg::with_name("disable-slash-monitoring")
+        .long("disable-slash-monitoring")
+        .help(
+          "Disable slash monitoring for 'scouty'. (e.g. with this flag active 'scouty' will not send a high-priority notification when one of the Validator stashes defined is slashed)",
+        ),
+    )
+    .arg(
+      Arg::with_name("hook-unclaimed-rewards-path")
+        .long("hook-unclaimed-rewards-path")
+        .takes_value(true)
+        .value_name("FILE")
+        .help(
+          "Sets the path for the script that is called every new era if one of the Validator stashes defined has unclaimed rewards. The unclaimed eras are exported comma separated as SCOUTY_UNCLAIMED_ERAS.",
+        ),
+    )
+    .arg(
+      Arg::with_name("disable-rewards-monitoring")
+        .long("disable-rewards-monitoring")
+        .help(
+          "Disable rewards monitoring for 'scouty'. (e.g. with this flag active 'scouty' will not check for unclaimed rewards of the Validator stashes defined every new era)",
+        ),
+    )
+    .arg(
+      Arg::with_name("rewards-lookback-eras")
+        .long("rewards-lookback-eras")
+        .takes_value(true)
+        .value_name("ERAS")
+        .help(
+          "Sets the number of past eras to check for unclaimed rewards every new era. [default: 14]",
+        ),
+    )
+    .subcommand(
+      SubCommand::with_name("daemon")
+        .about("Subscribes to on-chain events and keeps running (default)"))
+    .subcommand(
+      SubCommand::with_name("view")
+        .about("Prints the active status and the next era queue of each stash, then exits"))
+    .subcommand(
+      SubCommand::with_name("test-notifications")
+        .about("Sends a test message to every notification backend enabled, then exits with a non-zero code if any of them failed"))
+    .subcommand(
+      SubCommand::with_name("list-chains")
+        .about("Prints each chain preset with its websocket url, SS58 prefix, token symbol and decimals, then exits"))
+    .subcommand(
+      SubCommand::with_name("config-example")
+        .about("Prints a commented example configuration with every config variable, its default and a description, then exits")
+        .arg(
+          Arg::with_name("format")
+            .long("format")
+            .takes_value(true)
+            .value_name("FORMAT")
+            .possible_values(&["env", "toml"])
+            .help("Format of the example configuration. [default: env]")))
+    .subcommand(
+      SubCommand::with_name("validate")
+        .about("Checks the configuration without connecting to the substrate node, then exits with a non-zero code and the list of problems found if it is not valid")
+        .arg(
+          Arg::with_name("config-path")
+            .short("c")
+            .long("config-path")
+            .takes_value(true)
+            .value_name("FILE")
+            .help("Sets the path of the config file to check. [default: .env]")))
+    .subcommand(
+      SubCommand::with_name("decode")
+        .about("Prints the public key and the SS58 prefix of the address, and whether it matches the prefix of the chain, then exits")
+        .arg(
+          Arg::with_name("ADDRESS")
+            .required(true)
+            .index(1)
+            .help("Sets the SS58 address to decode, e.g. a stash to sanity-check before adding it")))
+}
+
+/// Inject dotenv and env vars into the Config struct
+fn get_config() -> Config {
+    let matches = cli().get_matches();
+
+    // The env prefix is read before any other variable, from the CLI flag or
+    // from the one env var that always keeps the default prefix
+    if let Some(env_prefix) = matches
+        .value_of("env-prefix")
+        .map(|prefix| prefix.to_string())
+        .or_else(|| env::var("SCOUTY_ENV_PREFIX").ok())
+    {
+        with_env_prefix(&env_prefix);
+    }
+
+    let mut reload_source = ReloadSource {
+        initial_env: scouty_env_vars(),
+        ..Default::default()
+    };
+
+    // Try to load configuration from file first
+    let validate_config_path = matches
+        .subcommand_matches("validate")
+        .and_then(|matches| matches.value_of("config-path"));
+    let config_path = validate_config_path
+        .or_else(|| matches.value_of("config-path"))
+        .unwrap_or(".env");
+
+    // The file given to the validate command must exist
+    if validate_config_path.is_some() && !Path::new(config_path).exists() {
+        exit_on_config_error(format!("Config file {} not found", config_path));
+    }
+
+    let mut config_files = ConfigFiles::default();
+    match load_config_file(&config_path) {
+        Ok(loaded) if !loaded.paths.is_empty() => {
+            for path in loaded.paths.iter() {
+                info!("Loading configuration from {} file", path);
+            }
+            reload_source.config_path = Some(config_path.to_string());
+            config_files = loaded;
+        }
+        Ok(_) => {
+            let config_path =
+                env::var("SCOUTY_CONFIG_FILENAME").unwrap_or(".env".to_string());
+            match load_config_file(&config_path) {
+                Ok(loaded) if !loaded.paths.is_empty() => {
+                    for path in loaded.paths.iter() {
+                        info!("Loading configuration from {} file", path);
+                    }
+                    reload_source.config_path = Some(config_path.to_string());
+                    config_files = loaded;
+                }
+                Ok(_) => (),
+                Err(e) => exit_on_config_error(e),
+            }
+        }
+        Err(e) => exit_on_config_error(e),
+    }
+
+    let file_env = scouty_env_vars();
+
+    match matches
+        .value_of("CHAIN")
+        .and_then(|chain| chain.parse::<ChainPreset>().ok())
+    {
+        Some(chain) => {
+            env::set_var("SCOUTY_CHAIN", chain.to_string());
+            env::set_var("SCOUTY_SUBSTRATE_WS_URL", chain.ws_url());
+        }
+        None => {
+            if env::var("SCOUTY_SUBSTRATE_WS_URL").is_err()
+                && env::var("SCOUTY_SUBSTRATE_WS_URLS").is_err()
+                && !config_files.table.contains_key("substrate_ws_url")
+                && !config_files.table.contains_key("substrate_ws_urls")
+                && matches.value_of("substrate-ws-urls").is_none()
+            {
+                env::set_var("SCOUTY_SUBSTRATE_WS_URL", "ws://127.0.0.1:9944");
+            };
+        }
+    }
+
+    if let Some(stashes) = matches.value_of("stashes") {
+        env::set_var("SCOUTY_STASHES", stashes);
+    }
+
+    if let Some(stashes_path) = matches.value_of("stashes-path") {
+        env::set_var("SCOUTY_STASHES_PATH", stashes_path);
+    }
+
+    if let Some(max_stashes) = matches.value_of("max-stashes") {
+        env::set_var("SCOUTY_MAX_STASHES", max_stashes);
+    }
+
+    if let Some(substrate_ws_url) = matches.value_of("substrate-ws-url") {
+        env::set_var("SCOUTY_SUBSTRATE_WS_URL", substrate_ws_url);
+    }
+
+    if let Some(substrate_ws_tls_fingerprint) =
+        matches.value_of("substrate-ws-tls-fingerprint")
+    {
+        env::set_var(
+            "SCOUTY_SUBSTRATE_WS_TLS_FINGERPRINT",
+            substrate_ws_tls_fingerprint,
+        );
+    }
+
+    if let Some(substrate_ws_urls) = matches.value_of("substrate-ws-urls") {
+        env::set_var("SCOUTY_SUBSTRATE_WS_URLS", substrate_ws_urls);
+    }
+
+    if let Some(interval) = matches.value_of("endpoint-promotion-interval") {
+        env::set_var("SCOUTY_ENDPOINT_PROMOTION_INTERVAL", interval);
+    }
+
+    if let Some(ss58_prefix) = matches.value_of("ss58-prefix") {
+        env::set_var("SCOUTY_SS58_PREFIX", ss58_prefix);
+    }
+
+    if let Some(token_decimals) = matches.value_of("token-decimals") {
+        env::set_var("SCOUTY_TOKEN_DECIMALS", token_decimals);
+    }
+
+    if let Some(token_symbol) = matches.value_of("token-symbol") {
+        env::set_var("SCOUTY_TOKEN_SYMBOL", token_symbol);
+    }
+
+    if let Some(explorer_base_url) = matches.value_of("explorer-base-url") {
+        env::set_var("SCOUTY_EXPLORER_BASE_URL", explorer_base_url);
+    }
+
+    if let Some(timezone) = matches.value_of("timezone") {
+        env::set_var("SCOUTY_TIMEZONE", timezone);
+    }
+
+    if matches.is_present("debug") {
+        env::set_var("SCOUTY_IS_DEBUG", "true");
+    }
+
+    if matches.is_present("quiet") {
+        env::set_var("SCOUTY_QUIET", "true");
+    }
+
+    if let Some(log_level) = matches.value_of("log-level") {
+        env::set_var("SCOUTY_LOG_LEVEL", log_level);
+    }
+
+    if matches.is_present("no-color") {
+        env::set_var("SCOUTY_NO_COLOR", "true");
+    }
+
+    if let Some(log_file_path) = matches.value_of("log-file-path") {
+        env::set_var("SCOUTY_LOG_FILE_PATH", log_file_path);
+    }
+
+    if let Some(log_file_max_size_mb) = matches.value_of("log-file-max-size-mb") {
+        env::set_var("SCOUTY_LOG_FILE_MAX_SIZE_MB", log_file_max_size_mb);
+    }
+
+    if let Some(log_file_max_files) = matches.value_of("log-file-max-files") {
+        env::set_var("SCOUTY_LOG_FILE_MAX_FILES", log_file_max_files);
+    }
+
+    if let Some(log_format) = matches.value_of("log-format") {
+        env::set_var("SCOUTY_LOG_FORMAT", log_format);
+    }
+
+    if matches.is_present("short") {
+        env::set_var("SCOUTY_IS_SHORT", "true");
+    }
+
+    if matches.is_present("dry-run") {
+        env::set_var("SCOUTY_IS_DRY_RUN", "true");
+    }
+
+    if matches.is_present("once") {
+        env::set_var("SCOUTY_RUN_ONCE", "true");
+    }
+
+    if let Some(print_config) = matches.value_of("print-config") {
+        env::set_var("SCOUTY_PRINT_CONFIG", print_config);
+    }
+
+    if let Some(command) = matches.subcommand_name() {
+        env::set_var("SCOUTY_COMMAND", command);
+    }
+
+    if let Some(format) = matches
+        .subcommand_matches("config-example")
+        .and_then(|matches| matches.value_of("format"))
+    {
+        env::set_var("SCOUTY_CONFIG_EXAMPLE_FORMAT", format);
+    }
+
+    if let Some(address) = matches
+        .subcommand_matches("decode")
+        .and_then(|matches| matches.value_of("ADDRESS"))
+    {
+        env::set_var("SCOUTY_DECODE_ADDRESS", address);
+    }
+
+    if let Some(hook_init_path) = matches.value_of("hook-init-path") {
+        env::set_var("SCOUTY_HOOK_INIT_PATH", hook_init_path);
+    }
+
+    if let Some(hook_new_session_path) = matches.value_of("hook-new-session-path") {
+        env::set_var("SCOUTY_HOOK_NEW_SESSION_PATH", hook_new_session_path);
+    }
+
+    if let Some(hook_new_era_path) = matches.value_of("hook-new-era-path") {
+        env::set_var("SCOUTY_HOOK_NEW_ERA_PATH", hook_new_era_path);
+    }
+
+    if let Some(hook_validator_starts_active_next_era_path) =
+        matches.value_of("hook-validator-starts-active-next-era-path")
+    {
+        env::set_var(
+            "SCOUTY_HOOK_VALIDATOR_STARTS_ACTIVE_NEXT_ERA_PATH",
+            hook_validator_starts_active_next_era_path,
+        );
+    }
+
+    if let Some(hook_validator_starts_inactive_next_era_path) =
+        matches.value_of("hook-validator-starts-inactive-next-era-path")
+    {
+        env::set_var(
+            "SCOUTY_HOOK_VALIDATOR_STARTS_INACTIVE_NEXT_ERA_PATH",
+            hook_validator_starts_inactive_next_era_path,
+        );
+    }
+
+    if let Some(hook_validator_slashed_path) =
+        matches.value_of("hook-validator-slashed-path")
+    {
+        env::set_var(
+            "SCOUTY_HOOK_VALIDATOR_SLASHED_PATH",
+            hook_validator_slashed_path,
+        );
+    }
+
+    if let Some(hook_validator_chilled_path) =
+        matches.value_of("hook-validator-chilled-path")
+    {
+        env::set_var(
+            "SCOUTY_HOOK_VALIDATOR_CHILLED_PATH",
+            hook_validator_chilled_path,
+        );
+    }
+
+    if let Some(hook_validator_offline_path) =
+        matches.value_of("hook-validator-offline-path")
+    {
+        env::set_var(
+            "SCOUTY_HOOK_VALIDATOR_OFFLINE_PATH",
+            hook_validator_offline_path,
+        );
+    }
+
+    if let Some(hook_validator_commission_changed_path) =
+        matches.value_of("hook-validator-commission-changed-path")
+    {
+        env::set_var(
+            "SCOUTY_HOOK_VALIDATOR_COMMISSION_CHANGED_PATH",
+            hook_validator_commission_changed_path,
+        );
+    }
+
+    if matches.is_present("disable-commission-monitoring") {
+        env::set_var("SCOUTY_COMMISSION_MONITORING_DISABLED", "true");
+    }
+
+    if let Some(threshold) = matches.value_of("stake-change-threshold-percent") {
+        env::set_var("SCOUTY_STAKE_CHANGE_THRESHOLD_PERCENT", threshold);
+    }
+
+    if let Some(message_templates_path) = matches.value_of("message-templates-path") {
+        env::set_var("SCOUTY_MESSAGE_TEMPLATES_PATH", message_templates_path);
+    }
+
+    if matches.is_present("disable-chill-monitoring") {
+        env::set_var("SCOUTY_CHILL_MONITORING_DISABLED", "true");
+    }
+
+    if let Some(enabled_events) = matches.value_of("enabled-events") {
+        env::set_var("SCOUTY_ENABLED_EVENTS", enabled_events);
+    }
+
+    if let Some(hook_slash_path) = matches.value_of("hook-slash-path") {
+        env::set_var("SCOUTY_HOOK_SLASH_PATH", hook_slash_path);
+    }
+
+    if matches.is_present("disable-slash-monitoring") {
+        env::set_var("SCOUTY_SLASH_MONITORING_DISABLED", "true");
+    }
+
+    if let Some(hook_unclaimed_rewards_path) =
+        matches.value_of("hook-unclaimed-rewards-path")
+    {
+        env::set_var(
+            "SCOUTY_HOOK_UNCLAIMED_REWARDS_PATH",
+            hook_unclaimed_rewards_path,
+        );
+    }
+
+    if matches.is_present("disable-rewards-monitoring") {
+        env::set_var("SCOUTY_REWARDS_MONITORING_DISABLED", "true");
+    }
+
+    if let Some(rewards_lookback_eras) = matches.value_of("rewards-lookback-eras") {
+        env::set_var("SCOUTY_REWARDS_LOOKBACK_ERAS", rewards_lookback_eras);
+    }
+
+    if let Some(hook_democracy_started_path) =
+        matches.value_of("hook-democracy-started-path")
+    {
+        env::set_var(
+            "SCOUTY_HOOK_DEMOCRACY_STARTED_PATH",
+            hook_democracy_started_path,
+        );
+    }
+
+    if matches.is_present("expose-all") {
+        env::set_var("SCOUTY_EXPOSE_ALL", "true");
+    }
+
+    if matches.is_present("expose-network") {
+        env::set_var("SCOUTY_EXPOSE_NETWORK", "true");
+    }
+
+    if matches.is_present("expose-nominators") {
+        env::set_var("SCOUTY_EXPOSE_NOMINATORS", "true");
+    }
+
+    if matches.is_present("expose-all-nominators") {
+        env::set_var("SCOUTY_EXPOSE_ALL_NOMINATORS", "true");
+    }
+
+    if matches.is_present("expose-authored-blocks") {
+        env::set_var("SCOUTY_EXPOSE_AUTHORED_BLOCKS", "true");
+    }
+
+    if matches.is_present("expose-para-validator") {
+        env::set_var("SCOUTY_EXPOSE_PARA_VALIDATOR", "true");
+    }
+
+    if matches.is_present("expose-era-points") {
+        env::set_var("SCOUTY_EXPOSE_ERA_POINTS", "true");
+    }
+
+    if matches.is_present("expose-all") {
+        env::set_var("SCOUTY_EXPOSE_ALL", "true");
+    }
+
+    if let Some(hook_timeout_seconds) = matches.value_of("hook-timeout-seconds") {
+        env::set_var("SCOUTY_HOOK_TIMEOUT_SECONDS", hook_timeout_seconds);
+    }
+
+    if matches.is_present("hooks-async") {
+        env::set_var("SCOUTY_HOOKS_ASYNC", "true");
+    }
+
+    if matches.is_present("hook-failure-fatal") {
+        env::set_var("SCOUTY_HOOK_FAILURE_FATAL", "true");
+    }
+
+    if let Some(hook_extra_args) = matches.value_of("hook-extra-args") {
+        env::set_var("SCOUTY_HOOK_EXTRA_ARGS", hook_extra_args);
+    }
+
+    if matches.is_present("disable-hook-new-session") {
+        env::set_var("SCOUTY_HOOK_NEW_SESSION_DISABLED", "true");
+    }
+
+    if matches.is_present("disable-hook-active-next-era") {
+        env::set_var("SCOUTY_HOOK_ACTIVE_NEXT_ERA_DISABLED", "true");
+    }
+
+    if matches.is_present("disable-hook-inactive-next-era") {
+        env::set_var("SCOUTY_HOOK_INACTIVE_NEXT_ERA_DISABLED", "true");
+    }
+
+    if let Some(substrate_ws_proxy) = matches.value_of("substrate-ws-proxy") {
+        env::set_var("SCOUTY_SUBSTRATE_WS_PROXY", substrate_ws_proxy);
+    }
+
+    if let Some(rpc_client_name) = matches.value_of("rpc-client-name") {
+        env::set_var("SCOUTY_RPC_CLIENT_NAME", rpc_client_name);
+    }
+
+    if let Some(connect_timeout_seconds) = matches.value_of("connect-timeout-seconds") {
+        env::set_var("SCOUTY_CONNECT_TIMEOUT_SECONDS", connect_timeout_seconds);
+    }
+
+    if let Some(request_timeout_seconds) = matches.value_of("request-timeout-seconds") {
+        env::set_var("SCOUTY_REQUEST_TIMEOUT_SECONDS", request_timeout_seconds);
+    }
+
+    if let Some(startup_delay_seconds) = matches.value_of("startup-delay-seconds") {
+        env::set_var("SCOUTY_STARTUP_DELAY_SECONDS", startup_delay_seconds);
+    }
+
+    if let Some(confirmation_blocks) = matches.value_of("confirmation-blocks") {
+        env::set_var("SCOUTY_CONFIRMATION_BLOCKS", confirmation_blocks);
+    }
+
+    if matches.is_present("follow-latest") {
+        env::set_var("SCOUTY_HEAD_MODE", "latest");
+    }
+
+    if matches.is_present("follow-finalized") {
+        env::set_var("SCOUTY_HEAD_MODE", "finalized");
+    }
+
+    if let Some(prometheus_listen_addr) = matches.value_of("prometheus-listen-addr") {
+        env::set_var("SCOUTY_PROMETHEUS_LISTEN_ADDR", prometheus_listen_addr);
+    }
+
+    if let Some(healthcheck_listen_addr) = matches.value_of("healthcheck-listen-addr") {
+        env::set_var("SCOUTY_HEALTHCHECK_LISTEN_ADDR", healthcheck_listen_addr);
+    }
+
+    if let Some(api_listen_addr) = matches.value_of("api-listen-addr") {
+        env::set_var("SCOUTY_API_LISTEN_ADDR", api_listen_addr);
+    }
+
+    if let Some(expected_spec_version) = matches.value_of("expected-spec-version") {
+        env::set_var("SCOUTY_EXPECTED_SPEC_VERSION", expected_spec_version);
+    }
+
+    if let Some(max_concurrent_queries) = matches.value_of("max-concurrent-queries") {
+        env::set_var("SCOUTY_MAX_CONCURRENT_QUERIES", max_concurrent_queries);
+    }
+
+    if matches.is_present("disable-matrix") {
+        env::set_var("SCOUTY_MATRIX_DISABLED", "true");
+    }
+
+    if let Some(matrix_user) = matches.value_of("matrix-user") {
+        env::set_var("SCOUTY_MATRIX_USER", matrix_user);
+    }
+
+    if let Some(matrix_bot_user) = matches.value_of("matrix-bot-user") {
+        env::set_var("SCOUTY_MATRIX_BOT_USER", matrix_bot_user);
+    }
+
+    if let Some(matrix_homeserver_url) = matches.value_of("matrix-homeserver-url") {
+        env::set_var("SCOUTY_MATRIX_HOMESERVER_URL", matrix_homeserver_url);
+    }
+
+    if let Some(matrix_format) = matches.value_of("matrix-format") {
+        env::set_var("SCOUTY_MATRIX_FORMAT", matrix_format);
+    }
+
+    if let Some(matrix_rooms) = matches.value_of("matrix-rooms") {
+        env::set_var("SCOUTY_MATRIX_ROOMS", matrix_rooms);
+    }
+
+    if let Some(matrix_bot_password) = matches.value_of("matrix-bot-password") {
+        env::set_var("SCOUTY_MATRIX_BOT_PASSWORD", matrix_bot_password);
+    }
+
+    if let Some(matrix_bot_password_path) = matches.value_of("matrix-bot-password-path") {
+        env::set_var("SCOUTY_MATRIX_BOT_PASSWORD_PATH", matrix_bot_password_path);
+    }
+
+    if matches.is_present("disable-telegram") {
+        env::set_var("SCOUTY_TELEGRAM_DISABLED", "true");
+    }
+
+    if let Some(telegram_bot_token) = matches.value_of("telegram-bot-token") {
+        env::set_var("SCOUTY_TELEGRAM_BOT_TOKEN", telegram_bot_token);
+    }
+
+    if let Some(telegram_chat_id) = matches.value_of("telegram-chat-id") {
+        env::set_var("SCOUTY_TELEGRAM_CHAT_ID", telegram_chat_id);
+    }
+
+    if let Some(heartbeat_interval_hours) = matches.value_of("heartbeat-interval-hours") {
+        env::set_var("SCOUTY_HEARTBEAT_INTERVAL_HOURS", heartbeat_interval_hours);
+    }
+
+    if matches.is_present("heartbeat-node-health") {
+        env::set_var("SCOUTY_HEARTBEAT_NODE_HEALTH", "true");
+    }
+
+    if matches.is_present("daily-report-enabled") {
+        env::set_var("SCOUTY_DAILY_REPORT_ENABLED", "true");
+    }
+
+    if let Some(daily_report_hour) = matches.value_of("daily-report-hour") {
+        env::set_var("SCOUTY_DAILY_REPORT_HOUR", daily_report_hour);
+    }
+
+    if let Some(limit) = matches.value_of("notification-rate-limit-per-minute") {
+        env::set_var("SCOUTY_NOTIFICATION_RATE_LIMIT_PER_MINUTE", limit);
+    }
+
+    if let Some(window) = matches.value_of("notification-dedup-window-seconds") {
+        env::set_var("SCOUTY_NOTIFICATION_DEDUP_WINDOW_SECONDS", window);
+    }
+
+    if let Some(notification_prefix) = matches.value_of("notification-prefix") {
+        env::set_var("SCOUTY_NOTIFICATION_PREFIX", notification_prefix);
+    }
+
+    if let Some(notification_suffix) = matches.value_of("notification-suffix") {
+        env::set_var("SCOUTY_NOTIFICATION_SUFFIX", notification_suffix);
+    }
+
+    if matches.is_present("notify-on-change-only") {
+        env::set_var("SCOUTY_NOTIFY_ON_CHANGE_ONLY", "true");
+    }
+
+    if matches.is_present("suppress-startup-notifications") {
+        env::set_var("SCOUTY_SUPPRESS_STARTUP_NOTIFICATIONS", "true");
+    }
+
+    if let Some(state_file_path) = matches.value_of("state-file-path") {
+        env::set_var("SCOUTY_STATE_FILE_PATH", state_file_path);
+    }
+
+    if matches.is_present("disable-smtp") {
+        env::set_var("SCOUTY_SMTP_DISABLED", "true");
+    }
+
+    if let Some(smtp_host) = matches.value_of("smtp-host") {
+        env::set_var("SCOUTY_SMTP_HOST", smtp_host);
+    }
+
+    if let Some(smtp_port) = matches.value_of("smtp-port") {
+        env::set_var("SCOUTY_SMTP_PORT", smtp_port);
+    }
+
+    if let Some(smtp_user) = matches.value_of("smtp-user") {
+        env::set_var("SCOUTY_SMTP_USER", smtp_user);
+    }
+
+    if let Some(smtp_password) = matches.value_of("smtp-password") {
+        env::set_var("SCOUTY_SMTP_PASSWORD", smtp_password);
+    }
+
+    if let Some(smtp_from) = matches.value_of("smtp-from") {
+        env::set_var("SCOUTY_SMTP_FROM", smtp_from);
+    }
+
+    if let Some(smtp_to) = matches.value_of("smtp-to") {
+        env::set_var("SCOUTY_SMTP_TO", smtp_to);
+    }
+
+    if matches.is_present("disable-webhook") {
+        env::set_var("SCOUTY_WEBHOOK_DISABLED", "true");
+    }
+
+    if let Some(webhook_url) = matches.value_of("webhook-url") {
+        env::set_var("SCOUTY_WEBHOOK_URL", webhook_url);
+    }
+
+    if matches.is_present("disable-pagerduty") {
+        env::set_var("SCOUTY_PAGERDUTY_DISABLED", "true");
+    }
+
+    if let Some(routing_key) = matches.value_of("pagerduty-routing-key") {
+        env::set_var("SCOUTY_PAGERDUTY_ROUTING_KEY", routing_key);
+    }
+
+    if let Some(vault_addr) = matches.value_of("vault-addr") {
+        env::set_var("SCOUTY_VAULT_ADDR", vault_addr);
+    }
+
+    if let Some(vault_token) = matches.value_of("vault-token") {
+        env::set_var("SCOUTY_VAULT_TOKEN", vault_token);
+    }
+
+    if let Some(path) = matches.value_of("matrix-bot-password-vault-path") {
+        env::set_var("SCOUTY_MATRIX_BOT_PASSWORD_VAULT_PATH", path);
+    }
+
+    if let Some(path) = matches.value_of("telegram-bot-token-vault-path") {
+        env::set_var("SCOUTY_TELEGRAM_BOT_TOKEN_VAULT_PATH", path);
+    }
+
+    if let Some(path) = matches.value_of("smtp-password-vault-path") {
+        env::set_var("SCOUTY_SMTP_PASSWORD_VAULT_PATH", path);
+    }
+
+    if let Some(path) = matches.value_of("pagerduty-routing-key-vault-path") {
+        env::set_var("SCOUTY_PAGERDUTY_ROUTING_KEY_VAULT_PATH", path);
+    }
+
+    if let Some(error_interval) = matches.value_of("error-interval") {
+        env::set_var("SCOUTY_ERROR_INTERVAL", error_interval);
+    }
+
+    if let Some(error_interval_max) = matches.value_of("error-interval-max") {
+        env::set_var("SCOUTY_ERROR_INTERVAL_MAX", error_interval_max);
+    }
+
+    if let Some(max_restarts) = matches.value_of("max-restarts") {
+        env::set_var("SCOUTY_MAX_RESTARTS", max_restarts);
+    }
+
+    // Keep the env vars set from CLI flags so that they still take precedence
+    // when the configuration is reloaded
+    reload_source.cli_env = scouty_env_vars()
+        .into_iter()
+        .filter(|var| !file_env.contains(var))
+        .collect();
+    *RELOAD_SOURCE.lock().unwrap() = reload_source;
+
+    // A TOML table is deserialized as it is with the env vars on top, while a
+    // dotenv file was loaded into the env already
+    let vars = scouty_env_vars();
+    let config = if config_files.table.is_empty() {
+        Config::from_env().map_err(|e| describe_env_error(&e, &vars))
+    } else {
+        Config::from_toml_and_vars(config_files.table, vars.clone())
+            .map_err(|e| describe_env_error(&e, &vars))
+    };
+    match config {
+        Ok(config) => match config.finish_loading(&config_files.paths) {
+            Ok(config) => config.with_chain_ws_url_checked(
+                matches.value_of("CHAIN"),
+                matches.value_of("substrate-ws-url"),
+            ),
+            Err(e) => exit_on_config_error(e),
+        },
+        Err(e) => exit_on_config_error(e),
+    }
+}
+
+/// Returns the exit code of the validate command, non-zero if any problem is found
+pub fn lint_exit_code(problems: &[String]) -> i32 {
+    if problems.is_empty() {
+        EXIT_SUCCESS
+    } else {
+        EXIT_CONFIG_ERROR
+    }
+}
+
+/// The logger is not initialized while the configuration loads, so the error is
+/// printed to stderr before exiting with the configuration error code
+fn exit_on_config_error<E: std::fmt::Display>(error: E) -> ! {
+    eprintln!("Configuration error: {}", error);
+    std::process::exit(EXIT_CONFIG_ERROR)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the config of the test stash with the extra vars given, like at
+    /// startup the local endpoint is used if no substrate websocket url is defined
+    fn config_with(extra: &[(&str, &str)]) -> Config {
+        let mut vars = vec![(
+            "SCOUTY_STASHES".to_string(),
+            "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY".to_string(),
+        )];
+        if !extra
+            .iter()
+            .any(|(key, _)| key.starts_with("SCOUTY_SUBSTRATE_WS_URL"))
+        {
+            vars.push((
+                "SCOUTY_SUBSTRATE_WS_URL".to_string(),
+                "ws://127.0.0.1:9944".to_string(),
+            ));
+        }
+        vars.retain(|(key, _)| !extra.iter().any(|(k, _)| k == key));
+        for (key, value) in extra {
+            vars.push((key.to_string(), value.to_string()));
+        }
+        Config::from_vars(vars).unwrap()
+    }
+
+    #[test]
+    fn it_gets_a_config() {
+        let config = get_config();
+        assert_ne!(config.substrate_ws_url, "".to_string());
+    }
+
+    #[test]
+    fn it_gets_a_config_from_the_lazy_static() {
+        let config = CONFIG.load();
+        assert_ne!(config.substrate_ws_url, "".to_string());
+    }
+
+    #[test]
+    fn it_accepts_valid_stashes() {
+        let stashes = vec![
+            StashConfig::parse("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"),
+            StashConfig::parse("5GTD7ZeD823BjpmZBCSzBQp7cvHR1Gunq7oDkurZr9zUev2n:Bob"),
+        ];
+        assert!(validate_stashes(&stashes, Some(42)).is_ok());
+        // a prefix mismatch is only a warning
+        assert!(validate_stashes(&stashes, Some(2)).is_ok());
+    }
+
+    #[test]
+    fn it_decodes_an_address() {
+        let output =
+            decode_address("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", Some(42))
+                .unwrap();
+        assert_eq!(
+            output,
+            "public key 0xd43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d\nss58 prefix 42 (matches the chain prefix 42)"
+        );
+        let output =
+            decode_address("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", Some(2))
+                .unwrap();
+        assert!(output.ends_with("ss58 prefix 42 (does not match the chain prefix 2)"));
+        let output =
+            decode_address("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", None)
+                .unwrap();
+        assert!(output.ends_with("(chain prefix unknown)"));
+
+        let err = decode_address("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQ", None)
+            .unwrap_err();
+        assert!(err.to_string().contains("is not a valid SS58 address"));
+    }
+
+    #[test]
+    fn it_rejects_an_unknown_timezone() {
+        let config = config_with(&[("SCOUTY_TIMEZONE", "Europe/Lisbon")]);
+        assert_eq!(config.timezone(), Tz::Europe__Lisbon);
+        assert!(config.validate().is_ok());
+
+        let config = config_with(&[("SCOUTY_TIMEZONE", "Europe/Atlantis")]);
+        assert_eq!(config.timezone(), Tz::UTC);
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("Unknown timezone 'Europe/Atlantis'"));
+    }
+
+    #[test]
+    fn it_rejects_malformed_stashes() {
+        let stashes = vec![
+            StashConfig::parse("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"),
+            StashConfig::parse("not-a-stash"),
+            StashConfig::parse("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQ"),
+        ];
+        let err = validate_stashes(&stashes, None).unwrap_err().to_string();
+        assert!(err.contains("'not-a-stash' (index 1)"));
+        assert!(
+            err.contains("'5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQ' (index 2)")
+        );
+        assert!(!err.contains("index 0"));
+    }
+
+    #[test]
+    fn it_parses_substrate_ws_urls_in_order() {
+        let config = config_with(&[(
+            "SCOUTY_SUBSTRATE_WS_URLS",
+            "ws://10.0.0.1:9944,ws://10.0.0.2:9944,ws://10.0.0.3:9944",
+        )]);
+        assert_eq!(
+            config.substrate_ws_urls,
+            vec![
+                "ws://10.0.0.1:9944".to_string(),
+                "ws://10.0.0.2:9944".to_string(),
+                "ws://10.0.0.3:9944".to_string(),
+            ]
+        );
+        // the single url is folded as the first endpoint
+        assert_eq!(
+            fold_substrate_ws_urls(
+                "wss://rpc.polkadot.io:443",
+                &config.substrate_ws_urls
+            ),
+            vec![
+                "wss://rpc.polkadot.io:443".to_string(),
+                "ws://10.0.0.1:9944".to_string(),
+                "ws://10.0.0.2:9944".to_string(),
+                "ws://10.0.0.3:9944".to_string(),
+            ]
+        );
+        assert_eq!(
+            fold_substrate_ws_urls("", &config.substrate_ws_urls),
+            config.substrate_ws_urls
+        );
+    }
+
+    #[test]
+    fn it_sorts_the_substrate_endpoints_by_priority() {
+        assert_eq!(
+            "wss://mynode:9944|10".parse::<Endpoint>(),
+            Ok(Endpoint {
+                url: "wss://mynode:9944".to_string(),
+                priority: 10
+            })
+        );
+        assert_eq!(
+            "wss://rpc.polkadot.io:443".parse::<Endpoint>(),
+            Ok(Endpoint {
+                url: "wss://rpc.polkadot.io:443".to_string(),
+                priority: 0
+            })
+        );
+        assert_eq!("wss://mynode:9944|high".parse::<Endpoint>(), Err(()));
+
+        let vars = vec![
+            (
+                "SCOUTY_SUBSTRATE_WS_URL".to_string(),
+                "wss://rpc.polkadot.io:443".to_string(),
+            ),
+            (
+                "SCOUTY_SUBSTRATE_WS_URLS".to_string(),
+                "wss://public:443|1,wss://mynode:9944|10,wss://other:443|1".to_string(),
+            ),
+        ];
+        let config = Config::from_vars(vars).unwrap();
+        assert_eq!(
+            config.substrate_ws_urls,
+            vec![
+                "wss://mynode:9944".to_string(),
+                "wss://public:443".to_string(),
+                "wss://other:443".to_string(),
+                "wss://rpc.polkadot.io:443".to_string(),
+            ]
+        );
+        assert_eq!(
+            config
+                .substrate_ws_endpoints
+                .iter()
+                .map(|endpoint| endpoint.priority)
+                .collect::<Vec<u32>>(),
+            vec![10, 1, 1, 0]
+        );
+        assert_eq!(config.endpoint_promotion_interval, Duration::from_secs(600));
+
+        // a priority not valid is reported with the url
+        let vars = vec![(
+            "SCOUTY_SUBSTRATE_WS_URLS".to_string(),
+            "wss://mynode:9944|high".to_string(),
+        )];
+        let config = Config::from_vars(vars).unwrap();
+        assert!(config.with_substrate_ws_urls_validated().is_err());
+    }
+
+    #[test]
+    fn it_gets_a_config_from_a_toml_file() {
+        let content = fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/config.toml"
+        ))
+        .unwrap();
+        let table = parse_toml_config(&content).unwrap();
+        let config = Config::from_toml_and_vars(table, Vec::new()).unwrap();
+        assert_eq!(
+            config.stashes,
+            vec![
+                StashConfig::parse("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"),
+                StashConfig::parse("5GTD7ZeD823BjpmZBCSzBQp7cvHR1Gunq7oDkurZr9zUev2n"),
+            ]
+        );
+        assert_eq!(config.substrate_ws_url, "wss://westend-rpc.polkadot.io:443");
+        assert_eq!(config.error_interval, Duration::from_secs(15 * 60));
+        assert!(config.is_short);
+        assert!(config.matrix_disabled);
+        assert_eq!(
+            config.hook_new_session_path,
+            "/opt/scouty-cli/hooks/_new_session.sh"
+        );
+    }
+
+    #[test]
+    fn it_applies_the_env_vars_on_top_of_a_toml_file() {
+        let table = parse_toml_config(
+            "interval = 3600\nis_short = true\nenabled_events = [\"slash\", \"chill\"]\nsubstrate_ws_urls = [\"wss://a.example:443\", \"wss://b.example:443\"]\n\n[[stashes]]\naddress = \"5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY\"\nname = \"MyValidator01\"\n",
+        )
+        .unwrap();
+        let config = Config::from_toml_and_vars(table.clone(), Vec::new()).unwrap();
+        assert_eq!(config.interval, Duration::from_secs(3600));
+        assert!(config.is_short);
+        assert_eq!(config.enabled_events.len(), 2);
+        assert_eq!(
+            config.substrate_ws_urls,
+            vec!["wss://a.example:443", "wss://b.example:443"]
+        );
+        assert_eq!(
+            config.stashes,
+            vec![StashConfig::parse(
+                "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY:MyValidator01"
+            )]
+        );
+
+        let vars = vec![
+            ("SCOUTY_INTERVAL".to_string(), "30m".to_string()),
+            ("SCOUTY_ENABLED_EVENTS".to_string(), "offline".to_string()),
+            (
+                "SCOUTY_SUBSTRATE_WS_URLS".to_string(),
+                "wss://c.example:443".to_string(),
+            ),
+        ];
+        let config = Config::from_toml_and_vars(table, vars).unwrap();
+        assert_eq!(config.interval, Duration::from_secs(30 * 60));
+        assert!(config.is_short);
+        assert!(config.is_event_allowlisted(EventKind::Offline));
+        assert!(!config.is_event_allowlisted(EventKind::Slash));
+        assert_eq!(config.substrate_ws_urls, vec!["wss://c.example:443"]);
+        assert_eq!(config.stashes.len(), 1);
+    }
+
+    #[test]
+    fn it_reports_the_offending_toml_key_and_line() {
+        let err = parse_toml_config("is_short = true\nerror_interval = abc\n")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("line 2"));
+
+        let err = parse_toml_config(
+            "is_short = true\n[substrate_ws_url]\nurl = \"ws://127.0.0.1:9944\"\n",
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("'substrate_ws_url' at line 2"), "{}", err);
+
+        let err = parse_toml_config("is_short = true\ninterval = \"abc\"\n")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("'interval' at line 2"), "{}", err);
+    }
+
+    #[test]
+    fn it_gets_the_chain_presets() {
+        assert_eq!("westend".parse::<ChainPreset>(), Ok(ChainPreset::Westend));
+        assert_eq!(ChainPreset::Westend.ss58_prefix(), 42);
+        assert_eq!(ChainPreset::Westend.token_symbol(), "WND");
+        assert_eq!(ChainPreset::Westend.token_decimals(), 12);
+        assert_eq!(
+            ChainPreset::Westend.ws_url(),
+            "wss://westend-rpc.polkadot.io:443"
+        );
+        assert_eq!("kusama".parse::<ChainPreset>(), Ok(ChainPreset::Kusama));
+        assert_eq!(ChainPreset::Kusama.ss58_prefix(), 2);
+        assert_eq!(ChainPreset::Kusama.token_symbol(), "KSM");
+        assert_eq!(ChainPreset::Kusama.token_decimals(), 12);
+        assert_eq!(
+            ChainPreset::Kusama.ws_url(),
+            "wss://kusama-rpc.polkadot.io:443"
+        );
+        assert_eq!("polkadot".parse::<ChainPreset>(), Ok(ChainPreset::Polkadot));
+        assert_eq!(ChainPreset::Polkadot.ss58_prefix(), 0);
+        assert_eq!(ChainPreset::Polkadot.token_symbol(), "DOT");
+        assert_eq!(ChainPreset::Polkadot.token_decimals(), 10);
+        assert_eq!(ChainPreset::Polkadot.ws_url(), "wss://rpc.polkadot.io:443");
+        assert_eq!("paseo".parse::<ChainPreset>(), Ok(ChainPreset::Paseo));
+        assert_eq!(ChainPreset::Paseo.ss58_prefix(), 0);
+        assert_eq!(
+            ChainPreset::Paseo.ws_url(),
+            "wss://paseo-rpc.dwellir.com:443"
+        );
+        assert_eq!("rococo".parse::<ChainPreset>(), Err(()));
+    }
+
+    #[test]
+    fn it_lists_the_chain_presets() {
+        let output = list_chains();
+        assert_eq!(output.lines().count(), ChainPreset::ALL.len());
+        for (name, ws_url, ss58_prefix) in [
+            ("westend", "wss://westend-rpc.polkadot.io:443", "ss58 prefix 42 "),
+            ("kusama", "wss://kusama-rpc.polkadot.io:443", "ss58 prefix 2 "),
+            ("polkadot", "wss://rpc.polkadot.io:443", "ss58 prefix 0 "),
+        ] {
+            let line = output
+                .lines()
+                .find(|line| line.starts_with(name))
+                .unwrap();
+            assert!(line.contains(ws_url));
+            assert!(line.contains(ss58_prefix));
+        }
+        assert!(output.contains("token KSM (12 decimals)"));
+        assert!(output.contains("token DOT (10 decimals)"));
+    }
+
+    #[test]
+    fn it_gets_the_chain_preset_from_env() {
+        let config = config_with(&[("SCOUTY_CHAIN", "kusama")]);
+        assert_eq!(config.chain, Some(ChainPreset::Kusama));
+    }
+
+    #[test]
+    fn it_gets_the_dry_run_flag() {
+        let config = config_with(&[
+            ("SCOUTY_IS_DRY_RUN", "true"),
+            ("SCOUTY_MATRIX_BOT_PASSWORD", "anotthateasypassword"),
+        ]);
+        assert!(config.is_dry_run);
+        let plan = config.resolved_plan();
+        assert!(plan.contains("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"));
+        assert!(!plan.contains("anotthateasypassword"));
+    }
+
+    #[test]
+    fn it_redacts_secrets_in_debug_output() {
+        let config =
+            config_with(&[("SCOUTY_MATRIX_BOT_PASSWORD", "anotthateasypassword")]);
+        assert_eq!(config.matrix_bot_password.expose(), "anotthateasypassword");
+        let output = format!("{:?}", config);
+        assert!(!output.contains("anotthateasypassword"));
+        assert!(output.contains("matrix_bot_password: \"***\""));
+        let output = format!("{:#?}", config);
+        assert!(!output.contains("anotthateasypassword"));
+    }
+
+    #[test]
+    fn it_gets_labeled_stashes() {
+        let vars = vec![(
+            "SCOUTY_STASHES".to_string(),
+            "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY:MyValidator01,5GTD7ZeD823BjpmZBCSzBQp7cvHR1Gunq7oDkurZr9zUev2n:MyValidator02".to_string(),
+        )];
+        let config = Config::from_vars(vars).unwrap();
+        assert_eq!(
+            config.stashes,
+            vec![
+                StashConfig {
+                    address: "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+                        .to_string(),
+                    name: Some("MyValidator01".to_string()),
+                    hook_active_next_era_path: None,
+                },
+                StashConfig {
+                    address: "5GTD7ZeD823BjpmZBCSzBQp7cvHR1Gunq7oDkurZr9zUev2n"
+                        .to_string(),
+                    name: Some("MyValidator02".to_string()),
+                    hook_active_next_era_path: None,
+                },
+            ]
+        );
+        assert_eq!(config.stashes[0].name(), "MyValidator01");
+    }
+
+    #[test]
+    fn it_gets_unlabeled_stashes() {
+        let vars = vec![(
+            "SCOUTY_STASHES".to_string(),
+            "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY,5GTD7ZeD823BjpmZBCSzBQp7cvHR1Gunq7oDkurZr9zUev2n:".to_string(),
+        )];
+        let config = Config::from_vars(vars).unwrap();
+        assert_eq!(config.stashes.len(), 2);
+        for stash in config.stashes.iter() {
+            assert_eq!(stash.name, None);
+            assert_eq!(stash.name(), stash.address);
+        }
+        assert_eq!(
+            config.stashes[1].address,
+            "5GTD7ZeD823BjpmZBCSzBQp7cvHR1Gunq7oDkurZr9zUev2n"
+        );
+    }
+
+    #[test]
+    fn it_gets_mixed_labeled_and_unlabeled_stashes() {
+        let vars = vec![(
+            "SCOUTY_STASHES".to_string(),
+            "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY,5GTD7ZeD823BjpmZBCSzBQp7cvHR1Gunq7oDkurZr9zUev2n:MyValidator02".to_string(),
+        )];
+        let config = Config::from_vars(vars).unwrap();
+        assert_eq!(
+            config.stashes[0].name(),
+            "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+        );
+        assert_eq!(config.stashes[1].name(), "MyValidator02");
+        assert_eq!(
+            config.stashes[1].address,
+            "5GTD7ZeD823BjpmZBCSzBQp7cvHR1Gunq7oDkurZr9zUev2n"
+        );
+    }
+
+    #[test]
+    fn it_warns_about_hooks_not_executable() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let executable = dir.path().join("_new_era.sh");
+        fs::write(&executable, "#!/bin/bash\necho \"new era\"\n").unwrap();
+        fs::set_permissions(&executable, fs::Permissions::from_mode(0o755)).unwrap();
+        let not_executable = dir.path().join("_new_session.sh");
+        fs::write(&not_executable, "#!/bin/bash\necho \"new session\"\n").unwrap();
+        fs::set_permissions(&not_executable, fs::Permissions::from_mode(0o644)).unwrap();
+        let missing = dir.path().join("_validator_chilled.sh");
+
+        let config = config_with(&[
+            ("SCOUTY_HOOK_NEW_ERA_PATH", executable.to_str().unwrap()),
+            ("SCOUTY_HOOK_NEW_SESSION_PATH", not_executable.to_str().unwrap()),
+            ("SCOUTY_HOOK_VALIDATOR_CHILLED_PATH", missing.to_str().unwrap()),
+        ]);
+        let warnings = config.check_hooks();
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains(HOOK_NEW_SESSION));
+        assert!(warnings[0].contains("is not executable"));
+        assert!(warnings[1].contains(HOOK_VALIDATOR_CHILLED));
+        assert!(warnings[1].contains("not found"));
+        // hooks left empty are not reported and do not fail validation
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn it_gets_a_config_from_vars() {
+        let config = Config::from_vars(vec![
+            (
+                "SCOUTY_STASHES".to_string(),
+                "5GTD7ZeD823BjpmZBCSzBQp7cvHR1Gunq7oDkurZr9zUev2n".to_string(),
+            ),
+            (
+                "SCOUTY_SUBSTRATE_WS_URL".to_string(),
+                "ws://127.0.0.1:9944".to_string(),
+            ),
+        ])
+        .unwrap();
+        assert!(!config.stashes.is_empty());
+        assert_eq!(
+            config.substrate_ws_urls.first(),
+            Some(&"ws://127.0.0.1:9944".to_string())
+        );
+    }
+
+    #[test]
+    fn it_gets_the_telegram_config() {
+        let config = config_with(&[
+            ("SCOUTY_TELEGRAM_BOT_TOKEN", "123456789:AAEanotthateasytoken"),
+            ("SCOUTY_TELEGRAM_CHAT_ID", "987654321"),
+            ("SCOUTY_MATRIX_BOT_PASSWORD", "anotthateasypassword"),
+        ]);
+        assert_eq!(
+            config.telegram_bot_token.expose(),
+            "123456789:AAEanotthateasytoken"
+        );
+        assert_eq!(config.telegram_chat_id, "987654321");
+        assert!(!config.telegram_disabled);
+        // both matrix and telegram can be enabled simultaneously
+        assert!(!config.matrix_disabled);
+        assert!(config.is_telegram_enabled());
+        assert!(!format!("{:?}", config).contains("anotthateasytoken"));
+    }
+
+    #[test]
+    fn it_disables_telegram_without_token_or_chat_id() {
+        let config = config_with(&[("SCOUTY_TELEGRAM_CHAT_ID", "987654321")]);
+        assert!(!config.telegram_disabled);
+        assert!(!config.is_telegram_enabled());
+    }
+
+    #[test]
+    fn it_gets_the_heartbeat_interval() {
+        let config = config_with(&[]);
+        assert_eq!(config.heartbeat_interval_hours, 0);
+        assert!(!config.heartbeat_node_health);
+
+        let config = config_with(&[
+            ("SCOUTY_HEARTBEAT_INTERVAL_HOURS", "24"),
+            ("SCOUTY_HEARTBEAT_NODE_HEALTH", "true"),
+        ]);
+        assert_eq!(config.heartbeat_interval_hours, 24);
+        assert!(config.heartbeat_node_health);
+    }
+
+    #[test]
+    fn it_gets_the_daily_report_hour() {
+        let config = config_with(&[("SCOUTY_DAILY_REPORT_HOUR", "9")]);
+        assert_eq!(config.daily_report_hour(), None);
+
+        let config = config_with(&[
+            ("SCOUTY_DAILY_REPORT_ENABLED", "true"),
+            ("SCOUTY_DAILY_REPORT_HOUR", "9"),
+        ]);
+        assert_eq!(config.daily_report_hour(), Some(9));
+        assert!(config.validate().is_ok());
+
+        let config = config_with(&[
+            ("SCOUTY_DAILY_REPORT_ENABLED", "true"),
+            ("SCOUTY_DAILY_REPORT_HOUR", "24"),
+        ]);
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("Invalid daily report hour '24', expected 0 to 23"));
+    }
+
+    #[test]
+    fn it_gets_the_notification_rate_limit() {
+        let config = config_with(&[]);
+        assert_eq!(config.notification_rate_limit_per_minute, 0);
+
+        let config = config_with(&[("SCOUTY_NOTIFICATION_RATE_LIMIT_PER_MINUTE", "10")]);
+        assert_eq!(config.notification_rate_limit_per_minute, 10);
+    }
+
+    #[test]
+    fn it_gets_the_notification_dedup_window() {
+        let config = config_with(&[]);
+        assert_eq!(config.notification_dedup_window_seconds, 0);
+
+        let config = config_with(&[("SCOUTY_NOTIFICATION_DEDUP_WINDOW_SECONDS", "600")]);
+        assert_eq!(config.notification_dedup_window_seconds, 600);
+    }
+
+    #[test]
+    fn it_wraps_the_notification_with_the_prefix_and_suffix() {
+        let config = config_with(&[
+            ("SCOUTY_NOTIFICATION_PREFIX", "[kusama-01]"),
+            ("SCOUTY_NOTIFICATION_SUFFIX", "<eu>"),
+        ]);
+        assert_eq!(
+            config.wrap_notification("New session 25021"),
+            "[kusama-01] New session 25021 <eu>"
+        );
+        assert_eq!(
+            config.wrap_formatted_notification("<b>New session</b> 25021"),
+            "[kusama-01] <b>New session</b> 25021 &lt;eu&gt;"
+        );
+
+        // empty values are no-ops
+        let config = config_with(&[("SCOUTY_NOTIFICATION_PREFIX", "")]);
+        assert_eq!(
+            config.wrap_notification("New session 25021"),
+            "New session 25021"
+        );
+    }
+
+    #[test]
+    fn it_gets_the_smtp_config_defaults() {
+        let config = config_with(&[]);
+        assert_eq!(config.smtp_host, "");
+        assert_eq!(config.smtp_port, 587);
+        assert_eq!(config.smtp_user, "");
+        assert!(config.smtp_password.is_empty());
+        assert_eq!(config.smtp_from, "");
+        assert!(config.smtp_to.is_empty());
+        assert!(!config.smtp_disabled);
+        assert!(!config.is_smtp_enabled());
+    }
+
+    #[test]
+    fn it_gets_the_smtp_config() {
+        let config = config_with(&[
+            ("SCOUTY_SMTP_HOST", "smtp.example.com"),
+            ("SCOUTY_SMTP_PORT", "2525"),
+            ("SCOUTY_SMTP_USER", "scouty"),
+            ("SCOUTY_SMTP_PASSWORD", "anotthateasypassword"),
+            ("SCOUTY_SMTP_FROM", "scouty@example.com"),
+            ("SCOUTY_SMTP_TO", "alice@example.com,bob@example.com"),
+        ]);
+        assert_eq!(config.smtp_port, 2525);
+        assert_eq!(
+            config.smtp_to,
+            vec![
+                "alice@example.com".to_string(),
+                "bob@example.com".to_string()
+            ]
+        );
+        assert!(config.is_smtp_enabled());
+        assert!(!format!("{:?}", config).contains("anotthateasypassword"));
+    }
+
+    #[test]
+    fn it_gets_the_webhook_config() {
+        let config = config_with(&[("SCOUTY_WEBHOOK_URL", "https://example.com/scouty")]);
+        assert_eq!(config.webhook_url, "https://example.com/scouty");
+        assert!(!config.webhook_disabled);
+
+        let config = config_with(&[("SCOUTY_WEBHOOK_DISABLED", "true")]);
+        assert_eq!(config.webhook_url, "");
+        assert!(config.webhook_disabled);
+    }
+
+    #[test]
+    fn it_gets_the_pagerduty_config() {
+        let config = config_with(&[]);
+        assert!(config.pagerduty_routing_key.is_none());
+        assert!(!config.is_pagerduty_enabled());
+
+        let config = config_with(&[("SCOUTY_PAGERDUTY_ROUTING_KEY", "R0UT1NGK3Y")]);
+        assert!(config.is_pagerduty_enabled());
+        assert!(!format!("{:?}", config).contains("R0UT1NGK3Y"));
+
+        let config = config_with(&[
+            ("SCOUTY_PAGERDUTY_ROUTING_KEY", "R0UT1NGK3Y"),
+            ("SCOUTY_PAGERDUTY_DISABLED", "true"),
+        ]);
+        assert!(!config.is_pagerduty_enabled());
+    }
+
+    #[test]
+    fn it_parses_durations() {
+        let config =
+            config_with(&[("SCOUTY_INTERVAL", "6h"), ("SCOUTY_ERROR_INTERVAL", "30m")]);
+        assert_eq!(config.interval, Duration::from_secs(6 * 60 * 60));
+        assert_eq!(config.error_interval, Duration::from_secs(30 * 60));
+
+        // bare integers are seconds for interval and minutes for error interval
+        let config = config_with(&[
+            ("SCOUTY_INTERVAL", "21600"),
+            ("SCOUTY_ERROR_INTERVAL", "30"),
+        ]);
+        assert_eq!(config.interval, Duration::from_secs(6 * 60 * 60));
+        assert_eq!(config.error_interval, Duration::from_secs(30 * 60));
+
+        assert!(parse_duration("six hours", 1).is_err());
+    }
+
+    #[test]
+    fn it_formats_durations() {
+        assert_eq!(format_duration(Duration::from_secs(21600)), "6h");
+        assert_eq!(format_duration(Duration::from_secs(30 * 60)), "30m");
+        assert_eq!(format_duration(Duration::from_millis(90_500)), "1m 30s");
+    }
+
+    #[test]
+    fn it_splits_the_hook_extra_args() {
+        let config =
+            config_with(&[("SCOUTY_HOOK_EXTRA_ARGS", "--region eu --label \"a b\"")]);
+        assert_eq!(
+            config.hook_extra_args(),
+            vec!["--region", "eu", "--label", "a b"]
+        );
+        assert!(config.validation_errors().is_empty());
+
+        let config = config_with(&[("SCOUTY_HOOK_EXTRA_ARGS", "\"a b")]);
+        assert!(config.hook_extra_args().is_empty());
+        assert_eq!(config.validation_errors().len(), 1);
+    }
+
+    #[test]
+    fn it_gets_the_hooks_disabled_flags() {
+        let config = config_with(&[]);
+        assert!(!config.hook_new_session_disabled);
+        assert!(!config.hook_active_next_era_disabled);
+        assert!(!config.hook_inactive_next_era_disabled);
+        assert_eq!(config.hook_timeout_seconds, 60);
+
+        let config = config_with(&[
+            ("SCOUTY_HOOK_NEW_SESSION_PATH", "/opt/scouty-cli/hooks/_new_session.sh"),
+            ("SCOUTY_HOOK_NEW_SESSION_DISABLED", "true"),
+            ("SCOUTY_HOOK_INACTIVE_NEXT_ERA_DISABLED", "true"),
+        ]);
+        assert_eq!(
+            config.hook_new_session_path,
+            "/opt/scouty-cli/hooks/_new_session.sh"
+        );
+        assert!(config.is_hook_disabled(HOOK_NEW_SESSION));
+        assert!(!config.is_hook_disabled(HOOK_VALIDATOR_STARTS_ACTIVE_NEXT_ERA));
+        assert!(config.is_hook_disabled(HOOK_VALIDATOR_STARTS_INACTIVE_NEXT_ERA));
+        assert!(!config.is_hook_disabled(HOOK_NEW_ERA));
+    }
+
+    #[test]
+    fn it_gets_the_substrate_ws_tls_fingerprint() {
+        let config = config_with(&[]);
+        assert_eq!(config.substrate_ws_tls_fingerprint, None);
+
+        let config = config_with(&[(
+            "SCOUTY_SUBSTRATE_WS_TLS_FINGERPRINT",
+            "3f4c1e3f0d0e5b0d9a5c6e2b7f8a9b0c1d2e3f405162738495a6b7c8d9e0f1a2",
+        )]);
+        assert_eq!(
+            config.substrate_ws_tls_fingerprint,
+            Some(
+                "3f4c1e3f0d0e5b0d9a5c6e2b7f8a9b0c1d2e3f405162738495a6b7c8d9e0f1a2"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn it_gets_the_substrate_ws_proxy() {
+        let config = config_with(&[(
+            "SCOUTY_SUBSTRATE_WS_PROXY",
+            "socks5://proxy.example.com:1080",
+        )]);
+        assert_eq!(
+            config.substrate_ws_proxy,
+            Some("socks5://proxy.example.com:1080".to_string())
+        );
+    }
+
+    #[test]
+    fn it_gets_the_prometheus_listen_addr() {
+        let config = config_with(&[("SCOUTY_PROMETHEUS_LISTEN_ADDR", "0.0.0.0:9090")]);
+        assert_eq!(
+            config.prometheus_listen_addr,
+            Some("0.0.0.0:9090".to_string())
+        );
+        assert!(config.validate().is_ok());
+
+        let config = config_with(&[("SCOUTY_PROMETHEUS_LISTEN_ADDR", "0.0.0.0")]);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn it_reloads_a_changed_stash_list() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join(".env");
+        fs::write(
+            &config_path,
+            "SCOUTY_STASHES=5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY\nSCOUTY_SUBSTRATE_WS_URL=ws://127.0.0.1:9944\n",
+        )
+        .unwrap();
+        let source = ReloadSource {
+            config_path: Some(config_path.to_str().unwrap().to_string()),
+            initial_env: vec![],
+            cli_env: vec![("SCOUTY_IS_SHORT".to_string(), "true".to_string())],
+        };
+        let current =
+            Config::from_vars(vec![("SCOUTY_STASHES".to_string(), "".to_string())])
+                .unwrap();
+        let config = reload_from(&source, &current).unwrap();
+        assert_eq!(
+            config.stashes,
+            vec![StashConfig::parse(
+                "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+            )]
+        );
+
+        fs::write(
+            &config_path,
+            "SCOUTY_STASHES=5GTD7ZeD823BjpmZBCSzBQp7cvHR1Gunq7oDkurZr9zUev2n:MyValidator02\nSCOUTY_SUBSTRATE_WS_URL=ws://10.0.0.1:9944\n",
+        )
+        .unwrap();
+        let current = Config::from_vars(vec![
+            (
+                "SCOUTY_STASHES".to_string(),
+                "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY".to_string(),
+            ),
+            (
+                "SCOUTY_SUBSTRATE_WS_URL".to_string(),
+                "ws://127.0.0.1:9944".to_string(),
+            ),
+        ])
+        .unwrap();
+        let config = reload_from(&source, &current).unwrap();
+        assert_eq!(
+            config.stashes,
+            vec![StashConfig::parse(
+                "5GTD7ZeD823BjpmZBCSzBQp7cvHR1Gunq7oDkurZr9zUev2n:MyValidator02"
+            )]
+        );
+        // CLI flags are kept and substrate endpoints require a restart
+        assert!(config.is_short);
+        assert_eq!(
+            config.substrate_ws_urls,
+            vec!["ws://127.0.0.1:9944".to_string()]
+        );
+    }
+
+    #[test]
+    fn it_expands_env_vars_in_hook_paths_on_reload() {
+        let home = env::var("HOME").unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("scouty.toml");
+        fs::write(
+            &config_path,
+            "stashes = [\"5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY\"]\nsubstrate_ws_url = \"ws://127.0.0.1:9944\"\nhook_new_era_path = \"$HOME/scouty/hooks/_new_era.sh\"\n",
+        )
+        .unwrap();
+        let source = ReloadSource {
+            config_path: Some(config_path.to_str().unwrap().to_string()),
+            initial_env: vec![],
+            cli_env: vec![],
+        };
+        let current =
+            Config::from_vars(vec![("SCOUTY_STASHES".to_string(), "".to_string())])
+                .unwrap();
+        let config = reload_from(&source, &current).unwrap();
+        assert_eq!(
+            config.hook_new_era_path,
+            format!("{}/scouty/hooks/_new_era.sh", home)
+        );
+    }
+
+    #[test]
+    fn it_reads_the_matrix_bot_password_from_a_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let password_path = dir.path().join("matrix_bot_password");
+        fs::write(&password_path, "anotthateasypasswordfromfile\n\n").unwrap();
+
+        let config = config_with(&[(
+            "SCOUTY_MATRIX_BOT_PASSWORD_PATH",
+            password_path.to_str().unwrap(),
+        )])
+        .with_secret_files()
+        .unwrap();
+        assert_eq!(
+            config.matrix_bot_password.expose(),
+            "anotthateasypasswordfromfile"
+        );
+        assert!(config.load_warnings.is_empty());
+
+        // the password path takes precedence
+        let config = config_with(&[
+            ("SCOUTY_MATRIX_BOT_PASSWORD", "anotthateasypassword"),
+            (
+                "SCOUTY_MATRIX_BOT_PASSWORD_PATH",
+                password_path.to_str().unwrap(),
+            ),
+        ])
+        .with_secret_files()
+        .unwrap();
+        assert_eq!(
+            config.matrix_bot_password.expose(),
+            "anotthateasypasswordfromfile"
+        );
+        assert_eq!(config.load_warnings.len(), 1);
+    }
+
+    /// Serves a single vault KV v2 read of secret/scouty with the token s.scouty
+    fn serve_vault_secret(body: &'static str) -> String {
+        use std::io::{BufRead, BufReader, Write};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = String::new();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                    break;
+                }
+                request.push_str(&line.to_lowercase());
+            }
+            let status = if request.starts_with("get /v1/secret/data/scouty ")
+                && request.contains("x-vault-token: s.scouty")
+            {
+                "200 OK"
+            } else {
+                "403 Forbidden"
+            };
+            write!(
+                stream,
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            )
+            .unwrap();
+        });
+        format!("http://{}", addr)
+    }
+
+    #[test]
+    fn it_reads_secrets_from_vault() {
+        let vault_addr = serve_vault_secret(
+            r#"{"data":{"data":{"matrix_bot_password":"anotthateasypasswordfromvault"},"metadata":{"version":1}}}"#,
+        );
+        let config = config_with(&[
+            ("SCOUTY_VAULT_ADDR", &vault_addr),
+            ("SCOUTY_VAULT_TOKEN", "s.scouty"),
+            ("SCOUTY_MATRIX_BOT_PASSWORD_VAULT_PATH", "secret/scouty"),
+        ])
+        .with_vault_secrets()
+        .unwrap();
+        assert_eq!(
+            config.matrix_bot_password.expose(),
+            "anotthateasypasswordfromvault"
+        );
+
+        // a vault error is not silently ignored
+        let vault_addr = serve_vault_secret(r#"{"data":{"data":{},"metadata":{}}}"#);
+        let vars = vec![
+            ("SCOUTY_VAULT_ADDR".to_string(), vault_addr),
+            ("SCOUTY_VAULT_TOKEN".to_string(), "s.scouty".to_string()),
+            (
+                "SCOUTY_SMTP_PASSWORD_VAULT_PATH".to_string(),
+                "secret/scouty".to_string(),
+            ),
+        ];
+        let err = Config::from_vars(vars)
+            .unwrap()
+            .with_vault_secrets()
+            .unwrap_err();
+        assert!(err.to_string().contains("has no key 'smtp_password'"));
+
+        // secrets already defined inline are kept without reading the vault
+        let vars = vec![
+            (
+                "SCOUTY_SMTP_PASSWORD".to_string(),
+                "inlinepassword".to_string(),
+            ),
+            (
+                "SCOUTY_SMTP_PASSWORD_VAULT_PATH".to_string(),
+                "secret/scouty".to_string(),
+            ),
+        ];
+        let config = Config::from_vars(vars)
+            .unwrap()
+            .with_vault_secrets()
+            .unwrap();
+        assert_eq!(config.smtp_password.expose(), "inlinepassword");
+    }
+
+    #[test]
+    fn it_describes_the_var_that_failed_to_deserialize() {
+        let vars = vec![
+            (
+                "SCOUTY_STASHES".to_string(),
+                "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY".to_string(),
+            ),
+            ("SCOUTY_INTERVAL".to_string(), "6x".to_string()),
+        ];
+        let error = Config::from_vars(vars.clone()).unwrap_err();
+        let message = describe_env_error(&error, &vars);
+        assert!(
+            message.starts_with(
+                "SCOUTY_INTERVAL='6x' is not valid, expected a value like '6h'"
+            ),
+            "{}",
+            message
+        );
+        assert!(!message.contains("Custom"));
+
+        let vars = vec![("SCOUTY_IS_DEBUG".to_string(), "yes".to_string())];
+        let error = Config::from_vars(vars.clone()).unwrap_err();
+        let message = describe_env_error(&error, &vars);
+        assert!(
+            message.starts_with(
+                "SCOUTY_IS_DEBUG='yes' is not valid, expected true or false"
+            ),
+            "{}",
+            message
+        );
+
+        let vars = vec![("SCOUTY_SMTP_PORT".to_string(), "smtp".to_string())];
+        let error = Config::from_vars(vars.clone()).unwrap_err();
+        let message = describe_env_error(&error, &vars);
+        assert!(
+            message.starts_with(
+                "SCOUTY_SMTP_PORT='smtp' is not valid, expected a non-negative integer (e.g. 587)"
+            ),
+            "{}",
+            message
+        );
+    }
 
-    // Try to load configuration from file first
-    let config_path = matches.value_of("config-path").unwrap_or(".env");
+    #[test]
+    fn it_lints_a_config_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let clean_path = dir.path().join("clean.env");
+        fs::write(
+            &clean_path,
+            "SCOUTY_STASHES=5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY\nSCOUTY_SUBSTRATE_WS_URL=ws://127.0.0.1:9944\nSCOUTY_MATRIX_USER=@scouty:matrix.org\n",
+        )
+        .unwrap();
+        let mut vars = read_config_file(clean_path.to_str().unwrap())
+            .unwrap()
+            .unwrap()
+            .vars;
+        vars.push(("SCOUTY_COMMAND".to_string(), "validate".to_string()));
+        let config = Config::from_vars(vars).unwrap();
+        assert_eq!(config.command, Command::Validate);
+        let problems = config.lint();
+        assert!(problems.is_empty(), "{:?}", problems);
+        assert_eq!(lint_exit_code(&problems), EXIT_SUCCESS);
 
-    match dotenv::from_filename(&config_path).ok() {
-        Some(_) => info!("Loading configuration from {} file", &config_path),
-        None => {
-            let config_path =
-                env::var("SCOUTY_CONFIG_FILENAME").unwrap_or(".env".to_string());
-            if let Some(_) = dotenv::from_filename(&config_path).ok() {
-                info!("Loading configuration from {} file", &config_path);
-            }
-        }
+        let broken_path = dir.path().join("broken.env");
+        fs::write(
+            &broken_path,
+            "SCOUTY_STASHES=abc\nSCOUTY_SUBSTRATE_WS_URL=ws://127.0.0.1:9944\nSCOUTY_MATRIX_USER=scouty\nSCOUTY_HOOK_NEW_ERA_PATH=/not/a/hook.sh\n",
+        )
+        .unwrap();
+        let mut vars = read_config_file(broken_path.to_str().unwrap())
+            .unwrap()
+            .unwrap()
+            .vars;
+        vars.push(("SCOUTY_COMMAND".to_string(), "validate".to_string()));
+        let config = Config::from_vars(vars).unwrap();
+        let problems = config.lint();
+        assert_eq!(problems.len(), 3, "{:?}", problems);
+        assert!(problems[0].contains("/not/a/hook.sh"));
+        assert!(problems[1].contains("Matrix user 'scouty' is not valid"));
+        assert!(problems[2].contains("'abc'"));
+        assert_eq!(lint_exit_code(&problems), EXIT_CONFIG_ERROR);
     }
 
-    match matches.value_of("CHAIN") {
-        Some("westend") => {
-            env::set_var(
-                "SCOUTY_SUBSTRATE_WS_URL",
-                "wss://westend-rpc.polkadot.io:443",
-            );
-        }
-        Some("kusama") => {
-            env::set_var(
-                "SCOUTY_SUBSTRATE_WS_URL",
-                "wss://kusama-rpc.polkadot.io:443",
-            );
+    #[test]
+    fn it_overlays_the_host_and_local_config_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join(".env");
+        fs::write(
+            &config_path,
+            "SCOUTY_STASHES=5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY\nSCOUTY_INTERVAL=6h\nSCOUTY_IS_SHORT=false\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join(".env.node01"),
+            "SCOUTY_INTERVAL=1h\nSCOUTY_IS_SHORT=true\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join(".env.local"), "SCOUTY_INTERVAL=30m\n").unwrap();
+        let config_path = config_path.to_str().unwrap();
+
+        let config_files = read_config_files(config_path, Some("node01")).unwrap();
+        assert_eq!(
+            config_files.paths,
+            vec![
+                config_path.to_string(),
+                format!("{}.node01", config_path),
+                format!("{}.local", config_path),
+            ]
+        );
+        let config = Config::from_vars(config_files.vars).unwrap();
+        assert_eq!(config.interval, Duration::from_secs(30 * 60));
+        assert!(config.is_short);
+        assert_eq!(config.stashes.len(), 1);
+
+        // missing overlay files are skipped
+        let config_files = read_config_files(config_path, Some("node02")).unwrap();
+        assert_eq!(config_files.paths.len(), 2);
+        let config = Config::from_vars(config_files.vars).unwrap();
+        assert!(!config.is_short);
+        assert_eq!(
+            overlay_paths("/opt/scouty.toml", Some("node01")),
+            vec!["/opt/scouty.node01.toml", "/opt/scouty.local.toml"]
+        );
+    }
+
+    #[test]
+    fn it_reads_secrets_from_the_credentials_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("matrix_bot_password"),
+            "anotthateasypasswordfromsystemd\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("telegram_bot_token"),
+            "123456789:AAEanotthateasytoken\n",
+        )
+        .unwrap();
+
+        let config =
+            config_with(&[("SCOUTY_TELEGRAM_BOT_TOKEN", "123456789:AAEinlinetoken")])
+                .with_secret_files_from(Some(dir.path()))
+                .unwrap();
+        assert_eq!(
+            config.matrix_bot_password.expose(),
+            "anotthateasypasswordfromsystemd"
+        );
+        // secrets already defined are kept
+        assert_eq!(
+            config.telegram_bot_token.expose(),
+            "123456789:AAEinlinetoken"
+        );
+    }
+
+    #[test]
+    fn it_gets_the_expected_spec_version() {
+        let config = config_with(&[]);
+        assert_eq!(config.expected_spec_version, None);
+        // the check is skipped when no spec version is expected
+        assert_eq!(config.check_spec_version(9130), None);
+
+        let config = config_with(&[("SCOUTY_EXPECTED_SPEC_VERSION", "9130")]);
+        assert_eq!(config.expected_spec_version, Some(9130));
+        assert_eq!(config.check_spec_version(9130), None);
+        let warning = config.check_spec_version(9140).unwrap();
+        assert!(warning.contains("9140"));
+        assert!(warning.contains("9130"));
+    }
+
+    #[test]
+    fn it_gets_the_log_format() {
+        let config = config_with(&[]);
+        assert_eq!(config.log_format, LogFormat::Text);
+
+        for (value, log_format) in [("text", LogFormat::Text), ("json", LogFormat::Json)]
+        {
+            let config = config_with(&[("SCOUTY_LOG_FORMAT", value)]);
+            assert_eq!(config.log_format, log_format);
         }
-        Some("polkadot") => {
-            env::set_var("SCOUTY_SUBSTRATE_WS_URL", "wss://rpc.polkadot.io:443");
+
+        let vars = vec![
+            (
+                "SCOUTY_STASHES".to_string(),
+                "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY".to_string(),
+            ),
+            ("SCOUTY_LOG_FORMAT".to_string(), "xml".to_string()),
+        ];
+        assert!(Config::from_vars(vars).is_err());
+    }
+
+    #[test]
+    fn it_gets_the_head_mode() {
+        let config = config_with(&[]);
+        assert_eq!(config.head_mode, HeadMode::Finalized);
+
+        for (value, head_mode) in [
+            ("latest", HeadMode::Latest),
+            ("finalized", HeadMode::Finalized),
+        ] {
+            let config = config_with(&[("SCOUTY_HEAD_MODE", value)]);
+            assert_eq!(config.head_mode, head_mode);
+            assert_eq!(format!("{}", config.head_mode), value);
         }
-        _ => {
-            if env::var("SCOUTY_SUBSTRATE_WS_URL").is_err() {
-                env::set_var("SCOUTY_SUBSTRATE_WS_URL", "ws://127.0.0.1:9944");
-            };
+
+        let vars = vec![
+            (
+                "SCOUTY_STASHES".to_string(),
+                "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY".to_string(),
+            ),
+            ("SCOUTY_HEAD_MODE".to_string(), "best".to_string()),
+        ];
+        assert!(Config::from_vars(vars).is_err());
+    }
+
+    #[test]
+    fn it_gets_the_log_level() {
+        for (value, log_level) in [
+            ("error", LevelFilter::Error),
+            ("warn", LevelFilter::Warn),
+            ("info", LevelFilter::Info),
+            ("debug", LevelFilter::Debug),
+            ("trace", LevelFilter::Trace),
+        ] {
+            let config = config_with(&[("SCOUTY_LOG_LEVEL", value)]);
+            assert_eq!(config.log_level(), log_level);
         }
     }
 
-    if let Some(stashes) = matches.value_of("stashes") {
-        env::set_var("SCOUTY_STASHES", stashes);
+    #[test]
+    fn it_gets_the_log_level_from_is_debug() {
+        let config = config_with(&[]);
+        assert_eq!(config.log_level(), LevelFilter::Info);
+
+        let config = config_with(&[("SCOUTY_IS_DEBUG", "true")]);
+        assert_eq!(config.log_level(), LevelFilter::Debug);
+
+        // the explicit log level wins
+        let config =
+            config_with(&[("SCOUTY_IS_DEBUG", "true"), ("SCOUTY_LOG_LEVEL", "warn")]);
+        assert_eq!(config.log_level(), LevelFilter::Warn);
     }
 
-    if let Some(substrate_ws_url) = matches.value_of("substrate-ws-url") {
-        env::set_var("SCOUTY_SUBSTRATE_WS_URL", substrate_ws_url);
+    #[test]
+    fn it_fails_with_an_invalid_log_level() {
+        let vars = vec![
+            (
+                "SCOUTY_STASHES".to_string(),
+                "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY".to_string(),
+            ),
+            ("SCOUTY_LOG_LEVEL".to_string(), "verbose".to_string()),
+        ];
+        let err = Config::from_vars(vars).unwrap_err().to_string();
+        assert!(err.contains("invalid log level 'verbose'"), "{}", err);
     }
 
-    if matches.is_present("debug") {
-        env::set_var("SCOUTY_IS_DEBUG", "true");
+    #[test]
+    fn it_gets_the_command() {
+        assert_eq!("daemon".parse::<Command>(), Ok(Command::Daemon));
+        assert_eq!("view".parse::<Command>(), Ok(Command::View));
+        assert_eq!("rewards".parse::<Command>(), Err(()));
+
+        let config = config_with(&[]);
+        assert_eq!(config.command, Command::Daemon);
+
+        for (value, command) in [
+            ("daemon", Command::Daemon),
+            ("view", Command::View),
+            ("test-notifications", Command::TestNotifications),
+            ("list-chains", Command::ListChains),
+            ("config-example", Command::ConfigExample),
+            ("validate", Command::Validate),
+            ("decode", Command::Decode),
+        ] {
+            let config = config_with(&[("SCOUTY_COMMAND", value)]);
+            assert_eq!(config.command, command);
+        }
+
+        let matches = cli()
+            .get_matches_from_safe(vec!["scouty", "test-notifications"])
+            .unwrap();
+        assert_eq!(
+            matches.subcommand_name().and_then(|name| name.parse::<Command>().ok()),
+            Some(Command::TestNotifications)
+        );
     }
 
-    if matches.is_present("short") {
-        env::set_var("SCOUTY_IS_SHORT", "true");
+    #[test]
+    fn it_gets_no_color() {
+        let config = config_with(&[("SCOUTY_NO_COLOR", "true")]);
+        assert!(config.no_color);
+
+        // NO_COLOR being present also disables colors
+        let mut config = config_with(&[]);
+        config.no_color = false;
+        let no_color = |value: Option<&str>| {
+            config
+                .clone()
+                .with_no_color_from(value.map(OsStr::new))
+                .no_color
+        };
+        assert!(no_color(Some("1")));
+        assert!(!no_color(Some("")));
+        assert!(!no_color(None));
     }
 
-    if let Some(hook_init_path) = matches.value_of("hook-init-path") {
-        env::set_var("SCOUTY_HOOK_INIT_PATH", hook_init_path);
+    #[test]
+    fn it_gets_the_healthcheck_listen_addr() {
+        let config = config_with(&[("SCOUTY_HEALTHCHECK_LISTEN_ADDR", "0.0.0.0:8080")]);
+        assert_eq!(
+            config.healthcheck_listen_addr,
+            Some("0.0.0.0:8080".to_string())
+        );
+        assert!(config.validate().is_ok());
+
+        let config = config_with(&[("SCOUTY_HEALTHCHECK_LISTEN_ADDR", "localhost")]);
+        assert!(config.validate().is_err());
     }
 
-    if let Some(hook_new_session_path) = matches.value_of("hook-new-session-path") {
-        env::set_var("SCOUTY_HOOK_NEW_SESSION_PATH", hook_new_session_path);
+    #[test]
+    fn it_binds_the_api_on_loopback_by_default() {
+        for (addr, expected) in [
+            ("8081", "127.0.0.1:8081"),
+            (":8081", "127.0.0.1:8081"),
+            ("0.0.0.0:8081", "0.0.0.0:8081"),
+        ] {
+            let config = config_with(&[("SCOUTY_API_LISTEN_ADDR", addr)]);
+            assert_eq!(config.api_bind_addr(), Some(expected.to_string()));
+            assert!(config.validate().is_ok());
+        }
+
+        let config = config_with(&[("SCOUTY_API_LISTEN_ADDR", "localhost")]);
+        assert!(config.validate().is_err());
     }
 
-    if let Some(hook_new_era_path) = matches.value_of("hook-new-era-path") {
-        env::set_var("SCOUTY_HOOK_NEW_ERA_PATH", hook_new_era_path);
+    #[test]
+    fn it_fails_without_stashes_for_the_daemon() {
+        let config = Config::from_vars(vec![]).unwrap();
+        assert_eq!(config.command, Command::Daemon);
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("--stashes"), "{}", err);
+        assert!(err.contains("SCOUTY_STASHES"), "{}", err);
+
+        let vars = vec![("SCOUTY_STASHES".to_string(), "".to_string())];
+        let config = Config::from_vars(vars).unwrap();
+        assert!(config.stashes.is_empty());
+        assert!(config.validate().is_err());
+
+        // the view command accepts no stashes
+        let vars = vec![
+            ("SCOUTY_COMMAND".to_string(), "view".to_string()),
+            (
+                "SCOUTY_SUBSTRATE_WS_URL".to_string(),
+                "ws://127.0.0.1:9944".to_string(),
+            ),
+        ];
+        let config = Config::from_vars(vars).unwrap();
+        assert!(config.validate().is_ok());
     }
 
-    if let Some(hook_validator_starts_active_next_era_path) =
-        matches.value_of("hook-validator-starts-active-next-era-path")
-    {
-        env::set_var(
-            "SCOUTY_HOOK_VALIDATOR_STARTS_ACTIVE_NEXT_ERA_PATH",
-            hook_validator_starts_active_next_era_path,
+    #[test]
+    fn it_gets_the_commission_monitoring_config() {
+        let config = config_with(&[]);
+        assert_eq!(config.hook_validator_commission_changed_path, "");
+        assert!(!config.commission_monitoring_disabled);
+
+        let config = config_with(&[
+            (
+                "SCOUTY_HOOK_VALIDATOR_COMMISSION_CHANGED_PATH",
+                "/opt/scouty-cli/hooks/_validator_commission_changed.sh",
+            ),
+            ("SCOUTY_COMMISSION_MONITORING_DISABLED", "true"),
+        ]);
+        assert_eq!(
+            config.hook_validator_commission_changed_path,
+            "/opt/scouty-cli/hooks/_validator_commission_changed.sh"
         );
+        assert!(config.commission_monitoring_disabled);
+        assert!(config.is_hook_disabled(HOOK_VALIDATOR_COMMISSION_CHANGED));
     }
 
-    if let Some(hook_validator_starts_inactive_next_era_path) =
-        matches.value_of("hook-validator-starts-inactive-next-era-path")
-    {
-        env::set_var(
-            "SCOUTY_HOOK_VALIDATOR_STARTS_INACTIVE_NEXT_ERA_PATH",
-            hook_validator_starts_inactive_next_era_path,
-        );
+    #[test]
+    fn it_gets_the_slash_monitoring_config() {
+        let config = config_with(&[]);
+        assert_eq!(config.hook_slash_path, "");
+        assert!(!config.slash_monitoring_disabled);
+
+        let config = config_with(&[
+            ("SCOUTY_HOOK_SLASH_PATH", "/opt/scouty-cli/hooks/_slash.sh"),
+            ("SCOUTY_SLASH_MONITORING_DISABLED", "true"),
+        ]);
+        assert_eq!(config.hook_slash_path, "/opt/scouty-cli/hooks/_slash.sh");
+        assert!(config.slash_monitoring_disabled);
+        assert!(config.is_hook_disabled(HOOK_STASH_SLASHED));
     }
 
-    if let Some(hook_validator_slashed_path) =
-        matches.value_of("hook-validator-slashed-path")
-    {
-        env::set_var(
-            "SCOUTY_HOOK_VALIDATOR_SLASHED_PATH",
-            hook_validator_slashed_path,
+    #[test]
+    fn it_gets_the_rewards_monitoring_config() {
+        let config = config_with(&[]);
+        assert_eq!(config.hook_unclaimed_rewards_path, "");
+        assert!(!config.rewards_monitoring_disabled);
+        assert_eq!(config.rewards_lookback_eras, 14);
+
+        let config = config_with(&[
+            (
+                "SCOUTY_HOOK_UNCLAIMED_REWARDS_PATH",
+                "/opt/scouty-cli/hooks/_unclaimed_rewards.sh",
+            ),
+            ("SCOUTY_REWARDS_MONITORING_DISABLED", "true"),
+            ("SCOUTY_REWARDS_LOOKBACK_ERAS", "28"),
+        ]);
+        assert_eq!(
+            config.hook_unclaimed_rewards_path,
+            "/opt/scouty-cli/hooks/_unclaimed_rewards.sh"
         );
+        assert!(config.rewards_monitoring_disabled);
+        assert_eq!(config.rewards_lookback_eras, 28);
+        assert!(config.is_hook_disabled(HOOK_UNCLAIMED_REWARDS));
     }
 
-    if let Some(hook_validator_chilled_path) =
-        matches.value_of("hook-validator-chilled-path")
-    {
-        env::set_var(
-            "SCOUTY_HOOK_VALIDATOR_CHILLED_PATH",
-            hook_validator_chilled_path,
+    #[test]
+    fn it_matches_only_the_stashes_defined() {
+        let config = config_with(&[]);
+        assert!(config.is_stash("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"));
+        assert!(!config.is_stash("5GTD7ZeD823BjpmZBCSzBQp7cvHR1Gunq7oDkurZr9zUev2n"));
+        assert!(!config.is_stash(""));
+    }
+
+    #[test]
+    fn it_gets_the_chill_monitoring_config() {
+        let config = config_with(&[]);
+        assert_eq!(config.hook_validator_chilled_path, "");
+        assert!(!config.chill_monitoring_disabled);
+        assert!(!config.is_hook_disabled(HOOK_VALIDATOR_CHILLED));
+
+        let config = config_with(&[
+            (
+                "SCOUTY_HOOK_CHILLED_PATH",
+                "/opt/scouty-cli/hooks/_validator_chilled.sh",
+            ),
+            ("SCOUTY_CHILL_MONITORING_DISABLED", "true"),
+        ]);
+        assert_eq!(
+            config.hook_validator_chilled_path,
+            "/opt/scouty-cli/hooks/_validator_chilled.sh"
         );
+        assert!(config.chill_monitoring_disabled);
+        assert!(config.is_hook_disabled(HOOK_VALIDATOR_CHILLED));
     }
 
-    if let Some(hook_validator_offline_path) =
-        matches.value_of("hook-validator-offline-path")
-    {
-        env::set_var(
-            "SCOUTY_HOOK_VALIDATOR_OFFLINE_PATH",
-            hook_validator_offline_path,
+    #[test]
+    fn it_parses_the_enabled_events() {
+        let config = config_with(&[(
+            "SCOUTY_ENABLED_EVENTS",
+            "new_session, slash,inactive_next_era,",
+        )]);
+        assert_eq!(
+            config.enabled_events,
+            vec![
+                EventKind::NewSession,
+                EventKind::Slash,
+                EventKind::InactiveNextEra
+            ]
+            .into_iter()
+            .collect::<HashSet<EventKind>>()
         );
+        assert!(config.is_event_enabled(EventKind::Slash));
+        assert!(!config.is_event_enabled(EventKind::Chill));
+        assert!(config.is_hook_disabled(HOOK_VALIDATOR_CHILLED));
+        assert!(!config.is_hook_disabled(HOOK_NEW_SESSION));
     }
 
-    if let Some(hook_democracy_started_path) =
-        matches.value_of("hook-democracy-started-path")
-    {
-        env::set_var(
-            "SCOUTY_HOOK_DEMOCRACY_STARTED_PATH",
-            hook_democracy_started_path,
+    #[test]
+    fn it_enables_all_events_if_none_is_listed() {
+        let config = config_with(&[("SCOUTY_ENABLED_EVENTS", "")]);
+        assert!(config.enabled_events.is_empty());
+        assert!(EventKind::ALL
+            .iter()
+            .all(|kind| config.is_event_enabled(*kind)));
+
+        // A disable flag wins over the enabled events
+        let config = config_with(&[
+            ("SCOUTY_ENABLED_EVENTS", "chill,slash"),
+            ("SCOUTY_CHILL_MONITORING_DISABLED", "true"),
+        ]);
+        assert!(config.is_event_allowlisted(EventKind::Chill));
+        assert!(!config.is_event_enabled(EventKind::Chill));
+        assert!(config.is_event_enabled(EventKind::Slash));
+    }
+
+    #[test]
+    fn it_rejects_unknown_enabled_events() {
+        let vars = vec![
+            (
+                "SCOUTY_STASHES".to_string(),
+                "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY".to_string(),
+            ),
+            (
+                "SCOUTY_ENABLED_EVENTS".to_string(),
+                "new_session,slashed".to_string(),
+            ),
+        ];
+        let err = Config::from_vars(vars).unwrap_err();
+        assert!(err.to_string().contains("unknown event 'slashed'"));
+        assert_eq!("rewards".parse::<EventKind>(), Ok(EventKind::Rewards));
+        assert_eq!("REWARDS".parse::<EventKind>(), Err(()));
+    }
+
+    #[test]
+    fn it_validates_the_message_templates_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("templates.toml");
+        fs::write(&path, "new_session = \"Session {{session}} of era {{era}}\"").unwrap();
+
+        let config =
+            config_with(&[("SCOUTY_MESSAGE_TEMPLATES_PATH", path.to_str().unwrap())]);
+        assert!(config.validate().is_ok());
+
+        fs::write(&path, "unknown_template = \"{{era}}\"").unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn it_prints_the_effective_config_redacted() {
+        let vars = vec![
+            (
+                "SCOUTY_STASHES".to_string(),
+                "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY:Alice".to_string(),
+            ),
+            ("SCOUTY_INTERVAL".to_string(), "6h".to_string()),
+            (
+                "SCOUTY_MATRIX_BOT_PASSWORD".to_string(),
+                "very-secret-password".to_string(),
+            ),
+            ("SCOUTY_PRINT_CONFIG".to_string(), "env".to_string()),
+        ];
+        let config = Config::from_vars(vars).unwrap();
+        assert_eq!(config.print_config, Some(ConfigFormat::Env));
+
+        let output = config.print(ConfigFormat::Env).unwrap();
+        assert!(output.lines().any(|line| line == "SCOUTY_INTERVAL=6h"));
+        assert!(output.lines().any(|line| line
+            == "SCOUTY_STASHES=5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY:Alice"));
+        assert!(output.lines().any(|line| line == "SCOUTY_MATRIX_BOT_PASSWORD=***"));
+        assert!(!output.contains("very-secret-password"));
+        assert!(!output.contains("SCOUTY_PRINT_CONFIG"));
+
+        let output = config.print(ConfigFormat::Toml).unwrap();
+        assert!(output.contains("interval = \"6h\""));
+        assert!(!output.contains("very-secret-password"));
+    }
+
+    #[test]
+    fn it_reads_the_stashes_from_a_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("stashes.txt");
+        fs::write(
+            &path,
+            "# kusama validators\n\n5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY:Alice\n   \n  # 5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty\n5GTD7ZeD823BjpmZBCSzBQp7cvHR1Gunq7oDkurZr9zUev2n:Bob\n5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY:Charlie\n",
+        )
+        .unwrap();
+
+        let vars = vec![
+            (
+                "SCOUTY_STASHES".to_string(),
+                "5GTD7ZeD823BjpmZBCSzBQp7cvHR1Gunq7oDkurZr9zUev2n".to_string(),
+            ),
+            (
+                "SCOUTY_STASHES_PATH".to_string(),
+                path.to_str().unwrap().to_string(),
+            ),
+        ];
+        let config = Config::from_vars(vars).unwrap().with_stashes_file().unwrap();
+        assert_eq!(
+            config.stashes,
+            vec![
+                StashConfig {
+                    address: "5GTD7ZeD823BjpmZBCSzBQp7cvHR1Gunq7oDkurZr9zUev2n".to_string(),
+                    name: Some("Bob".to_string()),
+                    hook_active_next_era_path: None,
+                },
+                StashConfig {
+                    address: "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY".to_string(),
+                    name: Some("Alice".to_string()),
+                    hook_active_next_era_path: None,
+                },
+            ]
         );
+
+        let vars = vec![(
+            "SCOUTY_STASHES_PATH".to_string(),
+            dir.path().join("not_found.txt").to_str().unwrap().to_string(),
+        )];
+        assert!(Config::from_vars(vars).unwrap().with_stashes_file().is_err());
     }
 
-    if matches.is_present("expose-all") {
-        env::set_var("SCOUTY_EXPOSE_ALL", "true");
+    #[test]
+    fn it_gets_the_max_concurrent_queries() {
+        let config = config_with(&[]);
+        assert_eq!(config.max_concurrent_queries, 4);
+
+        let config = config_with(&[("SCOUTY_MAX_CONCURRENT_QUERIES", "0")]);
+        assert!(config.validate().is_err());
     }
 
-    if matches.is_present("expose-network") {
-        env::set_var("SCOUTY_EXPOSE_NETWORK", "true");
+    #[test]
+    fn it_gets_the_error_interval_max() {
+        let config = config_with(&[]);
+        assert_eq!(config.error_interval_max, Duration::from_secs(6 * 3600));
+
+        let config = config_with(&[("SCOUTY_ERROR_INTERVAL_MAX", "120")]);
+        assert_eq!(config.error_interval_max, Duration::from_secs(2 * 3600));
     }
 
-    if matches.is_present("expose-nominators") {
-        env::set_var("SCOUTY_EXPOSE_NOMINATORS", "true");
+    #[test]
+    fn it_gets_the_run_once_flag() {
+        let config = config_with(&[]);
+        assert!(!config.run_once);
+
+        let config = config_with(&[("SCOUTY_RUN_ONCE", "true")]);
+        assert!(config.run_once);
     }
 
-    if matches.is_present("expose-all-nominators") {
-        env::set_var("SCOUTY_EXPOSE_ALL_NOMINATORS", "true");
+    #[test]
+    fn it_resolves_the_stash_hook_overrides() {
+        let vars = vec![
+            (
+                "SCOUTY_STASHES".to_string(),
+                "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY:Alice:/opt/scouty-cli/hooks/_alice_active_next_era.sh,5GTD7ZeD823BjpmZBCSzBQp7cvHR1Gunq7oDkurZr9zUev2n:Bob".to_string(),
+            ),
+            (
+                "SCOUTY_HOOK_VALIDATOR_STARTS_ACTIVE_NEXT_ERA_PATH".to_string(),
+                "/opt/scouty-cli/hooks/_validator_starts_active_next_era.sh".to_string(),
+            ),
+        ];
+        let config = Config::from_vars(vars).unwrap();
+        assert_eq!(config.stashes[0].name(), "Alice");
+        assert_eq!(
+            config.stashes[0].hook_active_next_era_path.as_deref(),
+            Some("/opt/scouty-cli/hooks/_alice_active_next_era.sh")
+        );
+        assert_eq!(
+            config.hook_active_next_era_path(
+                "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+            ),
+            "/opt/scouty-cli/hooks/_alice_active_next_era.sh"
+        );
+        // stashes without override fall back to the global hook
+        assert_eq!(
+            config.hook_active_next_era_path(
+                "5GTD7ZeD823BjpmZBCSzBQp7cvHR1Gunq7oDkurZr9zUev2n"
+            ),
+            "/opt/scouty-cli/hooks/_validator_starts_active_next_era.sh"
+        );
+
+        // the label is optional
+        let stash = StashConfig::parse(
+            "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY::/opt/scouty-cli/hooks/_alice.sh",
+        );
+        assert_eq!(stash.name, None);
+        assert_eq!(
+            stash.hook_active_next_era_path.as_deref(),
+            Some("/opt/scouty-cli/hooks/_alice.sh")
+        );
     }
 
-    if matches.is_present("expose-authored-blocks") {
-        env::set_var("SCOUTY_EXPOSE_AUTHORED_BLOCKS", "true");
+    #[test]
+    #[cfg(unix)]
+    fn it_warns_about_config_files_accessible_by_others() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join(".env");
+        fs::write(&config_path, "SCOUTY_MATRIX_BOT_PASSWORD=secret\n").unwrap();
+        let config_path = config_path.to_str().unwrap();
+
+        fs::set_permissions(config_path, fs::Permissions::from_mode(0o644)).unwrap();
+        let warning = check_config_file_permissions(config_path).unwrap();
+        assert!(warning.contains("mode 644"));
+
+        fs::set_permissions(config_path, fs::Permissions::from_mode(0o600)).unwrap();
+        assert_eq!(check_config_file_permissions(config_path), None);
+
+        // files not found are not checked
+        assert_eq!(
+            check_config_file_permissions(dir.path().join("missing").to_str().unwrap()),
+            None
+        );
+
+        // the overlay files are checked too
+        fs::write(
+            dir.path().join(".env.local"),
+            "SCOUTY_STASHES=5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY\nSCOUTY_SUBSTRATE_WS_URL=ws://127.0.0.1:9944\n",
+        )
+        .unwrap();
+        fs::set_permissions(
+            dir.path().join(".env.local"),
+            fs::Permissions::from_mode(0o644),
+        )
+        .unwrap();
+        let source = ReloadSource {
+            config_path: Some(config_path.to_string()),
+            ..Default::default()
+        };
+        let config = reload_from(&source, &config_with(&[])).unwrap();
+        assert_eq!(config.load_warnings.len(), 1, "{:?}", config.load_warnings);
+        assert!(config.load_warnings[0].contains(".env.local"));
     }
 
-    if matches.is_present("expose-para-validator") {
-        env::set_var("SCOUTY_EXPOSE_PARA_VALIDATOR", "true");
+    #[test]
+    fn it_validates_the_substrate_ws_urls() {
+        assert!(validate_ws_url("wss://rpc.polkadot.io:443").is_ok());
+        assert!(validate_ws_url("wss://kusama-rpc.polkadot.io").is_ok());
+        assert!(validate_ws_url("ws://127.0.0.1:9944").is_ok());
+        assert!(validate_ws_url("ws://[::1]:9944").is_ok());
+        let err = validate_ws_url("http://foo").unwrap_err().to_string();
+        assert!(err.contains("scheme must be ws or wss"));
+        // IPv6 hosts without brackets are ambiguous with the port
+        assert!(validate_ws_url("ws://::1:9944").is_err());
+        assert!(validate_ws_url("localhost:9944").is_err());
     }
 
-    if matches.is_present("expose-era-points") {
-        env::set_var("SCOUTY_EXPOSE_ERA_POINTS", "true");
+    #[test]
+    fn it_rejects_an_empty_substrate_ws_url_list() {
+        let config = config_with(&[
+            ("SCOUTY_SUBSTRATE_WS_URL", ""),
+            ("SCOUTY_SUBSTRATE_WS_URLS", " , "),
+        ]);
+        assert!(config.substrate_ws_urls.is_empty());
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("--substrate-ws-url"), "{}", err);
+        assert!(err.contains("SCOUTY_SUBSTRATE_WS_URLS"), "{}", err);
     }
 
-    if matches.is_present("expose-all") {
-        env::set_var("SCOUTY_EXPOSE_ALL", "true");
+    #[test]
+    fn it_limits_the_number_of_stashes() {
+        let vars = vec![
+            (
+                "SCOUTY_STASHES".to_string(),
+                "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY,5GTD7ZeD823BjpmZBCSzBQp7cvHR1Gunq7oDkurZr9zUev2n".to_string(),
+            ),
+            (
+                "SCOUTY_SUBSTRATE_WS_URL".to_string(),
+                "ws://127.0.0.1:9944".to_string(),
+            ),
+            ("SCOUTY_MAX_STASHES".to_string(), "1".to_string()),
+        ];
+        let config = Config::from_vars(vars).unwrap();
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("2 stashes defined, more than the maximum of 1"));
+
+        let vars = vec![
+            (
+                "SCOUTY_STASHES".to_string(),
+                "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY,5GTD7ZeD823BjpmZBCSzBQp7cvHR1Gunq7oDkurZr9zUev2n".to_string(),
+            ),
+            (
+                "SCOUTY_SUBSTRATE_WS_URL".to_string(),
+                "ws://127.0.0.1:9944".to_string(),
+            ),
+            ("SCOUTY_MAX_STASHES".to_string(), "2".to_string()),
+        ];
+        let config = Config::from_vars(vars).unwrap();
+        assert!(config.validate().is_ok());
     }
 
-    if matches.is_present("disable-matrix") {
-        env::set_var("SCOUTY_MATRIX_DISABLED", "true");
+    #[test]
+    fn it_reads_the_vars_with_a_custom_env_prefix() {
+        let vars = vec![
+            (
+                "SCOUTY_STASHES".to_string(),
+                "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY".to_string(),
+            ),
+            (
+                "SCOUTY_A_STASHES".to_string(),
+                "5GTD7ZeD823BjpmZBCSzBQp7cvHR1Gunq7oDkurZr9zUev2n".to_string(),
+            ),
+            ("SCOUTY_A_IS_SHORT".to_string(), "true".to_string()),
+            (
+                "SCOUTY_B_STASHES".to_string(),
+                "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY".to_string(),
+            ),
+        ];
+        let config = Config::from_vars(namespaced_env_vars("SCOUTY_A", vars)).unwrap();
+        assert_eq!(
+            config
+                .stashes
+                .iter()
+                .map(|stash| stash.address.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["5GTD7ZeD823BjpmZBCSzBQp7cvHR1Gunq7oDkurZr9zUev2n"]
+        );
+        assert!(config.is_short);
     }
 
-    if let Some(matrix_user) = matches.value_of("matrix-user") {
-        env::set_var("SCOUTY_MATRIX_ACCOUNT", matrix_user);
+    #[test]
+    fn it_gets_the_explorer_base_url() {
+        let config = config_with(&[("SCOUTY_CHAIN", "kusama")]);
+        assert_eq!(
+            config.explorer_base_url("Kusama"),
+            Some("https://kusama.subscan.io".to_string())
+        );
+
+        let config = config_with(&[]);
+        assert_eq!(
+            config.explorer_base_url("Polkadot"),
+            Some("https://polkadot.subscan.io".to_string())
+        );
+        assert_eq!(config.explorer_base_url("Rococo"), None);
+
+        let config = config_with(&[
+            ("SCOUTY_CHAIN", "kusama"),
+            ("SCOUTY_EXPLORER_BASE_URL", "https://explorer.mycompany.com/"),
+        ]);
+        assert_eq!(
+            config.explorer_base_url("Kusama"),
+            Some("https://explorer.mycompany.com".to_string())
+        );
     }
 
-    if let Some(matrix_bot_user) = matches.value_of("matrix-bot-user") {
-        env::set_var("SCOUTY_MATRIX_BOT_USER", matrix_bot_user);
+    #[test]
+    fn it_gets_the_ss58_prefix() {
+        let config = config_with(&[("SCOUTY_CHAIN", "kusama")]);
+        assert_eq!(config.ss58_prefix(), Some(2));
+
+        // the prefix defined takes precedence over the chain preset
+        let config =
+            config_with(&[("SCOUTY_CHAIN", "kusama"), ("SCOUTY_SS58_PREFIX", "42")]);
+        assert_eq!(config.ss58_prefix(), Some(42));
+        assert!(config.validate().is_ok());
+
+        // custom chains skip the prefix check
+        let config = config_with(&[("SCOUTY_SUBSTRATE_WS_URL", "ws://10.0.0.1:9944")]);
+        assert_eq!(config.ss58_prefix(), None);
+        assert!(config.validate().is_ok());
     }
 
-    if let Some(matrix_bot_password) = matches.value_of("matrix-bot-password") {
-        env::set_var("SCOUTY_MATRIX_BOT_PASSWORD", matrix_bot_password);
+    #[test]
+    fn it_gets_the_quiet_mode() {
+        let config =
+            config_with(&[("SCOUTY_QUIET", "true"), ("SCOUTY_IS_DEBUG", "true")]);
+        assert!(config.quiet);
+        assert_eq!(config.log_level(), LevelFilter::Warn);
+
+        assert!(cli().get_matches_from_safe(vec!["scouty", "--quiet"]).is_ok());
+        assert!(cli()
+            .get_matches_from_safe(vec!["scouty", "--quiet", "--debug"])
+            .is_err());
     }
 
-    if let Some(error_interval) = matches.value_of("error-interval") {
-        env::set_var("SCOUTY_ERROR_INTERVAL", error_interval);
+    #[test]
+    fn it_warns_when_both_chain_and_substrate_ws_url_are_defined() {
+        let matches = cli()
+            .get_matches_from_safe(vec![
+                "scouty",
+                "kusama",
+                "--substrate-ws-url",
+                "ws://10.0.0.1:9944",
+            ])
+            .unwrap();
+        let vars = vec![
+            (
+                "SCOUTY_STASHES".to_string(),
+                "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY".to_string(),
+            ),
+            ("SCOUTY_CHAIN".to_string(), "kusama".to_string()),
+            (
+                "SCOUTY_SUBSTRATE_WS_URL".to_string(),
+                matches.value_of("substrate-ws-url").unwrap().to_string(),
+            ),
+        ];
+        let config = Config::from_vars(vars).unwrap().with_chain_ws_url_checked(
+            matches.value_of("CHAIN"),
+            matches.value_of("substrate-ws-url"),
+        );
+        assert_eq!(config.load_warnings.len(), 1);
+        assert!(config.load_warnings[0].contains("ws://10.0.0.1:9944 is used"));
+        assert_eq!(
+            config.substrate_ws_urls.first(),
+            Some(&"ws://10.0.0.1:9944".to_string())
+        );
+
+        // no warning if only one of them is defined
+        let config = config_with(&[]).with_chain_ws_url_checked(Some("kusama"), None);
+        assert!(config.load_warnings.is_empty());
+    }
+
+    #[test]
+    fn it_expands_env_vars_in_hook_paths() {
+        let home = env::var("HOME").unwrap();
+        assert_eq!(
+            expand_env_vars("$HOME/scouty/hooks/active.sh"),
+            format!("{}/scouty/hooks/active.sh", home)
+        );
+        assert_eq!(
+            expand_env_vars("${HOME}/scouty/hooks/active.sh"),
+            format!("{}/scouty/hooks/active.sh", home)
+        );
+
+        let lookup = |name: &str| match name {
+            "HOOKS_DIR" => Some("/opt/scouty-cli/hooks".to_string()),
+            _ => None,
+        };
+        assert_eq!(
+            expand_vars_with("${HOOKS_DIR}/_new_era.sh", lookup),
+            "/opt/scouty-cli/hooks/_new_era.sh"
+        );
+        // escaped and undefined references are kept
+        assert_eq!(
+            expand_vars_with("/opt/$$HOOKS_DIR/_new_era.sh", lookup),
+            "/opt/$HOOKS_DIR/_new_era.sh"
+        );
+        assert_eq!(
+            expand_vars_with("$$$HOOKS_DIR", lookup),
+            "$/opt/scouty-cli/hooks"
+        );
+        assert_eq!(
+            expand_vars_with("$UNDEFINED/${UNDEFINED}", lookup),
+            "$UNDEFINED/${UNDEFINED}"
+        );
+        assert_eq!(expand_vars_with("/opt/$/${", lookup), "/opt/$/${");
+
+        let config = config_with(&[(
+            "SCOUTY_HOOK_NEW_ERA_PATH",
+            "$HOME/scouty/hooks/_new_era.sh",
+        )])
+        .with_hook_paths_expanded();
+        assert_eq!(
+            config.hook_new_era_path,
+            format!("{}/scouty/hooks/_new_era.sh", home)
+        );
     }
 
-    match envy::prefixed("SCOUTY_").from_env::<Config>() {
-        Ok(config) => config,
-        Err(error) => panic!("Configuration error: {:#?}", error),
+    #[test]
+    fn it_gets_the_rpc_client_name() {
+        let config = config_with(&[]);
+        assert_eq!(config.rpc_client_name, None);
+        assert_eq!(
+            config.rpc_client_name(),
+            format!("scouty/{}", env!("CARGO_PKG_VERSION"))
+        );
+
+        let config = config_with(&[("SCOUTY_RPC_CLIENT_NAME", "scouty-node-01")]);
+        assert_eq!(config.rpc_client_name(), "scouty-node-01");
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn it_gets_the_connection_timeouts() {
+        let config = config_with(&[]);
+        assert_eq!(config.connect_timeout_seconds, 30);
+        assert_eq!(config.request_timeout_seconds, 60);
+        assert_eq!(config.connect_timeout(), Duration::from_secs(30));
+        assert_eq!(config.request_timeout(), Duration::from_secs(60));
+
+        let config = config_with(&[
+            ("SCOUTY_CONNECT_TIMEOUT_SECONDS", "5"),
+            ("SCOUTY_REQUEST_TIMEOUT_SECONDS", "0"),
+        ]);
+        assert_eq!(config.connect_timeout(), Duration::from_secs(5));
+        assert_eq!(config.request_timeout(), Duration::from_secs(0));
+    }
 
     #[test]
-    fn it_gets_a_config() {
-        let config = get_config();
-        assert_ne!(config.substrate_ws_url, "".to_string());
+    fn it_gets_the_startup_delay() {
+        let config = config_with(&[]);
+        assert_eq!(config.startup_delay_seconds, 0);
+
+        let config = config_with(&[("SCOUTY_STARTUP_DELAY_SECONDS", "15")]);
+        assert_eq!(config.startup_delay(), Duration::from_secs(15));
     }
 
     #[test]
-    fn it_gets_a_config_from_the_lazy_static() {
-        let config = &CONFIG;
-        assert_ne!(config.substrate_ws_url, "".to_string());
+    fn it_filters_the_events_per_matrix_room() {
+        let config = config_with(&[]);
+        assert!(config.matrix_rooms.is_empty());
+
+        let config = config_with(&[(
+            "SCOUTY_MATRIX_ROOMS",
+            "!oncall:matrix.org=slash|chill,!general:matrix.org=session,!all:matrix.org",
+        )]);
+        assert_eq!(
+            config.matrix_rooms,
+            vec![
+                MatrixRoom {
+                    room_id: "!oncall:matrix.org".to_string(),
+                    events: vec!["slash".to_string(), "chill".to_string()],
+                },
+                MatrixRoom {
+                    room_id: "!general:matrix.org".to_string(),
+                    events: vec!["session".to_string()],
+                },
+                MatrixRoom {
+                    room_id: "!all:matrix.org".to_string(),
+                    events: vec![],
+                },
+            ]
+        );
+        let rooms_for = |event: &str| {
+            config
+                .matrix_rooms
+                .iter()
+                .filter(|room| room.accepts(event))
+                .map(|room| room.room_id.as_str())
+                .collect::<Vec<&str>>()
+        };
+        assert_eq!(rooms_for("slash"), vec!["!oncall:matrix.org", "!all:matrix.org"]);
+        assert_eq!(
+            rooms_for("session"),
+            vec!["!general:matrix.org", "!all:matrix.org"]
+        );
+        assert_eq!(rooms_for("heartbeat"), vec!["!all:matrix.org"]);
+        assert!(config.validate().is_ok());
+
+        let config =
+            config_with(&[("SCOUTY_MATRIX_ROOMS", "!oncall:matrix.org=slashes")]);
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("slashes"));
+    }
+
+    #[test]
+    fn it_validates_the_matrix_user_ids() {
+        let matrix_config = |user: &str, disabled: &str| {
+            config_with(&[
+                ("SCOUTY_MATRIX_USER", user),
+                ("SCOUTY_MATRIX_BOT_USER", "@scouty-bot:matrix.org"),
+                ("SCOUTY_MATRIX_DISABLED", disabled),
+            ])
+        };
+        assert!(matrix_config("@your-account:matrix.org", "false")
+            .validate()
+            .is_ok());
+
+        let err = matrix_config("your-account:matrix.org", "false")
+            .validate()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Matrix user 'your-account:matrix.org' is not valid, expected @localpart:server e.g. @your-own-scouty-bot-account:matrix.org"
+        );
+        let err = matrix_config("@your-account", "false")
+            .validate()
+            .unwrap_err();
+        assert!(err.to_string().contains("'@your-account'"));
+
+        // The user ids are not checked with matrix disabled
+        assert!(matrix_config("your-account", "true").validate().is_ok());
     }
 }
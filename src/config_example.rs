@@ -0,0 +1,291 @@
+// The MIT License (MIT)
+// Copyright © 2021 Aukbit Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Generates a commented example configuration from the `Config` definition
+//
+// The field names are the ones serde expects when deserializing a `Config`,
+// so a new field shows up in the example without any other change, and the
+// defaults are the values of a `Config` loaded without any variable defined.
+use crate::config::{toml_scalar_to_string, Config, ConfigFormat};
+use crate::errors::ConfigError;
+use serde::{
+    de::{self, Visitor},
+    forward_to_deserialize_any, Deserialize, Deserializer,
+};
+
+// serde lists the aliases along the field names, the deprecated ones are left
+// out of the example
+const DEPRECATED_ALIASES: &[&str] = &["hook_chilled_path"];
+
+// One line description of each config field
+const DESCRIPTIONS: &[(&str, &str)] = &[
+    ("interval", "Interval between checks, accepts durations like 6h or 30m, bare integers are in seconds"),
+    ("error_interval", "Interval before restarting after a critical error, bare integers are in minutes"),
+    ("error_interval_max", "Maximum error interval, the error interval doubles on every consecutive error"),
+    ("max_restarts", "Consecutive restarts after critical errors before giving up, unlimited if not defined"),
+    ("substrate_ws_url", "Substrate websocket endpoint to connect to"),
+    ("substrate_ws_urls", "Comma separated substrate websocket endpoints as <url>|<priority>, tried by priority and then in order on connection errors"),
+    ("endpoint_promotion_interval", "Interval from which a higher priority endpoint is checked while connected to a lower priority one, zero means never"),
+    ("substrate_ws_tls_fingerprint", "SHA-256 fingerprint of the substrate node TLS certificate to pin"),
+    ("substrate_ws_proxy", "Proxy for the substrate connection, takes precedence over https_proxy, http_proxy and all_proxy"),
+    ("rpc_client_name", "Name 'scouty' identifies itself with to the substrate connection"),
+    ("connect_timeout_seconds", "Connection timeout in seconds, zero means no timeout"),
+    ("request_timeout_seconds", "RPC request timeout in seconds, zero means no timeout"),
+    ("startup_delay_seconds", "Delay in seconds before the first connection attempt"),
+    ("confirmation_blocks", "Blocks on top of a block before its events are acted on, only with head_mode latest"),
+    ("head_mode", "Head of the chain followed, latest or finalized"),
+    ("chain", "Chain preset, one of the chains printed by the list-chains command"),
+    ("ss58_prefix", "SS58 prefix of the addresses, overrides the one of the chain preset"),
+    ("token_decimals", "Decimals of the chain token, used to format the balances"),
+    ("token_symbol", "Symbol of the chain token, used to format the balances"),
+    ("explorer_base_url", "Block explorer used in the links, overrides the one of the chain preset"),
+    ("timezone", "IANA timezone the timestamps in the notifications are rendered in, e.g. Europe/Lisbon"),
+    ("stashes", "Comma separated stash addresses to monitor"),
+    ("stashes_path", "File with one stash per line, merged with the stashes defined inline"),
+    ("max_stashes", "Safety limit for the number of stashes monitored"),
+    ("is_debug", "Logs debug messages"),
+    ("quiet", "Only logs warnings and errors, takes precedence over is_debug"),
+    ("log_level", "Log level, one of error, warn, info, debug or trace, takes precedence over quiet and is_debug"),
+    ("log_format", "Format of the log lines, text or json"),
+    ("no_color", "Disables the colors of the log lines"),
+    ("log_file_path", "File the log lines are written to instead of stderr"),
+    ("log_file_max_size_mb", "Size in MB at which the log file is rotated, zero is never"),
+    ("log_file_max_files", "Rotated log files kept, the oldest one is removed"),
+    ("is_short", "Hides certain sections of the messages"),
+    ("is_dry_run", "Prints the resolved configuration and exits without connecting to the substrate node"),
+    ("run_once", "Runs a single monitoring pass and exits, e.g. when scheduled by cron"),
+    ("command", "Command to run, one of daemon, view, test-notifications, list-chains, config-example, validate or decode"),
+    ("print_config", "Prints the effective configuration in the given format, env or toml, and exits"),
+    ("config_example_format", "Format of the example printed by the config-example command, env or toml"),
+    ("decode_address", "SS58 address decoded by the decode command"),
+    ("hook_init_path", "Script executed once 'scouty' starts"),
+    ("hook_new_session_path", "Script executed every new session"),
+    ("hook_new_era_path", "Script executed every new era"),
+    ("hook_validator_starts_active_next_era_path", "Script executed when a stash is in the active set of the next era"),
+    ("hook_validator_starts_inactive_next_era_path", "Script executed when a stash is not in the active set of the next era"),
+    ("hook_validator_chilled_path", "Script executed when a stash is chilled"),
+    ("hook_validator_slashed_path", "Script executed when a stash is slashed"),
+    ("hook_validator_offline_path", "Script executed when a stash is reported offline"),
+    ("hook_democracy_started_path", "Script executed when a democracy referendum starts"),
+    ("hook_validator_commission_changed_path", "Script executed when the commission of a stash changes"),
+    ("hook_slash_path", "Script executed when any validator is slashed"),
+    ("hook_timeout_seconds", "Hook timeout in seconds, zero means no timeout"),
+    ("hooks_async", "Runs the hooks in the background instead of blocking the monitoring loop"),
+    ("hook_failure_fatal", "Exits when a hook exits non-zero instead of restarting the subscription"),
+    ("hook_extra_args", "Static args appended to every hook invocation, split as shell words"),
+    ("hook_new_session_disabled", "Disables the new session hook without clearing its path"),
+    ("hook_active_next_era_disabled", "Disables the active next era hook without clearing its path"),
+    ("hook_inactive_next_era_disabled", "Disables the inactive next era hook without clearing its path"),
+    ("commission_monitoring_disabled", "Disables the commission changes checked every new era"),
+    ("stake_change_threshold_percent", "Notifies nominators count or total stake shifts above the percent every new era, 0 is off"),
+    ("slash_monitoring_disabled", "Disables the slashes notified for the stashes defined"),
+    ("hook_unclaimed_rewards_path", "Script executed when a stash has unclaimed rewards"),
+    ("rewards_monitoring_disabled", "Disables the unclaimed rewards checked every new era"),
+    ("rewards_lookback_eras", "Number of past eras checked for unclaimed rewards"),
+    ("chill_monitoring_disabled", "Disables the chilled and disabled stashes notified"),
+    ("enabled_events", "Comma separated events to monitor, all of them if empty"),
+    ("message_templates_path", "TOML file of named message templates, the built-in defaults are used for the ones not defined"),
+    ("matrix_user", "Your regular matrix user id, e.g. @your-regular-matrix-account:matrix.org"),
+    ("matrix_bot_user", "Matrix user id of the bot that sends the messages, e.g. @your-own-scouty-bot:matrix.org"),
+    ("matrix_bot_password", "Password of the matrix bot user"),
+    ("matrix_bot_password_path", "File with the password of the matrix bot user, takes precedence over matrix_bot_password"),
+    ("matrix_bot_password_vault_path", "Vault KV v2 path of the matrix bot user password, as <mount>/<path>#<key>"),
+    ("matrix_disabled", "Disables the matrix notifications"),
+    ("matrix_bot_display_name_disabled", "Keeps the display name of the matrix bot unchanged"),
+    ("matrix_homeserver_url", "Matrix homeserver, overrides matrix.org"),
+    ("matrix_format", "Format of the matrix messages, plain or html"),
+    ("matrix_rooms", "Rooms the messages are sent to filtered by event, the private room if empty"),
+    ("telegram_bot_token", "Token of the telegram bot that sends the messages"),
+    ("telegram_bot_token_vault_path", "Vault KV v2 path of the telegram bot token, as <mount>/<path>#<key>"),
+    ("telegram_chat_id", "Telegram chat the messages are sent to"),
+    ("telegram_disabled", "Disables the telegram notifications"),
+    ("heartbeat_interval_hours", "Interval in hours between heartbeat notifications, zero is off"),
+    ("heartbeat_node_health", "Includes the peer count and sync state of the node in the heartbeat"),
+    ("daily_report_enabled", "Sends a daily summary of the activity of each stash"),
+    ("daily_report_hour", "Hour of the day (0-23) the daily report is sent, in the timezone defined"),
+    ("notification_rate_limit_per_minute", "Maximum notifications sent per minute, zero is unlimited"),
+    ("notification_dedup_window_seconds", "Window in seconds in which identical consecutive notifications are suppressed"),
+    ("notification_prefix", "Text added at the start of every notification, e.g. [kusama-01]"),
+    ("notification_suffix", "Text added at the end of every notification"),
+    ("notify_on_change_only", "Only sends session notifications when the active state of a stash changes"),
+    ("suppress_startup_notifications", "Only records the state observed in the first monitoring cycle after start up"),
+    ("state_file_path", "File where the last state observed of each stash is kept across restarts"),
+    ("smtp_host", "SMTP server the emails are sent through"),
+    ("smtp_port", "Port of the SMTP server"),
+    ("smtp_user", "User of the SMTP server"),
+    ("smtp_password", "Password of the SMTP server"),
+    ("smtp_password_vault_path", "Vault KV v2 path of the SMTP password, as <mount>/<path>#<key>"),
+    ("smtp_from", "Sender address of the emails"),
+    ("smtp_to", "Comma separated recipient addresses of the emails"),
+    ("smtp_disabled", "Disables the email notifications"),
+    ("webhook_url", "Url the events are posted to as JSON"),
+    ("webhook_disabled", "Disables the webhook notifications"),
+    ("pagerduty_routing_key", "Routing key of the PagerDuty service critical events are sent to"),
+    ("pagerduty_routing_key_vault_path", "Vault KV v2 path of the PagerDuty routing key, as <mount>/<path>#<key>"),
+    ("pagerduty_disabled", "Disables the PagerDuty incidents"),
+    ("vault_addr", "Address of the HashiCorp Vault server the secrets with a vault path are read from, VAULT_ADDR if not defined"),
+    ("vault_token", "Token to authenticate on the HashiCorp Vault server, VAULT_TOKEN if not defined"),
+    ("prometheus_listen_addr", "Address the prometheus metrics are served on, e.g. 0.0.0.0:9090"),
+    ("healthcheck_listen_addr", "Address the healthcheck liveness probe is served on, e.g. 0.0.0.0:8080"),
+    ("api_listen_addr", "Address the read-only JSON API is served on, a port alone binds 127.0.0.1"),
+    ("expected_spec_version", "Runtime spec version known to be supported, a warning is logged on a different one"),
+    ("max_concurrent_queries", "Maximum per-stash queries sent to the substrate node at the same time"),
+    ("expose_network", "Exposes the network name, token symbol and token decimals as hook arguments"),
+    ("expose_nominators", "Exposes the active nominators of each stash as hook arguments"),
+    ("expose_authored_blocks", "Exposes the number of blocks authored by each stash as hook arguments"),
+    ("expose_all_nominators", "Exposes all the nominators of each stash as hook arguments"),
+    ("expose_para_validator", "Exposes the para validator details of each stash as hook arguments"),
+    ("expose_era_points", "Exposes the era points of each stash as arguments of the new era hook"),
+    ("expose_all", "Exposes all the optional hook arguments"),
+];
+
+/// Deserializer that only records the field names serde expects for a struct
+struct FieldNames<'a> {
+    fields: &'a mut &'static [&'static str],
+}
+
+impl<'de, 'a> Deserializer<'de> for FieldNames<'a> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(
+        self,
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        Err(de::Error::custom("expected a struct"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.fields = fields;
+        Err(de::Error::custom("field names recorded"))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+/// Returns the name of every field of the `Config` definition
+pub fn config_fields() -> Vec<&'static str> {
+    let mut fields: &'static [&'static str] = &[];
+    // the deserialization always fails once the field names are recorded
+    let _ = Config::deserialize(FieldNames {
+        fields: &mut fields,
+    });
+    fields
+        .iter()
+        .filter(|field| !DEPRECATED_ALIASES.contains(field))
+        .copied()
+        .collect()
+}
+
+fn description(field: &str) -> &'static str {
+    DESCRIPTIONS
+        .iter()
+        .find(|(name, _)| *name == field)
+        .map_or("", |(_, description)| description)
+}
+
+/// Returns a fully commented example config, either as 'SCOUTY_' env vars or as
+/// TOML, with every field set to its default value
+pub fn generate(format: ConfigFormat) -> Result<String, ConfigError> {
+    let defaults: Config = envy::prefixed("SCOUTY_")
+        .from_iter(Vec::<(String, String)>::new())
+        .map_err(|e| ConfigError::Other(e.to_string()))?;
+    let defaults = match toml::Value::try_from(defaults)
+        .map_err(|e| ConfigError::Other(e.to_string()))?
+    {
+        toml::Value::Table(table) => table,
+        _ => toml::value::Table::new(),
+    };
+    let mut entries: Vec<String> = Vec::new();
+    for field in config_fields() {
+        let default = defaults.get(field);
+        let line = match format {
+            ConfigFormat::Env => {
+                let value = match default {
+                    Some(toml::Value::Array(values)) => values
+                        .iter()
+                        .filter_map(toml_scalar_to_string)
+                        .collect::<Vec<String>>()
+                        .join(","),
+                    Some(value) => toml_scalar_to_string(value).unwrap_or_default(),
+                    None => String::new(),
+                };
+                format!("#SCOUTY_{}={}", field.to_uppercase(), value)
+            }
+            ConfigFormat::Toml => match default {
+                Some(value) => format!("#{} = {}", field, value),
+                None => format!("#{} =", field),
+            },
+        };
+        entries.push(format!("# {}\n{}", description(field), line));
+    }
+    Ok(entries.join("\n\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_lists_every_config_field() {
+        let fields = config_fields();
+        assert!(fields.contains(&"interval"));
+        assert!(fields.contains(&"hook_validator_chilled_path"));
+        assert!(!fields.contains(&"hook_chilled_path"));
+        assert!(!fields.contains(&"load_warnings"));
+        for field in fields.iter() {
+            assert!(
+                !description(field).is_empty(),
+                "{} has no description",
+                field
+            );
+        }
+    }
+
+    #[test]
+    fn it_generates_a_config_example() {
+        let fields = config_fields();
+        let env = generate(ConfigFormat::Env).unwrap();
+        let toml = generate(ConfigFormat::Toml).unwrap();
+        for field in fields.iter() {
+            assert!(env.contains(&format!("#SCOUTY_{}=", field.to_uppercase())));
+            assert!(toml.contains(&format!("#{} =", field)));
+        }
+        assert!(env.contains("#SCOUTY_INTERVAL=6h"));
+        assert!(env.contains("#SCOUTY_SMTP_PORT=587"));
+        assert!(toml.contains("#rewards_lookback_eras = 14"));
+        // every line is commented out
+        assert!(env
+            .lines()
+            .all(|line| line.is_empty() || line.starts_with('#')));
+        assert!(toml
+            .lines()
+            .all(|line| line.is_empty() || line.starts_with('#')));
+    }
+}
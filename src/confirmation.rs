@@ -0,0 +1,167 @@
+// The MIT License (MIT)
+// Copyright © 2021 Aukbit Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+use futures::Future;
+use std::collections::VecDeque;
+
+/// Holds the events of each block until the given number of blocks have been
+/// seen on top of it, so that hooks and notifications are never fired for an
+/// event of a short fork. Finalized blocks are never reverted, so the depth only
+/// matters when following the latest blocks (`head_mode=latest`).
+#[derive(Debug)]
+pub struct Confirmations<H, T> {
+    depth: u32,
+    pending: VecDeque<(u32, H, T)>,
+}
+
+impl<H: PartialEq, T> Confirmations<H, T> {
+    pub fn new(depth: u32) -> Confirmations<H, T> {
+        Confirmations {
+            depth,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Queues the item of the block and returns every item pending confirmed by
+    /// it, with its block number and hash, in the order they were queued. With
+    /// zero depth the item is returned straight away.
+    pub fn push(
+        &mut self,
+        block_number: u32,
+        block_hash: H,
+        item: T,
+    ) -> Vec<(u32, H, T)> {
+        self.pending.push_back((block_number, block_hash, item));
+        let mut confirmed = Vec::new();
+        while let Some((pending_block_number, _, _)) = self.pending.front() {
+            if block_number < pending_block_number.saturating_add(self.depth) {
+                break;
+            }
+            if let Some(pending) = self.pending.pop_front() {
+                confirmed.push(pending);
+            }
+        }
+        confirmed
+    }
+
+    /// Keeps only the confirmed items whose block is still the canonical one at
+    /// its height, as returned by `canonical_hash`, so that a block replaced by a
+    /// reorg while it was pending is dropped. With zero depth nothing was held
+    /// back, so the items are kept without querying the chain.
+    pub async fn retain_canonical<F, Fut, E>(
+        &self,
+        confirmed: Vec<(u32, H, T)>,
+        canonical_hash: F,
+    ) -> Result<Vec<T>, E>
+    where
+        F: Fn(u32) -> Fut,
+        Fut: Future<Output = Result<Option<H>, E>>,
+    {
+        let mut canonical = Vec::new();
+        for (block_number, block_hash, item) in confirmed {
+            if self.depth == 0
+                || canonical_hash(block_number).await?.as_ref() == Some(&block_hash)
+            {
+                canonical.push(item);
+            }
+        }
+        Ok(canonical)
+    }
+
+    /// Returns the number of items waiting to be confirmed
+    #[cfg(test)]
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_std::task;
+
+    #[test]
+    fn it_delays_the_events_until_confirmed() {
+        // a mocked stream of blocks, each one with its events
+        let blocks =
+            (100..=105).map(|block_number| (block_number, format!("#{}", block_number)));
+
+        let mut confirmations = Confirmations::new(2);
+        let confirmed: Vec<(u32, Vec<String>)> = blocks
+            .map(|(block_number, events)| {
+                let confirmed = confirmations.push(block_number, block_number, events);
+                (
+                    block_number,
+                    confirmed.into_iter().map(|(_, _, events)| events).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            confirmed,
+            vec![
+                (100, vec![]),
+                (101, vec![]),
+                (102, vec!["#100".to_string()]),
+                (103, vec!["#101".to_string()]),
+                (104, vec!["#102".to_string()]),
+                (105, vec!["#103".to_string()]),
+            ]
+        );
+        assert_eq!(confirmations.pending(), 2);
+
+        // a gap in the stream confirms every block pending at once
+        assert_eq!(
+            confirmations.push(110, 110, "#110".to_string()),
+            vec![
+                (104, 104, "#104".to_string()),
+                (105, 105, "#105".to_string())
+            ]
+        );
+
+        // by default the events are acted on immediately
+        let mut confirmations = Confirmations::new(0);
+        assert_eq!(
+            confirmations.push(100, 100, "#100"),
+            vec![(100, 100, "#100")]
+        );
+        assert_eq!(confirmations.pending(), 0);
+    }
+
+    #[test]
+    fn it_drops_the_events_of_a_reverted_fork() {
+        // two fork blocks at the same height, only "0xb" stays on the canonical chain
+        let mut confirmations = Confirmations::new(1);
+        assert!(confirmations.push(100, "0xa", "fork a").is_empty());
+        assert!(confirmations.push(100, "0xb", "fork b").is_empty());
+        let confirmed = confirmations.push(101, "0xc", "next");
+        assert_eq!(confirmed.len(), 2);
+
+        let canonical_hash = |block_number: u32| async move {
+            Ok::<_, ()>(match block_number {
+                100 => Some("0xb"),
+                101 => Some("0xc"),
+                _ => None,
+            })
+        };
+        let canonical =
+            task::block_on(confirmations.retain_canonical(confirmed, canonical_hash));
+        assert_eq!(canonical, Ok(vec!["fork b"]));
+    }
+}
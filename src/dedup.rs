@@ -0,0 +1,212 @@
+// The MIT License (MIT)
+// Copyright © 2021 Aukbit Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+use lazy_static::lazy_static;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+lazy_static! {
+    // Kept across subscription restarts so that flapping is also caught
+    pub static ref LAST_SENT: Mutex<LastSent> = Mutex::new(LastSent::default());
+    // Kept across subscription restarts so that a restart within the era does not
+    // fire the era hooks again
+    pub static ref FIRED_HOOKS: Mutex<FiredHooks> = Mutex::new(FiredHooks::default());
+}
+
+// Only the very first cycle counts, not the ones after a subscription restart
+pub static STARTUP_CYCLE: StartupCycle = StartupCycle::new();
+
+/// The first monitoring cycle after 'scouty' starts
+#[derive(Debug)]
+pub struct StartupCycle {
+    in_progress: AtomicBool,
+}
+
+impl StartupCycle {
+    pub const fn new() -> StartupCycle {
+        StartupCycle {
+            in_progress: AtomicBool::new(true),
+        }
+    }
+
+    /// Verifies if notifications are silenced, which only happens while the
+    /// startup cycle is in progress and startup notifications are suppressed
+    pub fn is_silenced(&self, suppress: bool) -> bool {
+        suppress && self.in_progress.load(Ordering::SeqCst)
+    }
+
+    pub fn end(&self) {
+        self.in_progress.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Last message sent to each target, with the instant it was sent
+#[derive(Debug, Default)]
+pub struct LastSent {
+    messages: HashMap<String, (String, Instant)>,
+}
+
+impl LastSent {
+    /// Verifies if the message is byte-identical to the previous one sent to the
+    /// same target within the window. If not, the message is recorded as sent.
+    /// A zero window disables deduplication.
+    pub fn is_duplicate(
+        &mut self,
+        target: &str,
+        message: &str,
+        window: Duration,
+        now: Instant,
+    ) -> bool {
+        if window.is_zero() {
+            return false;
+        }
+        if let Some((last_message, sent_at)) = self.messages.get(target) {
+            if last_message == message && now.saturating_duration_since(*sent_at) < window
+            {
+                return true;
+            }
+        }
+        self.record(target, message, now);
+        false
+    }
+
+    /// Records the message as sent to the target without sending it
+    pub fn record(&mut self, target: &str, message: &str, now: Instant) {
+        self.messages
+            .insert(target.to_string(), (message.to_string(), now));
+    }
+}
+
+/// Last era in which each hook fired for each stash
+#[derive(Debug, Default)]
+pub struct FiredHooks {
+    eras: HashMap<(String, String), u32>,
+}
+
+impl FiredHooks {
+    /// Verifies if the hook already fired for the stash in the era. If not, the
+    /// era is recorded so that any other invocation within the era is suppressed.
+    pub fn is_duplicate(&mut self, hook: &str, stash: &str, era: u32) -> bool {
+        let key = (hook.to_string(), stash.to_string());
+        if self.eras.get(&key) == Some(&era) {
+            return true;
+        }
+        self.eras.insert(key, era);
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hooks::{HOOK_NEW_ERA, HOOK_VALIDATOR_STARTS_ACTIVE_NEXT_ERA};
+
+    #[test]
+    fn it_fires_a_hook_once_per_era() {
+        let mut fired = FiredHooks::default();
+        let hook = HOOK_VALIDATOR_STARTS_ACTIVE_NEXT_ERA;
+        // two cycles within the same era
+        assert!(!fired.is_duplicate(hook, "alice", 1024));
+        assert!(fired.is_duplicate(hook, "alice", 1024));
+        // other stashes and hooks are recorded on their own
+        assert!(!fired.is_duplicate(hook, "bob", 1024));
+        assert!(!fired.is_duplicate(HOOK_NEW_ERA, "alice", 1024));
+        // a new era fires it again
+        assert!(!fired.is_duplicate(hook, "alice", 1025));
+        assert!(fired.is_duplicate(hook, "alice", 1025));
+    }
+
+    #[test]
+    fn it_suppresses_identical_consecutive_messages() {
+        let now = Instant::now();
+        let window = Duration::from_secs(60);
+        let mut last_sent = LastSent::default();
+        let messages = vec![
+            "Alice will be inactive next era",
+            "Alice will be inactive next era",
+            "Alice will be active next era",
+        ];
+        let sent: Vec<&str> = messages
+            .into_iter()
+            .filter(|message| !last_sent.is_duplicate("alice", message, window, now))
+            .collect();
+        assert_eq!(
+            sent,
+            vec![
+                "Alice will be inactive next era",
+                "Alice will be active next era"
+            ]
+        );
+    }
+
+    #[test]
+    fn it_does_not_dedup_different_targets() {
+        let now = Instant::now();
+        let window = Duration::from_secs(60);
+        let mut last_sent = LastSent::default();
+        assert!(!last_sent.is_duplicate("alice", "inactive next era", window, now));
+        assert!(!last_sent.is_duplicate("bob", "inactive next era", window, now));
+        assert!(last_sent.is_duplicate("bob", "inactive next era", window, now));
+        // once the window elapsed the message is sent again
+        let later = now + window;
+        assert!(!last_sent.is_duplicate("bob", "inactive next era", window, later));
+        // deduplication is off with a zero window
+        assert!(!last_sent.is_duplicate(
+            "bob",
+            "inactive next era",
+            Duration::ZERO,
+            later
+        ));
+    }
+
+    #[test]
+    fn it_records_the_startup_cycle_without_sending() {
+        let now = Instant::now();
+        let window = Duration::from_secs(60);
+        let mut last_sent = LastSent::default();
+        let cycle = StartupCycle::new();
+        assert!(!cycle.is_silenced(false));
+        assert!(cycle.is_silenced(true));
+
+        // The first cycle sends nothing but records the state observed
+        let mut sent: Vec<&str> = Vec::new();
+        for message in vec!["Alice is active", "Bob is inactive"] {
+            let target = &message[..message.find(' ').unwrap()];
+            if cycle.is_silenced(true) {
+                last_sent.record(target, message, now);
+            } else if !last_sent.is_duplicate(target, message, window, now) {
+                sent.push(message);
+            }
+        }
+        assert!(sent.is_empty());
+
+        // Only the changes are sent on the subsequent cycles
+        cycle.end();
+        assert!(!cycle.is_silenced(true));
+        assert!(last_sent.is_duplicate("Alice", "Alice is active", window, now));
+        assert!(!last_sent.is_duplicate("Bob", "Bob is active", window, now));
+    }
+}
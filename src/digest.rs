@@ -0,0 +1,197 @@
+// The MIT License (MIT)
+// Copyright © 2021 Aukbit Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+use chrono::{DateTime, NaiveDate, Timelike};
+use chrono_tz::Tz;
+use lazy_static::lazy_static;
+use std::{collections::BTreeMap, sync::Mutex};
+
+lazy_static! {
+    // Accumulated by the monitoring loop, taken once the daily report is sent
+    pub static ref DAILY_REPORT: Mutex<DailyReport> = Mutex::new(DailyReport::default());
+}
+
+/// Activity of a stash since the last daily report
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StashSummary {
+    pub stash: String,
+    pub name: String,
+    pub sessions: u32,
+    pub active_sessions: u32,
+    pub era_points: u32,
+    // number of incidents by event
+    pub incidents: BTreeMap<String, u32>,
+}
+
+/// Activity of every stash monitored since the last daily report, ordered by stash
+#[derive(Debug, Default)]
+pub struct DailyReport {
+    stashes: BTreeMap<String, StashSummary>,
+}
+
+impl DailyReport {
+    fn summary(&mut self, stash: &str, name: &str) -> &mut StashSummary {
+        let summary =
+            self.stashes
+                .entry(stash.to_string())
+                .or_insert_with(|| StashSummary {
+                    stash: stash.to_string(),
+                    ..Default::default()
+                });
+        if !name.is_empty() {
+            summary.name = name.to_string();
+        }
+        summary
+    }
+
+    pub fn record_session(&mut self, stash: &str, name: &str, is_active: bool) {
+        let summary = self.summary(stash, name);
+        summary.sessions += 1;
+        if is_active {
+            summary.active_sessions += 1;
+        }
+    }
+
+    /// Adds the points the stash earned in the era that just ended
+    pub fn record_era_points(&mut self, stash: &str, name: &str, points: u32) {
+        self.summary(stash, name).era_points += points;
+    }
+
+    pub fn record_incident(&mut self, stash: &str, name: &str, event: &str) {
+        *self
+            .summary(stash, name)
+            .incidents
+            .entry(event.to_string())
+            .or_insert(0) += 1;
+    }
+
+    /// Returns the summaries accumulated so far and starts over
+    pub fn take(&mut self) -> Vec<StashSummary> {
+        std::mem::take(&mut self.stashes).into_values().collect()
+    }
+}
+
+/// Schedules the daily report at the hour given, in the configured timezone. A
+/// report is not sent on start if its hour already passed today, since it would
+/// only cover part of the day.
+#[derive(Debug)]
+pub struct DailySchedule {
+    hour: Option<u32>,
+    last_sent: Option<NaiveDate>,
+}
+
+impl DailySchedule {
+    /// A `None` hour disables the daily report
+    pub fn new(hour: Option<u32>, now: DateTime<Tz>) -> DailySchedule {
+        let today = now.naive_local().date();
+        let last_sent = match hour {
+            Some(hour) if now.hour() >= hour => Some(today),
+            _ => None,
+        };
+        DailySchedule { hour, last_sent }
+    }
+
+    /// Verifies if the daily report is due, if so the next one is scheduled for
+    /// the following day
+    pub fn is_due(&mut self, now: DateTime<Tz>) -> bool {
+        let hour = match self.hour {
+            Some(hour) => hour,
+            None => return false,
+        };
+        let today = now.naive_local().date();
+        if now.hour() >= hour && self.last_sent != Some(today) {
+            self.last_sent = Some(today);
+            return true;
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn it_fires_at_the_configured_hour() {
+        let tz = Tz::Europe__Lisbon;
+        let start = tz.ymd(2022, 6, 1).and_hms(7, 30, 0);
+        let mut schedule = DailySchedule::new(Some(9), start);
+        assert!(!schedule.is_due(start));
+        assert!(!schedule.is_due(tz.ymd(2022, 6, 1).and_hms(8, 59, 59)));
+        assert!(schedule.is_due(tz.ymd(2022, 6, 1).and_hms(9, 0, 0)));
+        // only one report is sent per day
+        assert!(!schedule.is_due(tz.ymd(2022, 6, 1).and_hms(10, 0, 0)));
+        assert!(!schedule.is_due(tz.ymd(2022, 6, 2).and_hms(8, 0, 0)));
+        // a report missed at the hour is sent as soon as possible
+        assert!(schedule.is_due(tz.ymd(2022, 6, 2).and_hms(11, 0, 0)));
+
+        // the hour is in the local time of the timezone, 9:00 in Lisbon is 8:00 UTC
+        let utc = Tz::UTC;
+        let start = utc.ymd(2022, 6, 1).and_hms(7, 0, 0);
+        let mut schedule = DailySchedule::new(Some(9), start.with_timezone(&tz));
+        assert!(schedule.is_due(utc.ymd(2022, 6, 1).and_hms(8, 0, 0).with_timezone(&tz)));
+
+        // a report is not sent on start once its hour passed
+        let start = tz.ymd(2022, 6, 1).and_hms(12, 0, 0);
+        let mut schedule = DailySchedule::new(Some(9), start);
+        assert!(!schedule.is_due(start));
+        assert!(schedule.is_due(tz.ymd(2022, 6, 2).and_hms(9, 0, 0)));
+
+        let mut schedule = DailySchedule::new(None, start);
+        assert!(!schedule.is_due(tz.ymd(2022, 6, 2).and_hms(9, 0, 0)));
+    }
+
+    #[test]
+    fn it_accumulates_the_stash_activity() {
+        let mut report = DailyReport::default();
+        for is_active in [true, true, false] {
+            report.record_session("stash_a", "Alice", is_active);
+        }
+        report.record_session("stash_b", "", false);
+        report.record_era_points("stash_a", "Alice", 1200);
+        report.record_era_points("stash_a", "Alice", 800);
+        report.record_incident("stash_b", "Bob", "validator_chilled");
+        report.record_incident("stash_b", "Bob", "validator_chilled");
+        report.record_incident("stash_b", "Bob", "validator_slashed");
+
+        let summaries = report.take();
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(
+            summaries[0],
+            StashSummary {
+                stash: "stash_a".to_string(),
+                name: "Alice".to_string(),
+                sessions: 3,
+                active_sessions: 2,
+                era_points: 2000,
+                incidents: BTreeMap::new(),
+            }
+        );
+        assert_eq!(summaries[1].name, "Bob");
+        assert_eq!(summaries[1].sessions, 1);
+        assert_eq!(summaries[1].active_sessions, 0);
+        assert_eq!(summaries[1].incidents.get("validator_chilled"), Some(&2));
+        assert_eq!(summaries[1].incidents.get("validator_slashed"), Some(&1));
+
+        // the next report starts over
+        assert!(report.take().is_empty());
+    }
+}
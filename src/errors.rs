@@ -22,13 +22,14 @@
 use codec;
 use reqwest;
 use std::{str, string, string::String};
+use subxt::BasicError;
 use thiserror::Error;
 
 /// Scouty specific error messages
 #[derive(Error, Debug)]
 pub enum ScoutyError {
     #[error("Subxt error: {0}")]
-    SubxtError(#[from] subxt::BasicError),
+    SubxtError(#[from] BasicError),
     #[error("Codec error: {0}")]
     CodecError(#[from] codec::Error),
     #[error("IO error: {0}")]
@@ -41,12 +42,98 @@ pub enum ScoutyError {
     MetadataError(#[from] subxt::MetadataError),
     #[error("Matrix error: {0}")]
     MatrixError(String),
+    #[error("Telegram error: {0}")]
+    TelegramError(String),
+    #[error("Webhook error: {0}")]
+    WebhookError(String),
+    #[error("PagerDuty error: {0}")]
+    PagerDutyError(String),
+    #[error("SMTP error: {0}")]
+    SmtpError(String),
     #[error("Subscription finished")]
     SubscriptionFinished,
+    #[error("Timeout error: {0}")]
+    TimeoutError(String),
+    #[error("Config error: {0}")]
+    ConfigError(#[from] ConfigError),
+    #[error("Hook error: {0}")]
+    HookError(String),
+    #[error("Fatal hook error: {0}")]
+    FatalHookError(String),
     #[error("Other error: {0}")]
     Other(String),
 }
 
+/// Whether restarting 'scouty' may recover from an error
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ErrorClass {
+    /// e.g. a network drop, 'scouty' restarts after the error interval
+    Transient,
+    /// e.g. an invalid configuration, restarting would fail the same way
+    Fatal,
+}
+
+impl ScoutyError {
+    /// Returns the class of the error. Problems with the configuration or with
+    /// the runtime metadata, or hooks failing with `hook_failure_fatal` set, are
+    /// fatal, any other error is transient.
+    pub fn class(&self) -> ErrorClass {
+        match self {
+            ScoutyError::ConfigError(_) | ScoutyError::FatalHookError(_) => {
+                ErrorClass::Fatal
+            }
+            _ if self.is_metadata_mismatch() => ErrorClass::Fatal,
+            _ => ErrorClass::Transient,
+        }
+    }
+
+    /// Whether the error comes from decoding chain data with metadata that no
+    /// longer matches the runtime, e.g. after a runtime upgrade
+    pub fn is_metadata_mismatch(&self) -> bool {
+        match self {
+            ScoutyError::MetadataError(_) | ScoutyError::CodecError(_) => true,
+            ScoutyError::SubxtError(e) => matches!(
+                e,
+                BasicError::Codec(_)
+                    | BasicError::Metadata(_)
+                    | BasicError::EventsDecoding(_)
+            ),
+            _ => false,
+        }
+    }
+
+    /// Returns a hint on how to recover from the error, if there is one
+    pub fn hint(&self) -> Option<&'static str> {
+        if self.is_metadata_mismatch() {
+            return Some("Runtime metadata changed — scouty may need an update");
+        }
+        None
+    }
+}
+
+/// Exit codes 'scouty' terminates with, so that automation can tell why it exited
+pub const EXIT_SUCCESS: i32 = 0;
+/// e.g. a notification backend failed the test or a server could not be started
+pub const EXIT_FAILURE: i32 = 1;
+pub const EXIT_CONFIG_ERROR: i32 = 2;
+pub const EXIT_CONNECTION_ERROR: i32 = 3;
+pub const EXIT_FATAL_CHAIN_ERROR: i32 = 4;
+
+impl ScoutyError {
+    /// Returns the code 'scouty' exits with when giving up on the error
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ScoutyError::ConfigError(_) => EXIT_CONFIG_ERROR,
+            _ if self.is_metadata_mismatch() => EXIT_FATAL_CHAIN_ERROR,
+            ScoutyError::SubxtError(_)
+            | ScoutyError::IoError(_)
+            | ScoutyError::TimeoutError(_)
+            | ScoutyError::SubscriptionFinished => EXIT_CONNECTION_ERROR,
+            _ => EXIT_FAILURE,
+        }
+    }
+}
+
 /// Convert &str to ScoutyError
 impl From<&str> for ScoutyError {
     fn from(error: &str) -> Self {
@@ -78,3 +165,165 @@ impl From<MatrixError> for ScoutyError {
         ScoutyError::MatrixError(error.into())
     }
 }
+
+/// Telegram specific error messages
+#[derive(Error, Debug)]
+pub enum TelegramError {
+    #[error("Reqwest error: {0}")]
+    ReqwestError(#[from] reqwest::Error),
+    #[error("{0}")]
+    Other(String),
+}
+
+/// Convert TelegramError to ScoutyError
+impl From<TelegramError> for ScoutyError {
+    fn from(error: TelegramError) -> Self {
+        ScoutyError::TelegramError(format!("{}", error))
+    }
+}
+
+/// Webhook specific error messages
+#[derive(Error, Debug)]
+pub enum WebhookError {
+    #[error("Reqwest error: {0}")]
+    ReqwestError(#[from] reqwest::Error),
+    #[error("{0}")]
+    Other(String),
+}
+
+/// Convert WebhookError to ScoutyError
+impl From<WebhookError> for ScoutyError {
+    fn from(error: WebhookError) -> Self {
+        ScoutyError::WebhookError(format!("{}", error))
+    }
+}
+
+/// PagerDuty specific error messages
+#[derive(Error, Debug)]
+pub enum PagerDutyError {
+    #[error("Reqwest error: {0}")]
+    ReqwestError(#[from] reqwest::Error),
+    #[error("{0}")]
+    Other(String),
+}
+
+/// Convert PagerDutyError to ScoutyError
+impl From<PagerDutyError> for ScoutyError {
+    fn from(error: PagerDutyError) -> Self {
+        ScoutyError::PagerDutyError(format!("{}", error))
+    }
+}
+
+/// Vault specific error messages
+#[derive(Error, Debug)]
+pub enum VaultError {
+    #[error("Reqwest error: {0}")]
+    ReqwestError(#[from] reqwest::Error),
+    #[error("{0}")]
+    Other(String),
+}
+
+/// SMTP specific error messages
+#[derive(Error, Debug)]
+pub enum SmtpError {
+    #[error("Address error: {0}")]
+    AddressError(#[from] lettre::address::AddressError),
+    #[error("Message error: {0}")]
+    MessageError(#[from] lettre::error::Error),
+    #[error("Transport error: {0}")]
+    TransportError(#[from] lettre::transport::smtp::Error),
+}
+
+/// Convert SmtpError to ScoutyError
+impl From<SmtpError> for ScoutyError {
+    fn from(error: SmtpError) -> Self {
+        ScoutyError::SmtpError(format!("{}", error))
+    }
+}
+
+/// Config specific error messages
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Invalid stash address(es): {0}")]
+    InvalidStashes(String),
+    #[error("TOML error: {0}")]
+    TomlError(#[from] toml::de::Error),
+    #[error("{0}")]
+    Other(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_classifies_the_errors() {
+        let err: ScoutyError =
+            ConfigError::InvalidStashes("'abc' (index 0)".into()).into();
+        assert_eq!(err.class(), ErrorClass::Fatal);
+        let err: ScoutyError = ConfigError::Other("invalid config".into()).into();
+        assert_eq!(err.class(), ErrorClass::Fatal);
+
+        let err: ScoutyError =
+            std::io::Error::new(std::io::ErrorKind::ConnectionReset, "reset").into();
+        assert_eq!(err.class(), ErrorClass::Transient);
+        assert_eq!(
+            ScoutyError::SubscriptionFinished.class(),
+            ErrorClass::Transient
+        );
+        assert_eq!(
+            ScoutyError::TimeoutError("Connection to ws://x".into()).class(),
+            ErrorClass::Transient
+        );
+        assert_eq!(
+            ScoutyError::MatrixError("M_LIMIT_EXCEEDED".into()).class(),
+            ErrorClass::Transient
+        );
+        assert_eq!(
+            ScoutyError::from("Active era not available").class(),
+            ErrorClass::Transient
+        );
+    }
+
+    #[test]
+    fn it_maps_the_errors_to_exit_codes() {
+        let err: ScoutyError = ConfigError::Other("invalid config".into()).into();
+        assert_eq!(err.exit_code(), EXIT_CONFIG_ERROR);
+        let err: ScoutyError =
+            std::io::Error::new(std::io::ErrorKind::ConnectionRefused, "refused").into();
+        assert_eq!(err.exit_code(), EXIT_CONNECTION_ERROR);
+        assert_eq!(
+            ScoutyError::TimeoutError("Connection to ws://x".into()).exit_code(),
+            EXIT_CONNECTION_ERROR
+        );
+        assert_eq!(
+            ScoutyError::SubscriptionFinished.exit_code(),
+            EXIT_CONNECTION_ERROR
+        );
+        let err: ScoutyError = codec::Error::from("invalid data").into();
+        assert_eq!(err.exit_code(), EXIT_FATAL_CHAIN_ERROR);
+        assert_eq!(
+            ScoutyError::from("Active era not available").exit_code(),
+            EXIT_FAILURE
+        );
+    }
+
+    #[test]
+    fn it_gives_a_hint_on_decode_errors() {
+        let err: ScoutyError =
+            BasicError::Codec(codec::Error::from("invalid data")).into();
+        assert!(err.is_metadata_mismatch());
+        assert_eq!(err.class(), ErrorClass::Fatal);
+        assert_eq!(err.exit_code(), EXIT_FATAL_CHAIN_ERROR);
+        assert_eq!(
+            err.hint(),
+            Some("Runtime metadata changed — scouty may need an update")
+        );
+
+        let err: ScoutyError = BasicError::Other("connection closed".into()).into();
+        assert!(!err.is_metadata_mismatch());
+        assert_eq!(err.class(), ErrorClass::Transient);
+        assert_eq!(err.exit_code(), EXIT_CONNECTION_ERROR);
+        assert_eq!(err.hint(), None);
+    }
+}
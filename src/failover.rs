@@ -0,0 +1,147 @@
+// The MIT License (MIT)
+// Copyright © 2021 Aukbit Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+use crate::config::Endpoint;
+use lazy_static::lazy_static;
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+lazy_static! {
+    // Replaced on every connection, including the ones after a restart
+    pub static ref CONNECTED: Mutex<Connected> = Mutex::new(Connected::default());
+}
+
+/// Priority of the substrate endpoint connected, with the instant a higher
+/// priority one was last checked
+#[derive(Debug, Default)]
+pub struct Connected {
+    priority: u32,
+    checked_at: Option<Instant>,
+}
+
+impl Connected {
+    /// Records the connection to an endpoint, the higher priority ones are only
+    /// checked once the promotion interval elapses
+    pub fn record(&mut self, priority: u32, now: Instant) {
+        self.priority = priority;
+        self.checked_at = Some(now);
+    }
+
+    /// Returns the endpoints with a higher priority than the one connected once
+    /// the promotion interval has elapsed since the connection or since the last
+    /// check, which is then recorded. A zero interval disables the promotion.
+    pub fn preferred_endpoints_due(
+        &mut self,
+        endpoints: &[Endpoint],
+        interval: Duration,
+        now: Instant,
+    ) -> Vec<Endpoint> {
+        if interval.as_secs() == 0 {
+            return Vec::new();
+        }
+        match self.checked_at {
+            Some(checked_at) if now.duration_since(checked_at) >= interval => (),
+            _ => return Vec::new(),
+        }
+        let preferred: Vec<Endpoint> = endpoints
+            .iter()
+            .filter(|endpoint| endpoint.priority > self.priority)
+            .cloned()
+            .collect();
+        if !preferred.is_empty() {
+            self.checked_at = Some(now);
+        }
+        preferred
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoints() -> Vec<Endpoint> {
+        vec![
+            "wss://mynode:9944|10".parse::<Endpoint>().unwrap(),
+            "wss://public:443|1".parse::<Endpoint>().unwrap(),
+        ]
+    }
+
+    #[test]
+    fn it_checks_the_preferred_endpoints_periodically() {
+        let interval = Duration::from_secs(600);
+        let now = Instant::now();
+        let mut connected = Connected::default();
+        // fallen back to the public endpoint
+        connected.record(1, now);
+        assert!(connected
+            .preferred_endpoints_due(
+                &endpoints(),
+                interval,
+                now + Duration::from_secs(599)
+            )
+            .is_empty());
+        let preferred = connected.preferred_endpoints_due(
+            &endpoints(),
+            interval,
+            now + Duration::from_secs(600),
+        );
+        assert_eq!(preferred, vec![endpoints()[0].clone()]);
+        // the next check only happens after another interval
+        assert!(connected
+            .preferred_endpoints_due(
+                &endpoints(),
+                interval,
+                now + Duration::from_secs(900)
+            )
+            .is_empty());
+        assert_eq!(
+            connected
+                .preferred_endpoints_due(
+                    &endpoints(),
+                    interval,
+                    now + Duration::from_secs(1200)
+                )
+                .len(),
+            1
+        );
+
+        // nothing to promote to once connected to the highest priority endpoint
+        connected.record(10, now);
+        assert!(connected
+            .preferred_endpoints_due(
+                &endpoints(),
+                interval,
+                now + Duration::from_secs(3600)
+            )
+            .is_empty());
+
+        // a zero interval disables the promotion
+        connected.record(1, now);
+        assert!(connected
+            .preferred_endpoints_due(
+                &endpoints(),
+                Duration::from_secs(0),
+                now + Duration::from_secs(3600)
+            )
+            .is_empty());
+    }
+}
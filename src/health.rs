@@ -0,0 +1,85 @@
+// The MIT License (MIT)
+// Copyright © 2021 Aukbit Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+use crate::config::CONFIG;
+use crate::errors::ScoutyError;
+use crate::http::{self, Response};
+use std::{
+    result::Result,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+// Unix timestamp in seconds of the last successful RPC interaction, 0 if none
+static LAST_RPC_SUCCESS: AtomicU64 = AtomicU64::new(0);
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Records a successful RPC interaction with the substrate node
+pub fn touch() {
+    LAST_RPC_SUCCESS.store(now(), Ordering::Relaxed);
+}
+
+/// Returns the unix timestamp in seconds of the last successful RPC interaction,
+/// 0 if none
+pub fn last_rpc_success() -> u64 {
+    LAST_RPC_SUCCESS.load(Ordering::Relaxed)
+}
+
+/// Healthy if the last successful RPC interaction is not older than max age
+fn is_healthy(last_rpc_success: u64, now: u64, max_age: Duration) -> bool {
+    last_rpc_success > 0 && now.saturating_sub(last_rpc_success) <= max_age.as_secs()
+}
+
+/// Serve the liveness probe at /health, responding 200 if the last successful RPC
+/// interaction happened within twice the interval, or 503 otherwise
+pub fn serve(listen_addr: &str) -> Result<(), ScoutyError> {
+    http::serve(listen_addr, |path| match path {
+        "/health" => {
+            let max_age = CONFIG.load().interval * 2;
+            if is_healthy(last_rpc_success(), now(), max_age) {
+                Response::ok("text/plain; charset=utf-8", "OK\n".to_string())
+            } else {
+                Response::service_unavailable()
+            }
+        }
+        _ => Response::not_found(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_decides_health_from_the_last_rpc_success() {
+        let max_age = Duration::from_secs(60);
+        let now = 1_700_000_000;
+        assert!(is_healthy(now, now, max_age));
+        assert!(is_healthy(now - 60, now, max_age));
+        assert!(!is_healthy(now - 61, now, max_age));
+        // no RPC interaction succeeded yet
+        assert!(!is_healthy(0, now, max_age));
+    }
+}
@@ -0,0 +1,71 @@
+// The MIT License (MIT)
+// Copyright © 2021 Aukbit Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+use std::time::{Duration, Instant};
+
+/// Schedules the heartbeat notification, the first one is sent once the interval
+/// elapses after the start. A zero interval disables the heartbeat.
+#[derive(Debug)]
+pub struct Heartbeat {
+    interval: Duration,
+    last_sent: Instant,
+}
+
+impl Heartbeat {
+    pub fn new(interval: Duration, now: Instant) -> Heartbeat {
+        Heartbeat {
+            interval,
+            last_sent: now,
+        }
+    }
+
+    /// Verifies if the heartbeat is due, if so the next one is scheduled
+    pub fn is_due(&mut self, now: Instant) -> bool {
+        if self.interval.is_zero() {
+            return false;
+        }
+        if now.saturating_duration_since(self.last_sent) >= self.interval {
+            self.last_sent = now;
+            return true;
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_fires_at_the_configured_interval() {
+        let start = Instant::now();
+        let hour = Duration::from_secs(3600);
+        let mut heartbeat = Heartbeat::new(hour * 2, start);
+        assert!(!heartbeat.is_due(start));
+        assert!(!heartbeat.is_due(start + hour));
+        assert!(heartbeat.is_due(start + hour * 2));
+        // the next heartbeat is scheduled from the last one sent
+        assert!(!heartbeat.is_due(start + hour * 3));
+        assert!(heartbeat.is_due(start + hour * 4));
+
+        let mut heartbeat = Heartbeat::new(Duration::ZERO, start);
+        assert!(!heartbeat.is_due(start + hour * 24));
+    }
+}
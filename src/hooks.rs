@@ -19,12 +19,21 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+use crate::config::{format_duration, CONFIG};
 use crate::errors::ScoutyError;
-use log::{info, warn};
+use crate::metrics::{METRICS, RUNTIME_STATS};
+use crate::rewards::format_eras;
+use log::{debug, info, warn};
 use serde::Deserialize;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Read};
+use std::os::unix::process::CommandExt;
 use std::path::Path;
-use std::{process::Command, process::Stdio, result::Result};
+use std::{
+    process::{Child, Command, ExitStatus, Output, Stdio},
+    result::Result,
+    thread,
+    time::{Duration, Instant},
+};
 
 pub const HOOK_INIT: &'static str = "Scouty initialized";
 pub const HOOK_NEW_SESSION: &'static str = "New session";
@@ -37,6 +46,13 @@ pub const HOOK_VALIDATOR_SLASHED: &'static str = "Validator has been slashed";
 pub const HOOK_VALIDATOR_CHILLED: &'static str = "Validator has been chilled";
 pub const HOOK_VALIDATOR_OFFLINE: &'static str = "Validator has been offline";
 pub const HOOK_DEMOCRACY_STARTED: &'static str = "Democracy started";
+pub const HOOK_VALIDATOR_COMMISSION_CHANGED: &'static str =
+    "Validator commission changed";
+pub const HOOK_STASH_SLASHED: &'static str = "Stash has been slashed";
+pub const HOOK_UNCLAIMED_REWARDS: &'static str = "Unclaimed rewards found";
+
+// Exec format error, raised by the OS for a script without a shebang line
+const ENOEXEC: i32 = 8;
 
 #[derive(Debug, Deserialize, Default)]
 pub struct Hook {
@@ -48,6 +64,136 @@ pub struct Hook {
     pub filename_exists: bool,
     #[serde(default)]
     pub stdout: Vec<u8>,
+    #[serde(default)]
+    pub stderr: Vec<u8>,
+    #[serde(default)]
+    pub timed_out: bool,
+    // run in the background, the output is not captured in the hook
+    #[serde(default)]
+    pub detached: bool,
+}
+
+/// Read every line of the child stream in a separate thread, so that the child
+/// can be killed if it does not finish in time. Each line is logged at debug level
+/// prefixed with the hook name, note that debug level is only enabled if
+/// `log_level` is debug, or `is_debug` is set.
+fn read_lines<R>(
+    name: &str,
+    stream: &'static str,
+    reader: R,
+) -> thread::JoinHandle<Vec<u8>>
+where
+    R: Read + Send + 'static,
+{
+    let name = name.to_string();
+    thread::spawn(move || {
+        let mut formatted: Vec<u8> = Vec::new();
+        BufReader::new(reader)
+            .lines()
+            .filter_map(|line| line.ok())
+            .for_each(|line| {
+                debug!("{} {} $ {}", name, stream, line);
+                formatted.extend(format!("{}\n", line).as_bytes().to_vec());
+            });
+        formatted
+    })
+}
+
+/// Returns the hook timeout defined in config, zero means no timeout
+fn hook_timeout() -> Duration {
+    Duration::from_secs(CONFIG.load().hook_timeout_seconds)
+}
+
+/// Wait for the child to exit. Returns None if the timeout elapsed first, unless
+/// the timeout is zero in which case it waits indefinitely.
+fn wait_with_timeout(
+    child: &mut Child,
+    timeout: Duration,
+) -> Result<Option<ExitStatus>, ScoutyError> {
+    if timeout.as_secs() == 0 {
+        return Ok(Some(child.wait()?));
+    }
+    let start = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if start.elapsed() >= timeout {
+            return Ok(None);
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+}
+
+/// Kill the child and every process it spawned. The child leads its own process
+/// group, so a negative pid sends the signal to the whole group.
+fn kill_process_group(child: &Child) -> std::io::Result<()> {
+    if unsafe { libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL) } == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+/// A hook exiting non-zero is fatal if configured, so that 'scouty' exits instead
+/// of restarting the subscription
+fn with_failure_fatal(
+    result: Result<Hook, ScoutyError>,
+    is_fatal: bool,
+) -> Result<Hook, ScoutyError> {
+    match result {
+        Err(ScoutyError::HookError(e)) if is_fatal => Err(ScoutyError::FatalHookError(e)),
+        result => result,
+    }
+}
+
+/// Event data exported to the hook script as SCOUTY_* env vars
+#[derive(Debug, Clone, Default)]
+pub struct HookContext {
+    pub stash: String,
+    pub stash_name: String,
+    pub era: u32,
+    pub session: u32,
+    pub event: String,
+    pub is_active: bool,
+    // commissions in parts per billion, only exported if defined
+    pub old_commission: Option<u32>,
+    pub new_commission: Option<u32>,
+    // slashed amount in plancks, only exported if defined
+    pub slash_amount: Option<u128>,
+    // voluntary, forced or disabled, only exported if defined
+    pub chill_kind: Option<String>,
+    // eras with rewards left to claim, only exported if not empty
+    pub unclaimed_eras: Vec<u32>,
+}
+
+impl HookContext {
+    pub fn envs(&self) -> Vec<(&'static str, String)> {
+        let mut envs = vec![
+            ("SCOUTY_STASH", self.stash.to_string()),
+            ("SCOUTY_STASH_NAME", self.stash_name.to_string()),
+            ("SCOUTY_ERA", self.era.to_string()),
+            ("SCOUTY_SESSION", self.session.to_string()),
+            ("SCOUTY_EVENT", self.event.to_string()),
+            ("SCOUTY_IS_ACTIVE", self.is_active.to_string()),
+        ];
+        if let Some(old_commission) = self.old_commission {
+            envs.push(("SCOUTY_OLD_COMMISSION", old_commission.to_string()));
+        }
+        if let Some(new_commission) = self.new_commission {
+            envs.push(("SCOUTY_NEW_COMMISSION", new_commission.to_string()));
+        }
+        if let Some(slash_amount) = self.slash_amount {
+            envs.push(("SCOUTY_SLASH_AMOUNT", slash_amount.to_string()));
+        }
+        if let Some(chill_kind) = &self.chill_kind {
+            envs.push(("SCOUTY_CHILL_KIND", chill_kind.to_string()));
+        }
+        if !self.unclaimed_eras.is_empty() {
+            envs.push(("SCOUTY_UNCLAIMED_ERAS", format_eras(&self.unclaimed_eras)));
+        }
+        envs
+    }
 }
 
 impl Hook {
@@ -56,54 +202,134 @@ impl Hook {
         filename: &str,
         args: Vec<String>,
     ) -> Result<Hook, ScoutyError> {
-        if Path::new(filename).exists() {
-            info!("Run: {} {}", filename, args.join(" "));
-
-            let mut stdout_formatted: Vec<u8> = Vec::new();
+        Hook::run(name, filename, args, vec![])
+    }
 
-            let mut child = Command::new(filename)
-                .args(args)
-                .stderr(Stdio::piped())
-                .stdout(Stdio::piped())
-                .spawn()?;
+    /// Run the hook script with the event data from the context also set as env vars
+    pub fn try_run_with_context(
+        name: &str,
+        filename: &str,
+        args: Vec<String>,
+        context: &HookContext,
+    ) -> Result<Hook, ScoutyError> {
+        Hook::run(name, filename, args, context.envs())
+    }
 
-            if let Some(child_stdout) = child.stdout.take() {
-                let reader = BufReader::new(child_stdout);
+    /// Run the hook script inline, or in the background if hooks are async. The
+    /// extra args defined in config are appended to the event args.
+    fn run(
+        name: &str,
+        filename: &str,
+        mut args: Vec<String>,
+        envs: Vec<(&'static str, String)>,
+    ) -> Result<Hook, ScoutyError> {
+        let config = CONFIG.load();
+        args.extend(config.hook_extra_args());
+        if config.hooks_async {
+            let (hook, _) =
+                Hook::spawn_detached(name, filename, args, envs, hook_timeout());
+            Ok(hook)
+        } else {
+            with_failure_fatal(
+                Hook::spawn(name, filename, args, envs, hook_timeout()),
+                config.is_hook_failure_fatal(name),
+            )
+        }
+    }
 
-                reader
-                    .lines()
-                    .filter_map(|line| line.ok())
-                    .for_each(|line| {
-                        info!("$ {}", line);
-                        stdout_formatted
-                            .extend(format!("{}\n", line).as_bytes().to_vec());
-                    });
+    /// Run the hook script in a separate thread without waiting for it to finish,
+    /// its completion or failure is only logged once it exits
+    fn spawn_detached(
+        name: &str,
+        filename: &str,
+        args: Vec<String>,
+        envs: Vec<(&'static str, String)>,
+        timeout: Duration,
+    ) -> (Hook, Option<thread::JoinHandle<()>>) {
+        if !Path::new(filename).exists() {
+            warn!(
+                "Hook script - {} - filename ({}) not defined",
+                name, filename
+            );
+            let hook = Hook {
+                name: name.to_string(),
+                filename: filename.to_string(),
+                filename_exists: false,
+                ..Default::default()
+            };
+            return (hook, None);
+        }
+        let hook = Hook {
+            name: name.to_string(),
+            filename: filename.to_string(),
+            filename_exists: true,
+            detached: true,
+            ..Default::default()
+        };
+        let (name, filename) = (name.to_string(), filename.to_string());
+        let handle = thread::spawn(move || {
+            match Hook::spawn(&name, &filename, args, envs, timeout) {
+                Ok(hook) if hook.timed_out => (),
+                Ok(_) => {
+                    info!("Hook script - {} - filename ({}) completed", name, filename)
+                }
+                Err(e) => warn!("{}", e),
+            }
+        });
+        (hook, Some(handle))
+    }
 
-                let output = child.wait_with_output()?;
+    fn spawn(
+        name: &str,
+        filename: &str,
+        args: Vec<String>,
+        envs: Vec<(&'static str, String)>,
+        timeout: Duration,
+    ) -> Result<Hook, ScoutyError> {
+        if Path::new(filename).exists() {
+            info!("Run: {} {}", filename, args.join(" "));
 
-                if output.status.success() {
+            METRICS.inc_hook_executions(name);
+            RUNTIME_STATS.inc_hooks();
+            match Hook::capture(name, filename, args, envs, timeout)? {
+                Some(output) => {
+                    if output.status.success() {
+                        Ok(Hook {
+                            name: name.to_string(),
+                            filename: filename.to_string(),
+                            filename_exists: true,
+                            stdout: output.stdout,
+                            stderr: output.stderr,
+                            ..Default::default()
+                        })
+                    } else {
+                        METRICS.inc_hook_failures();
+                        let err = String::from_utf8(output.stderr)?;
+                        for line in err.lines() {
+                            warn!("{} stderr $ {}", name, line);
+                        }
+                        Err(ScoutyError::HookError(format!(
+                            "Hook script - {} - filename ({}) executed with error: {:?}",
+                            name, filename, err
+                        )))
+                    }
+                }
+                None => {
+                    METRICS.inc_hook_failures();
+                    warn!(
+                        "Hook script - {} - filename ({}) killed after a timeout of {}",
+                        name,
+                        filename,
+                        format_duration(timeout)
+                    );
                     Ok(Hook {
                         name: name.to_string(),
                         filename: filename.to_string(),
                         filename_exists: true,
-                        stdout: stdout_formatted,
+                        timed_out: true,
+                        ..Default::default()
                     })
-                } else {
-                    let err = String::from_utf8(output.stderr)?;
-                    Err(ScoutyError::Other(format!(
-                        "Hook script - {} - filename ({}) executed with error: {:?}",
-                        name, filename, err
-                    )))
                 }
-            } else {
-                warn!(
-                    "Hook script - {} - filename ({}) child stdout could not be captured",
-                    name, filename
-                );
-                Err(ScoutyError::Other(format!(
-                    "Hook script - {} - filename ({}) child stdout could not be captured",
-                    name, filename
-                )))
             }
         } else {
             warn!(
@@ -114,11 +340,89 @@ impl Hook {
                 name: name.to_string(),
                 filename: filename.to_string(),
                 filename_exists: false,
-                stdout: vec![],
+                ..Default::default()
             })
         }
     }
 
+    /// Run the script capturing both stdout and stderr. Returns None if the child
+    /// was killed after the timeout.
+    fn capture(
+        name: &str,
+        filename: &str,
+        args: Vec<String>,
+        envs: Vec<(&'static str, String)>,
+        timeout: Duration,
+    ) -> Result<Option<Output>, ScoutyError> {
+        let mut child = Hook::spawn_child(filename, &args, &envs)?;
+
+        let stdout_reader = match child.stdout.take() {
+            Some(child_stdout) => read_lines(name, "stdout", child_stdout),
+            None => {
+                return Err(ScoutyError::Other(format!(
+                    "Hook script - {} - filename ({}) child stdout could not be captured",
+                    name, filename
+                )))
+            }
+        };
+        let stderr_reader = match child.stderr.take() {
+            Some(child_stderr) => read_lines(name, "stderr", child_stderr),
+            None => {
+                return Err(ScoutyError::Other(format!(
+                    "Hook script - {} - filename ({}) child stderr could not be captured",
+                    name, filename
+                )))
+            }
+        };
+
+        match wait_with_timeout(&mut child, timeout)? {
+            Some(status) => Ok(Some(Output {
+                status,
+                stdout: stdout_reader.join().unwrap_or_default(),
+                stderr: stderr_reader.join().unwrap_or_default(),
+            })),
+            None => {
+                // Killing only the child would leave its children running, holding
+                // the stdout and stderr pipes open
+                kill_process_group(&child)?;
+                child.wait()?;
+                stdout_reader.join().ok();
+                stderr_reader.join().ok();
+                Ok(None)
+            }
+        }
+    }
+
+    /// Run the executable directly, whatever the language or the binary format it
+    /// has. Only a script without a shebang line, which can not be executed
+    /// directly, falls back to the shell.
+    fn spawn_child(
+        filename: &str,
+        args: &[String],
+        envs: &[(&'static str, String)],
+    ) -> std::io::Result<Child> {
+        let spawn = |program: &str, script: Option<&str>| {
+            Command::new(program)
+                .args(script)
+                .args(args)
+                .envs(envs.iter().map(|(key, value)| (*key, value)))
+                .stderr(Stdio::piped())
+                .stdout(Stdio::piped())
+                .process_group(0)
+                .spawn()
+        };
+        match spawn(filename, None) {
+            Err(e) if e.raw_os_error() == Some(ENOEXEC) => {
+                debug!(
+                    "Hook script - filename ({}) has no shebang line, run by /bin/sh",
+                    filename
+                );
+                spawn("/bin/sh", Some(filename))
+            }
+            result => result,
+        }
+    }
+
     pub fn exists(name: &str, filename: &str) -> bool {
         if !Path::new(filename).exists() {
             warn!(
@@ -130,3 +434,229 @@ impl Hook {
         return true;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::{ErrorClass, EXIT_FAILURE};
+    use std::{fs, os::unix::fs::PermissionsExt};
+
+    #[test]
+    fn it_runs_a_hook_with_the_context_env_vars() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("_new_session.sh");
+        let output = dir.path().join("output.txt");
+        fs::write(
+            &script,
+            "#!/bin/sh\necho \"$SCOUTY_STASH|$SCOUTY_STASH_NAME|$SCOUTY_ERA|$SCOUTY_SESSION|$SCOUTY_EVENT|$SCOUTY_IS_ACTIVE\" > \"$1\"\n",
+        )
+        .unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let context = HookContext {
+            stash: "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY".to_string(),
+            stash_name: "MyValidator01".to_string(),
+            era: 4170,
+            session: 25021,
+            event: HOOK_NEW_SESSION.to_string(),
+            is_active: true,
+            ..Default::default()
+        };
+        let hook = Hook::spawn(
+            HOOK_NEW_SESSION,
+            script.to_str().unwrap(),
+            vec![output.to_str().unwrap().to_string()],
+            context.envs(),
+            Duration::from_secs(0),
+        )
+        .unwrap();
+        assert!(hook.filename_exists);
+        assert_eq!(
+            fs::read_to_string(&output).unwrap(),
+            "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY|MyValidator01|4170|25021|New session|true\n"
+        );
+    }
+
+    #[test]
+    fn it_runs_any_executable() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("output.txt");
+        // an executable without the .sh extension, run without a shell wrapping
+        let executable = dir.path().join("notifier");
+        fs::write(
+            &executable,
+            "#!/bin/sh\nprintf '%s|%s' \"$1\" \"$2\" > \"$3\"\n",
+        )
+        .unwrap();
+        fs::set_permissions(&executable, fs::Permissions::from_mode(0o755)).unwrap();
+        let hook = Hook::spawn(
+            HOOK_NEW_ERA,
+            executable.to_str().unwrap(),
+            vec![
+                "My Validator".to_string(),
+                "$HOME; exit 1".to_string(),
+                output.to_str().unwrap().to_string(),
+            ],
+            vec![],
+            Duration::from_secs(5),
+        )
+        .unwrap();
+        assert!(hook.filename_exists);
+        // the arguments are passed as given, never interpreted by a shell
+        assert_eq!(
+            fs::read_to_string(&output).unwrap(),
+            "My Validator|$HOME; exit 1"
+        );
+
+        // a script without a shebang line falls back to the shell
+        let script = dir.path().join("notifier-without-shebang");
+        fs::write(&script, "printf '%s' \"$1\" > \"$2\"\n").unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+        Hook::spawn(
+            HOOK_NEW_ERA,
+            script.to_str().unwrap(),
+            vec!["fallback".to_string(), output.to_str().unwrap().to_string()],
+            vec![],
+            Duration::from_secs(5),
+        )
+        .unwrap();
+        assert_eq!(fs::read_to_string(&output).unwrap(), "fallback");
+    }
+
+    #[test]
+    fn it_exits_on_a_failing_hook_if_fatal() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("_new_era.sh");
+        fs::write(&script, "#!/bin/sh\necho \"failed\" >&2\nexit 3\n").unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+        let spawn = || {
+            Hook::spawn(
+                HOOK_NEW_ERA,
+                script.to_str().unwrap(),
+                vec![],
+                vec![],
+                Duration::from_secs(5),
+            )
+        };
+
+        // by default the subscription restarts after the failure
+        let err = with_failure_fatal(spawn(), false).unwrap_err();
+        assert_eq!(err.class(), ErrorClass::Transient);
+
+        // otherwise 'scouty' exits with a non-zero code
+        let err = with_failure_fatal(spawn(), true).unwrap_err();
+        assert_eq!(err.class(), ErrorClass::Fatal);
+        assert_eq!(err.exit_code(), EXIT_FAILURE);
+        assert!(err.to_string().contains("failed"));
+
+        // a hook exiting zero is never an error
+        fs::write(&script, "#!/bin/sh\nexit 0\n").unwrap();
+        assert!(with_failure_fatal(spawn(), true).is_ok());
+    }
+
+    #[test]
+    fn it_does_not_wait_for_a_detached_hook() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("_new_era.sh");
+        let output = dir.path().join("output.txt");
+        fs::write(&script, "#!/bin/sh\nsleep 2\necho \"done\" > \"$1\"\n").unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let start = Instant::now();
+        let (hook, handle) = Hook::spawn_detached(
+            HOOK_NEW_ERA,
+            script.to_str().unwrap(),
+            vec![output.to_str().unwrap().to_string()],
+            vec![],
+            Duration::from_secs(10),
+        );
+        // The caller proceeds while the slow hook is still running
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(hook.detached);
+        assert!(hook.filename_exists);
+        assert!(!output.exists());
+
+        handle.unwrap().join().unwrap();
+        assert_eq!(fs::read_to_string(&output).unwrap(), "done\n");
+
+        let (hook, handle) = Hook::spawn_detached(
+            HOOK_NEW_ERA,
+            "/not/a/hook.sh",
+            vec![],
+            vec![],
+            Duration::from_secs(1),
+        );
+        assert!(!hook.filename_exists);
+        assert!(handle.is_none());
+    }
+
+    #[test]
+    fn it_kills_a_hook_after_the_timeout() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("_new_era.sh");
+        let pid_file = dir.path().join("sleep.pid");
+        fs::write(
+            &script,
+            format!(
+                "#!/bin/sh\nsleep 10 &\necho $! > {}\nwait\n",
+                pid_file.display()
+            ),
+        )
+        .unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let start = Instant::now();
+        let hook = Hook::spawn(
+            HOOK_NEW_ERA,
+            script.to_str().unwrap(),
+            vec![],
+            vec![],
+            Duration::from_secs(1),
+        )
+        .unwrap();
+        assert!(hook.timed_out);
+        assert!(hook.filename_exists);
+        assert!(start.elapsed() < Duration::from_secs(5));
+        // the sleep spawned by the hook is killed along with it, at most left as a
+        // zombie until it is reaped
+        let pid = fs::read_to_string(&pid_file).unwrap();
+        let stat = fs::read_to_string(format!("/proc/{}/stat", pid.trim()));
+        assert!(stat.map(|stat| stat.contains(") Z ")).unwrap_or(true));
+    }
+
+    #[test]
+    fn it_captures_the_hook_stdout_and_stderr() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("_validator_chilled.sh");
+        fs::write(
+            &script,
+            "#!/bin/sh\necho \"to stdout\"\necho \"to stderr\" >&2\nexit 3\n",
+        )
+        .unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let output = Hook::capture(
+            HOOK_VALIDATOR_CHILLED,
+            script.to_str().unwrap(),
+            vec![],
+            vec![],
+            Duration::from_secs(5),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(output.status.code(), Some(3));
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), "to stdout\n");
+        assert_eq!(String::from_utf8(output.stderr).unwrap(), "to stderr\n");
+
+        let err = Hook::spawn(
+            HOOK_VALIDATOR_CHILLED,
+            script.to_str().unwrap(),
+            vec![],
+            vec![],
+            Duration::from_secs(5),
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("to stderr"));
+    }
+}
@@ -0,0 +1,190 @@
+// The MIT License (MIT)
+// Copyright © 2021 Aukbit Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+use crate::errors::ScoutyError;
+use log::{debug, info, warn};
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    result::Result,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+/// Connections served at the same time, any further one is closed right away
+const MAX_CONNECTIONS: usize = 16;
+
+/// Time a client has to send its request and to read the response, so that a
+/// client that connects and sends nothing does not hold a connection forever
+const IO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Minimal HTTP response returned by a request handler
+#[derive(Debug, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+impl Response {
+    pub fn ok(content_type: &'static str, body: String) -> Response {
+        Response {
+            status: 200,
+            content_type,
+            body,
+        }
+    }
+
+    pub fn not_found() -> Response {
+        Response {
+            status: 404,
+            content_type: "text/plain; charset=utf-8",
+            body: "Not Found\n".to_string(),
+        }
+    }
+
+    pub fn service_unavailable() -> Response {
+        Response {
+            status: 503,
+            content_type: "text/plain; charset=utf-8",
+            body: "Service Unavailable\n".to_string(),
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            404 => "Not Found",
+            503 => "Service Unavailable",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+/// Bind the listen address and serve the GET requests in the background, each
+/// connection in its own thread up to `MAX_CONNECTIONS` at the same time. The
+/// handler receives the request path (e.g. /metrics) and returns the response.
+pub fn serve<F>(listen_addr: &str, handler: F) -> Result<(), ScoutyError>
+where
+    F: Fn(&str) -> Response + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind(listen_addr)?;
+    info!("Listening on http://{}", listen_addr);
+    serve_listener(listener, handler);
+    Ok(())
+}
+
+fn serve_listener<F>(listener: TcpListener, handler: F)
+where
+    F: Fn(&str) -> Response + Send + Sync + 'static,
+{
+    let handler = Arc::new(handler);
+    let active = Arc::new(AtomicUsize::new(0));
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    warn!("HTTP connection error: {}", e);
+                    continue;
+                }
+            };
+            if active.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+                active.fetch_sub(1, Ordering::SeqCst);
+                warn!(
+                    "HTTP connection closed, {} connections are already being served",
+                    MAX_CONNECTIONS
+                );
+                continue;
+            }
+            let handler = handler.clone();
+            let active = active.clone();
+            thread::spawn(move || {
+                if let Err(e) = handle_connection(stream, handler.as_ref()) {
+                    warn!("HTTP connection error: {}", e);
+                }
+                active.fetch_sub(1, Ordering::SeqCst);
+            });
+        }
+    });
+}
+
+fn handle_connection<F>(mut stream: TcpStream, handler: &F) -> Result<(), ScoutyError>
+where
+    F: Fn(&str) -> Response,
+{
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+    debug!("HTTP request {}", request_line.trim());
+
+    // e.g. GET /metrics HTTP/1.1
+    let mut parts = request_line.split_whitespace();
+    let response = match (parts.next(), parts.next()) {
+        (Some("GET"), Some(path)) => handler(path),
+        _ => Response::not_found(),
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        response.reason(),
+        response.content_type,
+        response.body.len(),
+        response.body
+    )?;
+    stream.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn it_serves_a_request_while_another_client_is_silent() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        serve_listener(listener, |path| match path {
+            "/ping" => Response::ok("text/plain; charset=utf-8", "pong\n".to_string()),
+            _ => Response::not_found(),
+        });
+
+        // a client that connects and never sends its request
+        let _silent = TcpStream::connect(addr).unwrap();
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(2)))
+            .unwrap();
+        stream.write_all(b"GET /ping HTTP/1.1\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.ends_with("\r\n\r\npong\n"));
+    }
+}
@@ -0,0 +1,206 @@
+// The MIT License (MIT)
+// Copyright © 2021 Aukbit Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+use crate::config::{Config, LogFormat};
+use chrono::Utc;
+use env_logger::{Builder, Target, WriteStyle};
+use log::{LevelFilter, Record};
+use std::{
+    env,
+    ffi::OsString,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// Initialize the logger with the verbosity and the format defined in the config
+pub fn init(config: &Config) {
+    let log_level = config.log_level();
+    if log_level >= LevelFilter::Debug {
+        env::set_var("RUST_LOG", format!("scouty={0},subxt={0}", log_level));
+    } else {
+        env::set_var("RUST_LOG", format!("scouty={}", log_level));
+    }
+    let mut builder = Builder::from_default_env();
+    let mut is_file = false;
+    if let Some(path) = &config.log_file_path {
+        match RotatingFile::open(
+            path,
+            config.log_file_max_size_mb * 1024 * 1024,
+            config.log_file_max_files,
+        ) {
+            Ok(file) => {
+                builder.target(Target::Pipe(Box::new(file)));
+                is_file = true;
+            }
+            // The logger is not ready yet, so the error goes straight to stderr
+            Err(e) => eprintln!("Log file {} skipped, logging to stderr! {}", path, e),
+        }
+    }
+    // Colors are only written to a terminal
+    if is_file || config.no_color || !atty::is(atty::Stream::Stderr) {
+        builder.write_style(WriteStyle::Never);
+    }
+    if config.log_format == LogFormat::Json {
+        builder.format(|buf, record| writeln!(buf, "{}", to_json(record)));
+    }
+    builder.try_init().unwrap_or_default();
+}
+
+/// Log file rotated once the next line would make it exceed the max size. The
+/// rotated files are named `<path>.1`, the most recent, up to `<path>.<max_files>`.
+/// A zero max size never rotates and zero max files truncates the log file.
+#[derive(Debug)]
+pub struct RotatingFile {
+    path: PathBuf,
+    max_size: u64,
+    max_files: usize,
+    file: File,
+    size: u64,
+}
+
+impl RotatingFile {
+    pub fn open(path: &str, max_size: u64, max_files: usize) -> io::Result<RotatingFile> {
+        let path = PathBuf::from(path);
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(RotatingFile {
+            path,
+            max_size,
+            max_files,
+            file,
+            size,
+        })
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        if self.max_files > 0 {
+            // Shift the rotated files, the oldest one is overwritten
+            for i in (1..self.max_files).rev() {
+                let from = rotated_path(&self.path, i);
+                if from.exists() {
+                    fs::rename(&from, rotated_path(&self.path, i + 1))?;
+                }
+            }
+            fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        }
+        self.file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.max_size > 0
+            && self.size > 0
+            && self.size + buf.len() as u64 > self.max_size
+        {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Returns the path of the rotated log file with the index given, e.g. `scouty.log.1`
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut rotated = OsString::from(path.as_os_str());
+    rotated.push(format!(".{}", index));
+    PathBuf::from(rotated)
+}
+
+/// Serialize the log record as a single JSON line
+fn to_json(record: &Record) -> serde_json::Value {
+    serde_json::json!({
+        "ts": Utc::now().to_rfc3339(),
+        "level": record.level().to_string(),
+        "target": record.target(),
+        "msg": record.args().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use log::Level;
+
+    #[test]
+    fn it_serializes_a_record_as_json() {
+        let json = to_json(
+            &Record::builder()
+                .args(format_args!("New session {}", 42))
+                .level(Level::Info)
+                .target("scouty::runtimes::polkadot")
+                .build(),
+        );
+        assert_eq!(json["level"], "INFO");
+        assert_eq!(json["target"], "scouty::runtimes::polkadot");
+        assert_eq!(json["msg"], "New session 42");
+        assert!(json["ts"].is_string());
+    }
+
+    #[test]
+    fn it_rotates_the_log_file_once_it_reaches_the_max_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("scouty.log");
+        let mut file = RotatingFile::open(path.to_str().unwrap(), 100, 2).unwrap();
+        // each line is 20 bytes written at once, so 5 lines fit in each file
+        for i in 0..16 {
+            file.write_all(format!("log line number {:03}\n", i).as_bytes())
+                .unwrap();
+        }
+        file.flush().unwrap();
+
+        let read = |index: Option<usize>| {
+            let path = match index {
+                Some(index) => rotated_path(&path, index),
+                None => path.clone(),
+            };
+            fs::read_to_string(path).unwrap()
+        };
+        assert_eq!(read(None), "log line number 015\n");
+        assert!(read(Some(1)).starts_with("log line number 010\n"));
+        assert_eq!(read(Some(1)).len(), 100);
+        assert!(read(Some(2)).starts_with("log line number 005\n"));
+        // the oldest lines are removed with the files beyond the max files
+        assert!(!rotated_path(&path, 3).exists());
+
+        // a zero max size never rotates
+        let path = dir.path().join("unbounded.log");
+        let mut file = RotatingFile::open(path.to_str().unwrap(), 0, 2).unwrap();
+        for i in 0..16 {
+            file.write_all(format!("log line number {:03}\n", i).as_bytes())
+                .unwrap();
+        }
+        assert_eq!(fs::metadata(&path).unwrap().len(), 320);
+        assert!(!rotated_path(&path, 1).exists());
+    }
+}
@@ -19,30 +19,53 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+mod api;
 mod authority;
+mod backoff;
+mod changes;
+mod commission;
+mod concurrency;
 mod config;
+mod config_example;
+mod confirmation;
+mod dedup;
+mod digest;
 mod errors;
+mod failover;
+mod health;
+mod heartbeat;
 mod hooks;
+mod http;
+mod logger;
 mod matrix;
+mod metrics;
+mod pagerduty;
 mod para;
+mod proxy;
+mod ratelimit;
 mod report;
+mod rewards;
 mod runtimes;
 mod scouty;
+mod shutdown;
+mod smtp;
+mod stake;
 mod stats;
+mod telegram;
+mod templates;
+mod tls;
+mod vault;
+mod webhook;
 
-use crate::config::CONFIG;
+use crate::config::{Command, ConfigFormat, CONFIG};
+use crate::errors::{EXIT_CONFIG_ERROR, EXIT_FAILURE, EXIT_SUCCESS};
 use crate::scouty::Scouty;
-use log::info;
-use std::env;
+use log::{error, info, warn};
+use std::process;
 
 fn main() {
-    let config = CONFIG.clone();
-    if config.is_debug {
-        env::set_var("RUST_LOG", "scouty=debug,subxt=debug");
-    } else {
-        env::set_var("RUST_LOG", "scouty=info");
-    }
-    env_logger::try_init().unwrap_or_default();
+    let config = CONFIG.load_full();
+    logger::init(&config);
 
     info!(
         "{} v{} * {}",
@@ -51,5 +74,137 @@ fn main() {
         env!("CARGO_PKG_DESCRIPTION")
     );
 
+    if let Some(format) = config.print_config {
+        match config.print(format) {
+            Ok(output) => println!("{}", output),
+            Err(e) => {
+                error!("Configuration error: {}", e);
+                process::exit(EXIT_CONFIG_ERROR);
+            }
+        }
+        process::exit(EXIT_SUCCESS);
+    }
+
+    // The example config is printed without any stash or endpoint defined
+    if config.command == Command::ConfigExample {
+        let format = config.config_example_format.unwrap_or(ConfigFormat::Env);
+        match config_example::generate(format) {
+            Ok(output) => println!("{}", output),
+            Err(e) => {
+                error!("Configuration error: {}", e);
+                process::exit(EXIT_CONFIG_ERROR);
+            }
+        }
+        process::exit(EXIT_SUCCESS);
+    }
+
+    // The configuration is checked without opening any network connection
+    if config.command == Command::Validate {
+        let problems = config.lint();
+        for problem in problems.iter() {
+            error!("{}", problem);
+        }
+        if problems.is_empty() {
+            info!("Configuration is valid");
+        }
+        process::exit(config::lint_exit_code(&problems));
+    }
+
+    // The address is decoded without any stash or endpoint defined
+    if config.command == Command::Decode {
+        let address = config.decode_address.as_deref().unwrap_or_default();
+        match config::decode_address(address, config.ss58_prefix()) {
+            Ok(output) => println!("{}", output),
+            Err(e) => {
+                error!("{}", e);
+                process::exit(EXIT_CONFIG_ERROR);
+            }
+        }
+        process::exit(EXIT_SUCCESS);
+    }
+
+    // Chain presets are listed without any stash or endpoint defined
+    if config.command == Command::ListChains {
+        println!("{}", config::list_chains());
+        process::exit(EXIT_SUCCESS);
+    }
+
+    if let Err(e) = config.validate() {
+        error!("Configuration error: {}", e);
+        process::exit(EXIT_CONFIG_ERROR);
+    }
+
+    if config.is_dry_run {
+        println!("{}", config.resolved_plan());
+        process::exit(EXIT_SUCCESS);
+    }
+
+    if config.command == Command::View {
+        if let Err(e) = Scouty::view() {
+            error!("{}", e);
+            if let Some(hint) = e.hint() {
+                error!("{}", hint);
+            }
+            process::exit(e.exit_code());
+        }
+        process::exit(EXIT_SUCCESS);
+    }
+
+    if config.command == Command::TestNotifications {
+        let results = Scouty::test_notifications();
+        if results.is_empty() {
+            warn!("No notification backend enabled");
+        }
+        for (backend, result) in results.iter() {
+            match result {
+                Ok(()) => info!("{} * test notification sent ✅", backend),
+                Err(e) => error!("{} * test notification failed ❌ {}", backend, e),
+            }
+        }
+        process::exit(scouty::test_notifications_exit_code(&results));
+    }
+
+    if config.run_once {
+        if let Err(e) = Scouty::run_once() {
+            error!("{}", e);
+            if let Some(hint) = e.hint() {
+                error!("{}", hint);
+            }
+            process::exit(e.exit_code());
+        }
+        process::exit(EXIT_SUCCESS);
+    }
+
+    if let Some(prometheus_listen_addr) = &config.prometheus_listen_addr {
+        if let Err(e) = metrics::serve(prometheus_listen_addr) {
+            error!("Prometheus metrics server error: {}", e);
+            process::exit(EXIT_FAILURE);
+        }
+    }
+
+    if let Some(healthcheck_listen_addr) = &config.healthcheck_listen_addr {
+        if let Err(e) = health::serve(healthcheck_listen_addr) {
+            error!("Healthcheck server error: {}", e);
+            process::exit(EXIT_FAILURE);
+        }
+    }
+
+    if let Some(api_listen_addr) = config.api_bind_addr() {
+        if let Err(e) = api::serve(&api_listen_addr) {
+            error!("API server error: {}", e);
+            process::exit(EXIT_FAILURE);
+        }
+    }
+
+    if let Err(e) = config::reload_on_sighup() {
+        error!("Signal handler error: {}", e);
+        process::exit(EXIT_FAILURE);
+    }
+
+    if let Err(e) = shutdown::register() {
+        error!("Signal handler error: {}", e);
+        process::exit(EXIT_FAILURE);
+    }
+
     Scouty::subscribe();
 }
@@ -19,9 +19,11 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 #![allow(dead_code)]
-use crate::config::CONFIG;
+use crate::config::{MatrixFormat, MatrixRoom, CONFIG};
 use crate::errors::MatrixError;
+use crate::report::strip_html;
 use crate::runtimes::support::SupportedRuntime;
+use crate::webhook::EVENT_TEST;
 use async_recursion::async_recursion;
 use base64::encode;
 use log::{debug, info, warn};
@@ -31,6 +33,14 @@ use url::form_urlencoded::byte_serialize;
 
 const MATRIX_URL: &str = "https://matrix.org/_matrix/client/r0";
 
+/// Returns the client API url of the homeserver defined, or the matrix.org one
+fn client_url(homeserver_url: Option<&str>) -> String {
+    match homeserver_url.map(|url| url.trim().trim_end_matches('/')) {
+        Some(url) if !url.is_empty() => format!("{}/_matrix/client/r0", url),
+        _ => MATRIX_URL.to_string(),
+    }
+}
+
 type AccessToken = String;
 type RoomID = String;
 type EventID = String;
@@ -64,7 +74,7 @@ fn define_private_room_alias_name(
 
 impl Room {
     fn new_private(chain: SupportedRuntime) -> Room {
-        let config = CONFIG.clone();
+        let config = CONFIG.load_full();
         let room_alias_name = define_private_room_alias_name(
             env!("CARGO_PKG_NAME"),
             &chain.to_string(),
@@ -114,8 +124,29 @@ struct CreateRoomRequest {
 struct SendRoomMessageRequest {
     msgtype: String,
     body: String,
-    format: String,
-    formatted_body: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    formatted_body: Option<String>,
+}
+
+impl SendRoomMessageRequest {
+    fn new(message: &str, formatted_message: &str, format: MatrixFormat) -> Self {
+        match format {
+            MatrixFormat::Html => SendRoomMessageRequest {
+                msgtype: "m.text".to_string(),
+                body: message.to_string(),
+                format: Some("org.matrix.custom.html".to_string()),
+                formatted_body: Some(formatted_message.to_string()),
+            },
+            MatrixFormat::Plain => SendRoomMessageRequest {
+                msgtype: "m.text".to_string(),
+                body: strip_html(message),
+                format: None,
+                formatted_body: None,
+            },
+        }
+    }
 }
 
 #[derive(Deserialize, Debug)]
@@ -137,10 +168,13 @@ struct ErrorResponse {
 #[derive(Clone, Debug)]
 pub struct Matrix {
     pub client: reqwest::Client,
+    url: String,
+    format: MatrixFormat,
     access_token: Option<String>,
     chain: SupportedRuntime,
     private_room_id: String,
     public_room_id: String,
+    rooms: Vec<MatrixRoom>,
     disabled: bool,
 }
 
@@ -148,10 +182,13 @@ impl Default for Matrix {
     fn default() -> Matrix {
         Matrix {
             client: reqwest::Client::new(),
+            url: MATRIX_URL.to_string(),
+            format: MatrixFormat::default(),
             access_token: None,
             chain: SupportedRuntime::Westend,
             private_room_id: String::from(""),
             public_room_id: String::from(""),
+            rooms: Vec::new(),
             disabled: false,
         }
     }
@@ -159,18 +196,25 @@ impl Default for Matrix {
 
 impl Matrix {
     pub fn new() -> Matrix {
-        let config = CONFIG.clone();
+        let config = CONFIG.load_full();
         Matrix {
+            url: client_url(config.matrix_homeserver_url.as_deref()),
+            format: config.matrix_format,
+            rooms: config.matrix_rooms.clone(),
             disabled: config.matrix_disabled,
             ..Default::default()
         }
     }
 
+    pub fn is_enabled(&self) -> bool {
+        !self.disabled
+    }
+
     pub async fn login(&mut self) -> Result<(), MatrixError> {
         if self.disabled {
             return Ok(());
         }
-        let config = CONFIG.clone();
+        let config = CONFIG.load_full();
         if let None = config.matrix_bot_user.find(":") {
             return Err(MatrixError::Other(format!("matrix bot user '{}' does specifed the matrix server e.g. '@your-own-scouty-bot-account:matrix.org'", config.matrix_bot_user)));
         }
@@ -178,11 +222,11 @@ impl Matrix {
         let req = LoginRequest {
             r#type: "m.login.password".to_string(),
             user: config.matrix_bot_user.to_string(),
-            password: config.matrix_bot_password.to_string(),
+            password: config.matrix_bot_password.expose().to_string(),
         };
 
         let res = client
-            .post(format!("{}/login", MATRIX_URL))
+            .post(format!("{}/login", self.url))
             .json(&req)
             .send()
             .await?;
@@ -214,10 +258,7 @@ impl Matrix {
             Some(access_token) => {
                 let client = self.client.clone();
                 let res = client
-                    .post(format!(
-                        "{}/logout?access_token={}",
-                        MATRIX_URL, access_token
-                    ))
+                    .post(format!("{}/logout?access_token={}", self.url, access_token))
                     .send()
                     .await?;
                 debug!("response {:?}", res);
@@ -244,18 +285,34 @@ impl Matrix {
         if self.disabled {
             return Ok(());
         }
-        let config = CONFIG.clone();
+        let config = CONFIG.load_full();
         // Set chain
         self.chain = chain;
         // Login
         self.login().await?;
-        // Get or create user private room
-        if let Some(private_room) = self.get_or_create_private_room().await? {
-            self.private_room_id = private_room.room_id;
-            info!(
-                "Messages will be sent to room {} (Private)",
-                private_room.room_alias
-            );
+        if self.rooms.is_empty() {
+            // Get or create user private room
+            if let Some(private_room) = self.get_or_create_private_room().await? {
+                self.private_room_id = private_room.room_id;
+                info!(
+                    "Messages will be sent to room {} (Private)",
+                    private_room.room_alias
+                );
+            }
+        } else {
+            // Join the rooms defined, the bot must have been invited to private ones
+            for room in self.rooms.iter() {
+                self.join_room(&room.room_id).await?;
+                if room.events.is_empty() {
+                    info!("Messages will be sent to room {}", room.room_id);
+                } else {
+                    info!(
+                        "Messages will be sent to room {} ({})",
+                        room.room_id,
+                        room.events.join(", ")
+                    );
+                }
+            }
         }
         // Change Scouty Bot display name
         if !config.matrix_bot_display_name_disabled {
@@ -267,7 +324,7 @@ impl Matrix {
     async fn change_bot_display_name(&self) -> Result<(), MatrixError> {
         match &self.access_token {
             Some(access_token) => {
-                let config = CONFIG.clone();
+                let config = CONFIG.load_full();
                 let client = self.client.clone();
                 let v: Vec<&str> = config.matrix_user.split(":").collect();
                 let username = v.first().unwrap();
@@ -279,7 +336,7 @@ impl Matrix {
                 let res = client
                     .put(format!(
                         "{}/profile/{}/displayname?access_token={}",
-                        MATRIX_URL, user_id_encoded, access_token
+                        self.url, user_id_encoded, access_token
                     ))
                     .json(&data)
                     .send()
@@ -301,6 +358,53 @@ impl Matrix {
         }
     }
 
+    async fn join_room(&self, room_id: &str) -> Result<(), MatrixError> {
+        match &self.access_token {
+            Some(access_token) => {
+                let client = self.client.clone();
+                let room_id_encoded: String =
+                    byte_serialize(room_id.as_bytes()).collect();
+                let res = client
+                    .post(format!(
+                        "{}/join/{}?access_token={}",
+                        self.url, room_id_encoded, access_token
+                    ))
+                    .json(&HashMap::<String, String>::new())
+                    .send()
+                    .await?;
+
+                debug!("response {:?}", res);
+                match res.status() {
+                    reqwest::StatusCode::OK => {
+                        debug!("{} * Matrix room joined", room_id);
+                        Ok(())
+                    }
+                    _ => {
+                        let response = res.json::<ErrorResponse>().await?;
+                        Err(MatrixError::Other(format!(
+                            "Matrix room {} could not be joined: {}",
+                            room_id, response.error
+                        )))
+                    }
+                }
+            }
+            None => Err(MatrixError::Other("access_token not defined".to_string())),
+        }
+    }
+
+    /// Returns the rooms the messages of the event are sent to, the private room
+    /// if no rooms are defined. Test messages are sent to every room defined.
+    fn room_ids(&self, event: &str) -> Vec<String> {
+        if self.rooms.is_empty() {
+            return vec![self.private_room_id.to_string()];
+        }
+        self.rooms
+            .iter()
+            .filter(|room| event == EVENT_TEST || room.accepts(event))
+            .map(|room| room.room_id.to_string())
+            .collect()
+    }
+
     async fn get_room_id_by_room_alias(
         &self,
         room_alias: &str,
@@ -310,7 +414,7 @@ impl Matrix {
         let res = client
             .get(format!(
                 "{}/directory/room/{}",
-                MATRIX_URL, room_alias_encoded
+                self.url, room_alias_encoded
             ))
             .send()
             .await?;
@@ -332,7 +436,7 @@ impl Matrix {
     async fn create_private_room(&self) -> Result<Option<Room>, MatrixError> {
         match &self.access_token {
             Some(access_token) => {
-                let config = CONFIG.clone();
+                let config = CONFIG.load_full();
                 let client = self.client.clone();
                 let room: Room = Room::new_private(self.chain);
                 let req = CreateRoomRequest {
@@ -346,7 +450,7 @@ impl Matrix {
                 let res = client
                     .post(format!(
                         "{}/createRoom?access_token={}",
-                        MATRIX_URL, access_token
+                        self.url, access_token
                     ))
                     .json(&req)
                     .send()
@@ -389,15 +493,19 @@ impl Matrix {
 
     pub async fn send_message(
         &self,
+        event: &str,
         message: &str,
         formatted_message: &str,
     ) -> Result<(), MatrixError> {
         if self.disabled {
             return Ok(());
         }
-        // Send message to private room (private assigned to the matrix_username in config)
-        self.dispatch_message(&self.private_room_id, &message, &formatted_message)
-            .await?;
+        // Send message to the rooms of the event, or to the private room (private
+        // assigned to the matrix_username in config) if no rooms are defined
+        for room_id in self.room_ids(event) {
+            self.dispatch_message(&room_id, &message, &formatted_message)
+                .await?;
+        }
 
         Ok(())
     }
@@ -415,17 +523,13 @@ impl Matrix {
         match &self.access_token {
             Some(access_token) => {
                 let client = self.client.clone();
-                let req = SendRoomMessageRequest {
-                    msgtype: "m.text".to_string(),
-                    body: message.to_string(),
-                    format: "org.matrix.custom.html".to_string(),
-                    formatted_body: formatted_message.to_string(),
-                };
+                let req =
+                    SendRoomMessageRequest::new(message, formatted_message, self.format);
 
                 let res = client
                     .post(format!(
                         "{}/rooms/{}/send/m.room.message?access_token={}",
-                        MATRIX_URL, room_id, access_token
+                        self.url, room_id, access_token
                     ))
                     .json(&req)
                     .send()
@@ -459,3 +563,59 @@ impl Matrix {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn it_uses_the_matrix_homeserver_url_defined() {
+        let vars = vec![
+            (
+                "SCOUTY_STASHES".to_string(),
+                "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY".to_string(),
+            ),
+            (
+                "SCOUTY_MATRIX_HOMESERVER_URL".to_string(),
+                "https://matrix.mycompany.com/".to_string(),
+            ),
+        ];
+        let config = Config::from_vars(vars).unwrap();
+        assert_eq!(
+            config.matrix_homeserver_url,
+            Some("https://matrix.mycompany.com/".to_string())
+        );
+        assert_eq!(
+            client_url(config.matrix_homeserver_url.as_deref()),
+            "https://matrix.mycompany.com/_matrix/client/r0"
+        );
+        assert_eq!(client_url(None), MATRIX_URL);
+        assert_eq!(client_url(Some("")), MATRIX_URL);
+    }
+
+    #[test]
+    fn it_sends_the_messages_to_the_rooms_of_the_event() {
+        let matrix = Matrix {
+            private_room_id: "!private:matrix.org".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(matrix.room_ids("session"), vec!["!private:matrix.org"]);
+
+        let matrix = Matrix {
+            private_room_id: "!private:matrix.org".to_string(),
+            rooms: vec![
+                MatrixRoom::parse("!oncall:matrix.org=slash|chill"),
+                MatrixRoom::parse("!general:matrix.org=session"),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(matrix.room_ids("chill"), vec!["!oncall:matrix.org"]);
+        assert_eq!(matrix.room_ids("session"), vec!["!general:matrix.org"]);
+        assert!(matrix.room_ids("heartbeat").is_empty());
+        assert_eq!(
+            matrix.room_ids(EVENT_TEST),
+            vec!["!oncall:matrix.org", "!general:matrix.org"]
+        );
+    }
+}
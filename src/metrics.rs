@@ -0,0 +1,369 @@
+// The MIT License (MIT)
+// Copyright © 2021 Aukbit Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+use crate::config::format_duration;
+use crate::errors::ScoutyError;
+use crate::http::{self, Response};
+use lazy_static::lazy_static;
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    result::Result,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+lazy_static! {
+    pub static ref METRICS: Metrics = Metrics::default();
+    pub static ref RUNTIME_STATS: RuntimeStats = RuntimeStats::new(Instant::now());
+}
+
+/// Upper bounds in seconds of the RPC call duration histogram buckets
+const RPC_CALL_DURATION_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Observations of a histogram, each bucket counts only the observations that
+/// fall in it, they are summed up when rendered
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Histogram {
+    buckets: [u64; RPC_CALL_DURATION_BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    pub fn observe(&mut self, value: f64) {
+        if let Some(i) = RPC_CALL_DURATION_BUCKETS.iter().position(|le| value <= *le) {
+            self.buckets[i] += 1;
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+
+    #[cfg(test)]
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+/// Registry of the counters and gauges exposed in the Prometheus text format
+#[derive(Debug, Default)]
+pub struct Metrics {
+    sessions_total: AtomicU64,
+    eras_total: AtomicU64,
+    hook_executions_total: Mutex<BTreeMap<String, u64>>,
+    hook_failures_total: AtomicU64,
+    stash_active: Mutex<BTreeMap<String, bool>>,
+    rpc_call_duration_seconds: Mutex<BTreeMap<String, Histogram>>,
+}
+
+impl Metrics {
+    pub fn inc_sessions(&self) {
+        self.sessions_total.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn inc_eras(&self) {
+        self.eras_total.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn inc_hook_executions(&self, hook: &str) {
+        let mut executions = self.hook_executions_total.lock().unwrap();
+        *executions.entry(hook.to_string()).or_insert(0) += 1;
+    }
+
+    pub fn inc_hook_failures(&self) {
+        self.hook_failures_total.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn set_stash_active(&self, stash: &str, is_active: bool) {
+        let mut stash_active = self.stash_active.lock().unwrap();
+        stash_active.insert(stash.to_string(), is_active);
+    }
+
+    pub fn observe_rpc_call(&self, method: &str, duration: Duration) {
+        let mut durations = self.rpc_call_duration_seconds.lock().unwrap();
+        durations
+            .entry(method.to_string())
+            .or_default()
+            .observe(duration.as_secs_f64());
+    }
+
+    /// Returns the observations recorded for the RPC method, if any
+    #[cfg(test)]
+    pub fn rpc_call_duration(&self, method: &str) -> Option<Histogram> {
+        let durations = self.rpc_call_duration_seconds.lock().unwrap();
+        durations.get(method).cloned()
+    }
+
+    /// Renders all metrics in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        writeln!(
+            out,
+            "# HELP scouty_sessions_total Number of new sessions seen."
+        )
+        .ok();
+        writeln!(out, "# TYPE scouty_sessions_total counter").ok();
+        writeln!(
+            out,
+            "scouty_sessions_total {}",
+            self.sessions_total.load(Ordering::Relaxed)
+        )
+        .ok();
+        writeln!(out, "# HELP scouty_eras_total Number of new eras seen.").ok();
+        writeln!(out, "# TYPE scouty_eras_total counter").ok();
+        writeln!(
+            out,
+            "scouty_eras_total {}",
+            self.eras_total.load(Ordering::Relaxed)
+        )
+        .ok();
+        writeln!(
+            out,
+            "# HELP scouty_hook_executions_total Number of hook scripts executed."
+        )
+        .ok();
+        writeln!(out, "# TYPE scouty_hook_executions_total counter").ok();
+        for (hook, total) in self.hook_executions_total.lock().unwrap().iter() {
+            writeln!(
+                out,
+                "scouty_hook_executions_total{{hook=\"{}\"}} {}",
+                escape_label_value(hook),
+                total
+            )
+            .ok();
+        }
+        writeln!(
+            out,
+            "# HELP scouty_hook_failures_total Number of hook scripts that failed."
+        )
+        .ok();
+        writeln!(out, "# TYPE scouty_hook_failures_total counter").ok();
+        writeln!(
+            out,
+            "scouty_hook_failures_total {}",
+            self.hook_failures_total.load(Ordering::Relaxed)
+        )
+        .ok();
+        writeln!(
+            out,
+            "# HELP scouty_stash_active Whether the stash is in the active set (1) or not (0)."
+        )
+        .ok();
+        writeln!(out, "# TYPE scouty_stash_active gauge").ok();
+        for (stash, is_active) in self.stash_active.lock().unwrap().iter() {
+            writeln!(
+                out,
+                "scouty_stash_active{{stash=\"{}\"}} {}",
+                escape_label_value(stash),
+                *is_active as u8
+            )
+            .ok();
+        }
+        writeln!(
+            out,
+            "# HELP scouty_rpc_call_duration_seconds Duration of the RPC calls to the substrate node."
+        )
+        .ok();
+        writeln!(out, "# TYPE scouty_rpc_call_duration_seconds histogram").ok();
+        for (method, histogram) in self.rpc_call_duration_seconds.lock().unwrap().iter() {
+            let method = escape_label_value(method);
+            let mut cumulative = 0;
+            for (le, bucket) in RPC_CALL_DURATION_BUCKETS
+                .iter()
+                .zip(histogram.buckets.iter())
+            {
+                cumulative += bucket;
+                writeln!(
+                    out,
+                    "scouty_rpc_call_duration_seconds_bucket{{method=\"{}\",le=\"{}\"}} {}",
+                    method, le, cumulative
+                )
+                .ok();
+            }
+            writeln!(
+                out,
+                "scouty_rpc_call_duration_seconds_bucket{{method=\"{}\",le=\"+Inf\"}} {}",
+                method, histogram.count
+            )
+            .ok();
+            writeln!(
+                out,
+                "scouty_rpc_call_duration_seconds_sum{{method=\"{}\"}} {}",
+                method, histogram.sum
+            )
+            .ok();
+            writeln!(
+                out,
+                "scouty_rpc_call_duration_seconds_count{{method=\"{}\"}} {}",
+                method, histogram.count
+            )
+            .ok();
+        }
+        out
+    }
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Starts the HTTP server exposing the metrics at /metrics
+pub fn serve(listen_addr: &str) -> Result<(), ScoutyError> {
+    http::serve(listen_addr, |path| match path {
+        "/metrics" => {
+            Response::ok("text/plain; version=0.0.4; charset=utf-8", METRICS.render())
+        }
+        _ => Response::not_found(),
+    })
+}
+
+/// Counters of the current run, summarized in the log once 'scouty' shuts down
+#[derive(Debug)]
+pub struct RuntimeStats {
+    started_at: Instant,
+    sessions: AtomicU64,
+    eras: AtomicU64,
+    hooks: AtomicU64,
+    notifications: AtomicU64,
+    connections: AtomicU64,
+}
+
+impl RuntimeStats {
+    pub fn new(started_at: Instant) -> Self {
+        Self {
+            started_at,
+            sessions: AtomicU64::new(0),
+            eras: AtomicU64::new(0),
+            hooks: AtomicU64::new(0),
+            notifications: AtomicU64::new(0),
+            connections: AtomicU64::new(0),
+        }
+    }
+
+    pub fn inc_sessions(&self) {
+        self.sessions.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn inc_eras(&self) {
+        self.eras.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn inc_hooks(&self) {
+        self.hooks.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn inc_notifications(&self) {
+        self.notifications.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn inc_connections(&self) {
+        self.connections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Every connection established after the first one is a reconnection
+    pub fn reconnections(&self) -> u64 {
+        self.connections.load(Ordering::Relaxed).saturating_sub(1)
+    }
+
+    /// Returns how long 'scouty' has been running up to the instant given
+    pub fn uptime(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.started_at)
+    }
+
+    /// Returns a one line summary of the run up to the instant given
+    pub fn summary(&self, now: Instant) -> String {
+        let uptime = self.uptime(now);
+        format!(
+            "Uptime {} * {} sessions and {} eras observed * {} hooks executed * {} notifications sent * {} reconnections",
+            format_duration(uptime),
+            self.sessions.load(Ordering::Relaxed),
+            self.eras.load(Ordering::Relaxed),
+            self.hooks.load(Ordering::Relaxed),
+            self.notifications.load(Ordering::Relaxed),
+            self.reconnections(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hooks::{HOOK_NEW_ERA, HOOK_NEW_SESSION};
+
+    #[test]
+    fn it_increments_the_metrics_on_events() {
+        let metrics = Metrics::default();
+        // simulate a new era, which is also a new session
+        metrics.inc_sessions();
+        metrics.inc_eras();
+        metrics.inc_hook_executions(HOOK_NEW_SESSION);
+        metrics.inc_hook_executions(HOOK_NEW_ERA);
+        metrics
+            .set_stash_active("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", true);
+        // simulate a following session where the hook fails
+        metrics.inc_sessions();
+        metrics.inc_hook_executions(HOOK_NEW_SESSION);
+        metrics.inc_hook_failures();
+        metrics
+            .set_stash_active("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", false);
+
+        let output = metrics.render();
+        assert!(output.contains("scouty_sessions_total 2\n"));
+        assert!(output.contains("scouty_eras_total 1\n"));
+        assert!(output.contains("scouty_hook_executions_total{hook=\"New session\"} 2\n"));
+        assert!(output.contains("scouty_hook_executions_total{hook=\"New era\"} 1\n"));
+        assert!(output.contains("scouty_hook_failures_total 1\n"));
+        assert!(output.contains(
+            "scouty_stash_active{stash=\"5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY\"} 0\n"
+        ));
+    }
+
+    #[test]
+    fn it_increments_the_runtime_stats_on_events() {
+        let started_at = Instant::now();
+        let stats = RuntimeStats::new(started_at);
+        // simulate the first connection and a new era with its hooks
+        stats.inc_connections();
+        stats.inc_sessions();
+        stats.inc_eras();
+        stats.inc_hooks();
+        stats.inc_hooks();
+        stats.inc_notifications();
+        // simulate a reconnection followed by a new session
+        stats.inc_connections();
+        stats.inc_sessions();
+        stats.inc_hooks();
+        stats.inc_notifications();
+
+        assert_eq!(stats.reconnections(), 1);
+        assert_eq!(
+            stats.summary(started_at + Duration::from_secs(3725)),
+            "Uptime 1h 2m 5s * 2 sessions and 1 eras observed * 3 hooks executed * 2 notifications sent * 1 reconnections"
+        );
+    }
+}
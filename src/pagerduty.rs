@@ -0,0 +1,297 @@
+// The MIT License (MIT)
+// Copyright © 2021 Aukbit Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+use crate::config::CONFIG;
+use crate::errors::PagerDutyError;
+use crate::webhook::{
+    EVENT_VALIDATOR_CHILLED, EVENT_VALIDATOR_SLASHED,
+    EVENT_VALIDATOR_STARTS_INACTIVE_NEXT_ERA,
+};
+use log::debug;
+use serde::Serialize;
+use std::result::Result;
+
+const EVENTS_API_URL: &str = "https://events.pagerduty.com/v2/enqueue";
+
+/// Returns the key PagerDuty groups the alerts of the same stash and event
+/// under, so that the incident can be resolved later
+pub fn dedup_key(event: &str, stash: &str) -> String {
+    format!("scouty-{}-{}", event, stash)
+}
+
+/// Returns the incidents cleared at the end of the era by a stash, given whether it
+/// is active and its keys are queued for the next era. The chilled and inactive
+/// next era incidents stay open only while the stash is active without keys
+/// queued for the next era.
+pub fn cleared_incidents(is_active: bool, is_queued: bool) -> &'static [&'static str] {
+    if is_active && !is_queued {
+        &[]
+    } else {
+        &[
+            EVENT_VALIDATOR_CHILLED,
+            EVENT_VALIDATOR_STARTS_INACTIVE_NEXT_ERA,
+        ]
+    }
+}
+
+/// Event posted to the PagerDuty Events API v2
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Event {
+    pub routing_key: String,
+    pub event_action: String,
+    pub dedup_key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payload: Option<Payload>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Payload {
+    pub summary: String,
+    pub source: String,
+    pub severity: String,
+    pub component: String,
+    pub custom_details: Details,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Details {
+    pub event: String,
+    pub stash: String,
+    pub stash_name: String,
+}
+
+impl Event {
+    /// Builds the event that opens an incident for the stash, a slash is critical
+    /// any other event is an error
+    pub fn trigger(
+        routing_key: &str,
+        event: &str,
+        stash: &str,
+        stash_name: &str,
+    ) -> Event {
+        let name = if stash_name.is_empty() {
+            stash
+        } else {
+            stash_name
+        };
+        let summary = match event {
+            EVENT_VALIDATOR_SLASHED => format!("{} has been slashed", name),
+            EVENT_VALIDATOR_CHILLED => format!("{} has been chilled", name),
+            EVENT_VALIDATOR_STARTS_INACTIVE_NEXT_ERA => {
+                format!("{} will be inactive next era", name)
+            }
+            _ => format!("{} * {}", name, event),
+        };
+        let severity = if event == EVENT_VALIDATOR_SLASHED {
+            "critical"
+        } else {
+            "error"
+        };
+        Event {
+            routing_key: routing_key.to_string(),
+            event_action: "trigger".to_string(),
+            dedup_key: dedup_key(event, stash),
+            payload: Some(Payload {
+                summary,
+                source: stash.to_string(),
+                severity: severity.to_string(),
+                component: env!("CARGO_PKG_NAME").to_string(),
+                custom_details: Details {
+                    event: event.to_string(),
+                    stash: stash.to_string(),
+                    stash_name: stash_name.to_string(),
+                },
+            }),
+        }
+    }
+
+    /// Builds the event that resolves the incident opened for the stash
+    pub fn resolve(routing_key: &str, event: &str, stash: &str) -> Event {
+        Event {
+            routing_key: routing_key.to_string(),
+            event_action: "resolve".to_string(),
+            dedup_key: dedup_key(event, stash),
+            payload: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PagerDuty {
+    pub client: reqwest::Client,
+    routing_key: String,
+    disabled: bool,
+}
+
+impl Default for PagerDuty {
+    fn default() -> PagerDuty {
+        PagerDuty {
+            client: reqwest::Client::new(),
+            routing_key: String::from(""),
+            disabled: true,
+        }
+    }
+}
+
+impl PagerDuty {
+    pub fn new() -> PagerDuty {
+        let config = CONFIG.load_full();
+        PagerDuty {
+            routing_key: config
+                .pagerduty_routing_key
+                .as_ref()
+                .map(|key| key.expose().to_string())
+                .unwrap_or_default(),
+            disabled: !config.is_pagerduty_enabled(),
+            ..Default::default()
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.disabled
+    }
+
+    /// Opens (or updates) the incident of the stash for the event
+    pub async fn trigger(
+        &self,
+        event: &str,
+        stash: &str,
+        stash_name: &str,
+    ) -> Result<(), PagerDutyError> {
+        self.send(&Event::trigger(&self.routing_key, event, stash, stash_name))
+            .await
+    }
+
+    /// Resolves the incident of the stash for the event, if there is any open
+    pub async fn resolve(&self, event: &str, stash: &str) -> Result<(), PagerDutyError> {
+        self.send(&Event::resolve(&self.routing_key, event, stash))
+            .await
+    }
+
+    async fn send(&self, event: &Event) -> Result<(), PagerDutyError> {
+        if self.disabled {
+            return Ok(());
+        }
+        let res = self
+            .client
+            .post(EVENTS_API_URL)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .json(event)
+            .send()
+            .await?;
+
+        debug!("response {:?}", res);
+        if res.status().is_success() {
+            debug!(
+                "{} {} * PagerDuty event dispatched",
+                event.event_action, event.dedup_key
+            );
+            Ok(())
+        } else {
+            Err(PagerDutyError::Other(format!(
+                "PagerDuty responded with status code {} to {} {}",
+                res.status(),
+                event.event_action,
+                event.dedup_key
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_generates_the_dedup_keys() {
+        let stash = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+        assert_eq!(
+            dedup_key(EVENT_VALIDATOR_CHILLED, stash),
+            "scouty-validator_chilled-5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+        );
+        // The incident is resolved under the same key it was triggered with
+        assert_eq!(
+            Event::trigger("key", EVENT_VALIDATOR_CHILLED, stash, "").dedup_key,
+            Event::resolve("key", EVENT_VALIDATOR_CHILLED, stash).dedup_key
+        );
+        assert_ne!(
+            dedup_key(EVENT_VALIDATOR_CHILLED, stash),
+            dedup_key(EVENT_VALIDATOR_SLASHED, stash)
+        );
+    }
+
+    #[test]
+    fn it_resolves_the_inactive_next_era_incident_once_cleared() {
+        let stash = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+        // Active without keys queued, the incident is triggered and kept open
+        let trigger =
+            Event::trigger("key", EVENT_VALIDATOR_STARTS_INACTIVE_NEXT_ERA, stash, "");
+        assert!(cleared_incidents(true, false).is_empty());
+        // Any other state resolves it under the key it was triggered with
+        for (is_active, is_queued) in [(true, true), (false, true), (false, false)] {
+            let cleared = cleared_incidents(is_active, is_queued);
+            assert!(cleared.contains(&EVENT_VALIDATOR_CHILLED));
+            let resolve = cleared
+                .iter()
+                .map(|event| Event::resolve("key", event, stash))
+                .find(|resolve| resolve.dedup_key == trigger.dedup_key);
+            assert_eq!(resolve.unwrap().event_action, "resolve");
+        }
+    }
+
+    #[test]
+    fn it_builds_the_trigger_payload() {
+        let event = Event::trigger(
+            "R0UT1NGK3Y",
+            EVENT_VALIDATOR_SLASHED,
+            "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",
+            "MyValidator01",
+        );
+        let json = serde_json::to_string(&event).unwrap();
+        assert_eq!(
+            json,
+            r#"{"routing_key":"R0UT1NGK3Y","event_action":"trigger","dedup_key":"scouty-validator_slashed-5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY","payload":{"summary":"MyValidator01 has been slashed","source":"5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY","severity":"critical","component":"scouty","custom_details":{"event":"validator_slashed","stash":"5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY","stash_name":"MyValidator01"}}}"#
+        );
+
+        let event = Event::trigger(
+            "R0UT1NGK3Y",
+            EVENT_VALIDATOR_STARTS_INACTIVE_NEXT_ERA,
+            "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",
+            "",
+        );
+        let payload = event.payload.unwrap();
+        assert_eq!(
+            payload.summary,
+            "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY will be inactive next era"
+        );
+        assert_eq!(payload.severity, "error");
+
+        let event = Event::resolve(
+            "R0UT1NGK3Y",
+            EVENT_VALIDATOR_STARTS_INACTIVE_NEXT_ERA,
+            "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",
+        );
+        let json = serde_json::to_string(&event).unwrap();
+        assert_eq!(
+            json,
+            r#"{"routing_key":"R0UT1NGK3Y","event_action":"resolve","dedup_key":"scouty-validator_starts_inactive_next_era-5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"}"#
+        );
+    }
+}
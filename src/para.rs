@@ -49,12 +49,12 @@ impl ParaRecords {
         &mut self,
         active_validators: Vec<AccountId32>,
     ) -> Result<(), ScoutyError> {
-        let config = CONFIG.clone();
+        let config = CONFIG.load_full();
 
         let mut config_stashes: Vec<(AccountId32, u32)> = vec![];
 
         // Find stash indices
-        for stash_str in config.stashes.iter() {
+        for stash_str in config.stashes.iter().map(|stash| &stash.address) {
             let stash = AccountId32::from_str(stash_str)?;
             if let Some(index) = active_validators.iter().position(|x| x == &stash) {
                 config_stashes.push((stash, index.try_into().unwrap()));
@@ -0,0 +1,490 @@
+// The MIT License (MIT)
+// Copyright © 2021 Aukbit Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+use crate::errors::ScoutyError;
+use log::{debug, info, warn};
+use native_tls::TlsConnector;
+use std::{
+    env,
+    io::{self, Read, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    result::Result,
+    thread,
+    time::Duration,
+};
+use url::Url;
+
+/// Returns the proxy to be used for the substrate endpoint. The explicit
+/// `substrate_ws_proxy` takes precedence over the standard environment variables,
+/// `https_proxy` for wss:// endpoints or `http_proxy` for ws:// endpoints, and
+/// lastly `all_proxy`. The environment variables are not used for a host listed
+/// in `no_proxy`.
+pub fn resolve(substrate_ws_url: &str, explicit: Option<&str>) -> Option<String> {
+    resolve_from(substrate_ws_url, explicit, |name| env::var(name).ok())
+}
+
+fn resolve_from<F>(
+    substrate_ws_url: &str,
+    explicit: Option<&str>,
+    lookup: F,
+) -> Option<String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    if let Some(proxy) = explicit.filter(|proxy| !proxy.is_empty()) {
+        return Some(proxy.to_string());
+    }
+    let no_proxy = lookup("no_proxy")
+        .or_else(|| lookup("NO_PROXY"))
+        .unwrap_or_default();
+    let host = Url::parse(substrate_ws_url)
+        .ok()
+        .and_then(|url| url.host_str().map(|host| host.to_string()));
+    if host.map_or(false, |host| is_no_proxy(&host, &no_proxy)) {
+        return None;
+    }
+    let names: &[&str] = if substrate_ws_url.starts_with("wss://") {
+        &["https_proxy", "HTTPS_PROXY", "all_proxy", "ALL_PROXY"]
+    } else {
+        &["http_proxy", "HTTP_PROXY", "all_proxy", "ALL_PROXY"]
+    };
+    names
+        .iter()
+        .filter_map(|name| lookup(name))
+        .find(|proxy| !proxy.is_empty())
+}
+
+/// Whether the host matches one of the comma separated `no_proxy` entries, which
+/// are either `*`, the host itself or one of its parent domains (e.g.
+/// `.example.com`)
+fn is_no_proxy(host: &str, no_proxy: &str) -> bool {
+    no_proxy
+        .split(',')
+        .map(|entry| {
+            entry
+                .trim()
+                .trim_start_matches("*.")
+                .trim_start_matches('.')
+        })
+        .filter(|entry| !entry.is_empty())
+        .any(|entry| {
+            entry == "*" || host == entry || host.ends_with(&format!(".{}", entry))
+        })
+}
+
+/// Opens a TCP connection to host:port through an http:// (CONNECT) or a
+/// socks5:// proxy. The client name is sent as the User-Agent to http:// proxies.
+pub fn connect(
+    proxy: &str,
+    host: &str,
+    port: u16,
+    client_name: &str,
+) -> io::Result<TcpStream> {
+    let url = Url::parse(proxy).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid proxy {}: {}", proxy, e),
+        )
+    })?;
+    let proxy_host = url.host_str().unwrap_or_default();
+    match url.scheme() {
+        "http" => {
+            let mut stream = TcpStream::connect((
+                proxy_host,
+                url.port_or_known_default().unwrap_or(80),
+            ))?;
+            write!(
+                stream,
+                "CONNECT {0}:{1} HTTP/1.1\r\nHost: {0}:{1}\r\nUser-Agent: {2}\r\n\r\n",
+                host, port, client_name
+            )?;
+            // Read the response headers byte by byte so that nothing beyond them is
+            // consumed from the tunnel
+            let mut response = Vec::new();
+            let mut byte = [0u8; 1];
+            while !response.ends_with(b"\r\n\r\n") {
+                if stream.read(&mut byte)? == 0 {
+                    break;
+                }
+                response.push(byte[0]);
+            }
+            let response = String::from_utf8_lossy(&response);
+            let status = response.lines().next().unwrap_or_default();
+            if status.split_whitespace().nth(1) != Some("200") {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    format!("Proxy {} refused the connection: {}", proxy, status),
+                ));
+            }
+            Ok(stream)
+        }
+        "socks5" | "socks5h" => {
+            let mut stream =
+                TcpStream::connect((proxy_host, url.port().unwrap_or(1080)))?;
+            // Greeting without authentication
+            stream.write_all(&[5, 1, 0])?;
+            let mut reply = [0u8; 2];
+            stream.read_exact(&mut reply)?;
+            if reply != [5, 0] {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    format!("Proxy {} requires an unsupported authentication", proxy),
+                ));
+            }
+            // Connect request with the domain name resolved by the proxy
+            let mut request = vec![5, 1, 0, 3, host.len() as u8];
+            request.extend_from_slice(host.as_bytes());
+            request.extend_from_slice(&port.to_be_bytes());
+            stream.write_all(&request)?;
+            let mut reply = [0u8; 4];
+            stream.read_exact(&mut reply)?;
+            if reply[1] != 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    format!("Proxy {} refused the connection: code {}", proxy, reply[1]),
+                ));
+            }
+            // Skip the bound address
+            let len = match reply[3] {
+                1 => 4,
+                4 => 16,
+                _ => {
+                    let mut len = [0u8; 1];
+                    stream.read_exact(&mut len)?;
+                    len[0] as usize
+                }
+            };
+            let mut bound = vec![0u8; len + 2];
+            stream.read_exact(&mut bound)?;
+            Ok(stream)
+        }
+        scheme => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Unsupported proxy scheme {}", scheme),
+        )),
+    }
+}
+
+/// Opens a TCP connection to host:port, trying each of its addresses within the
+/// timeout. A zero timeout waits indefinitely.
+pub fn connect_direct(host: &str, port: u16, timeout: Duration) -> io::Result<TcpStream> {
+    if timeout.is_zero() {
+        return TcpStream::connect((host, port));
+    }
+    let mut last_error = None;
+    for addr in (host, port).to_socket_addrs()? {
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No address found for {}", host),
+        )
+    }))
+}
+
+/// Serves the connection already established with the substrate endpoint on a
+/// local tunnel and returns the ws:// url the substrate client should connect to.
+/// Only one connection is accepted, since the upstream one can not be reused.
+pub fn tunnel_stream<S>(
+    substrate_ws_url: &str,
+    upstream: S,
+) -> Result<String, ScoutyError>
+where
+    S: Read + Write + Send + 'static,
+{
+    let host = host_header(substrate_ws_url)?;
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let local_port = listener.local_addr()?.port();
+    thread::spawn(move || {
+        let result = listener.accept().and_then(|(mut local, _)| {
+            let mut upstream = upstream;
+            forward_handshake(&mut local, &mut upstream, &host)?;
+            pipe(local, upstream)
+        });
+        match result {
+            Ok(()) => debug!("Tunnel closed"),
+            Err(e) => warn!("Tunnel error: {}", e),
+        }
+    });
+    local_url(substrate_ws_url, local_port)
+}
+
+/// Returns the Host header of the substrate endpoint, with the port only if it is
+/// not the default one of the scheme
+fn host_header(substrate_ws_url: &str) -> Result<String, ScoutyError> {
+    let url = Url::parse(substrate_ws_url).map_err(|e| {
+        ScoutyError::Other(format!("Invalid url {}: {}", substrate_ws_url, e))
+    })?;
+    let host = url.host_str().ok_or_else(|| {
+        ScoutyError::Other(format!("Url {} has no host defined", substrate_ws_url))
+    })?;
+    Ok(match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    })
+}
+
+/// Reads the websocket handshake request the substrate client sends to the local
+/// tunnel and forwards it upstream with the Host header of the substrate endpoint
+/// instead of the local one, so that the request reaches the right virtual host
+fn forward_handshake<S: Write>(
+    local: &mut TcpStream,
+    upstream: &mut S,
+    host: &str,
+) -> io::Result<()> {
+    // Read the request headers byte by byte so that nothing beyond them is
+    // consumed before the pipe
+    let mut request = Vec::new();
+    let mut byte = [0u8; 1];
+    while !request.ends_with(b"\r\n\r\n") {
+        if local.read(&mut byte)? == 0 {
+            break;
+        }
+        request.push(byte[0]);
+    }
+    upstream.write_all(with_host(&String::from_utf8_lossy(&request), host).as_bytes())
+}
+
+/// Replaces the Host header of the HTTP request
+fn with_host(request: &str, host: &str) -> String {
+    request
+        .split("\r\n")
+        .map(|line| {
+            if line.to_ascii_lowercase().starts_with("host:") {
+                format!("Host: {}", host)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\r\n")
+}
+
+/// Returns the ws:// url of the local tunnel to the substrate endpoint
+fn local_url(substrate_ws_url: &str, local_port: u16) -> Result<String, ScoutyError> {
+    let mut url = Url::parse(substrate_ws_url).map_err(|e| {
+        ScoutyError::Other(format!("Invalid url {}: {}", substrate_ws_url, e))
+    })?;
+    if url.set_scheme("ws").is_err()
+        || url.set_host(Some("127.0.0.1")).is_err()
+        || url.set_port(Some(local_port)).is_err()
+    {
+        return Err(ScoutyError::Other(format!(
+            "Invalid url {}",
+            substrate_ws_url
+        )));
+    }
+    Ok(url.to_string())
+}
+
+/// Opens a local tunnel to the substrate endpoint through the proxy and returns
+/// the ws:// url the substrate client should connect to. For wss:// endpoints the
+/// TLS session is established by the tunnel, verifying the endpoint host. The
+/// handshake is forwarded with the Host header of the substrate endpoint.
+pub fn tunnel(
+    substrate_ws_url: &str,
+    proxy: &str,
+    client_name: &str,
+) -> Result<String, ScoutyError> {
+    let url = Url::parse(substrate_ws_url).map_err(|e| {
+        ScoutyError::Other(format!("Invalid url {}: {}", substrate_ws_url, e))
+    })?;
+    let host = url
+        .host_str()
+        .ok_or_else(|| {
+            ScoutyError::Other(format!("Url {} has no host defined", substrate_ws_url))
+        })?
+        .to_string();
+    let is_tls = url.scheme() == "wss";
+    let port = url.port().unwrap_or(if is_tls { 443 } else { 80 });
+    let host_header = host_header(substrate_ws_url)?;
+
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let local_addr = listener.local_addr()?;
+    info!("Connecting to {} through proxy {}", substrate_ws_url, proxy);
+
+    let proxy = proxy.to_string();
+    let client_name = client_name.to_string();
+    // A new tunnel is opened for every connection, so only one is accepted
+    thread::spawn(move || {
+        let result = listener.accept().and_then(|(mut local, _)| {
+            let upstream = connect(&proxy, &host, port, &client_name)?;
+            if is_tls {
+                let connector = TlsConnector::new()
+                    .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+                let mut upstream = connector
+                    .connect(&host, upstream)
+                    .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+                // The read timeout is only set once the handshake is completed
+                upstream
+                    .get_ref()
+                    .set_read_timeout(Some(Duration::from_millis(20)))?;
+                forward_handshake(&mut local, &mut upstream, &host_header)?;
+                pipe(local, upstream)
+            } else {
+                let mut upstream = upstream;
+                upstream.set_read_timeout(Some(Duration::from_millis(20)))?;
+                forward_handshake(&mut local, &mut upstream, &host_header)?;
+                pipe(local, upstream)
+            }
+        });
+        match result {
+            Ok(()) => debug!("Proxy tunnel closed"),
+            Err(e) => warn!("Proxy tunnel error: {}", e),
+        }
+    });
+
+    local_url(substrate_ws_url, local_addr.port())
+}
+
+/// Copies bytes in both directions until one of the sides closes the connection.
+/// Both sides are polled with a short read timeout, so that a TLS stream does not
+/// need to be split.
+fn pipe<S: Read + Write>(mut local: TcpStream, mut upstream: S) -> io::Result<()> {
+    local.set_read_timeout(Some(Duration::from_millis(20)))?;
+    let mut buf = [0u8; 16384];
+    loop {
+        match local.read(&mut buf) {
+            Ok(0) => return Ok(()),
+            Ok(n) => upstream.write_all(&buf[..n])?,
+            Err(e) if is_timeout(&e) => {}
+            Err(e) => return Err(e),
+        }
+        match upstream.read(&mut buf) {
+            Ok(0) => return Ok(()),
+            Ok(n) => local.write_all(&buf[..n])?,
+            Err(e) if is_timeout(&e) => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+fn is_timeout(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_resolves_the_proxy_with_precedence() {
+        let lookup = |name: &str| match name {
+            "https_proxy" => Some("http://proxy.example.com:3128".to_string()),
+            "all_proxy" => Some("socks5://proxy.example.com:1080".to_string()),
+            _ => None,
+        };
+        // the explicit proxy overrides the environment variables
+        assert_eq!(
+            resolve_from(
+                "wss://rpc.polkadot.io:443",
+                Some("http://explicit.example.com:8080"),
+                lookup
+            ),
+            Some("http://explicit.example.com:8080".to_string())
+        );
+        assert_eq!(
+            resolve_from("wss://rpc.polkadot.io:443", None, lookup),
+            Some("http://proxy.example.com:3128".to_string())
+        );
+        assert_eq!(
+            resolve_from("ws://127.0.0.1:9944", None, lookup),
+            Some("socks5://proxy.example.com:1080".to_string())
+        );
+        assert_eq!(resolve_from("ws://127.0.0.1:9944", None, |_| None), None);
+    }
+
+    #[test]
+    fn it_does_not_resolve_the_proxy_of_a_no_proxy_host() {
+        let lookup = |name: &str| match name {
+            "all_proxy" => Some("socks5://proxy.example.com:1080".to_string()),
+            "NO_PROXY" => Some("localhost, .internal.example.com".to_string()),
+            _ => None,
+        };
+        assert_eq!(resolve_from("ws://localhost:9944", None, lookup), None);
+        assert_eq!(
+            resolve_from("wss://rpc.internal.example.com", None, lookup),
+            None
+        );
+        assert_eq!(
+            resolve_from("wss://rpc.polkadot.io:443", None, lookup),
+            Some("socks5://proxy.example.com:1080".to_string())
+        );
+        // the explicit proxy is used whatever no_proxy lists
+        assert_eq!(
+            resolve_from(
+                "ws://localhost:9944",
+                Some("http://explicit.example.com:8080"),
+                lookup
+            ),
+            Some("http://explicit.example.com:8080".to_string())
+        );
+        assert!(is_no_proxy("rpc.polkadot.io", "*"));
+        assert!(!is_no_proxy("rpc.polkadot.io", ""));
+    }
+
+    #[test]
+    fn it_forwards_the_handshake_with_the_endpoint_host() {
+        let request =
+            "GET / HTTP/1.1\r\nhost: 127.0.0.1:43210\r\nUpgrade: websocket\r\n\r\n";
+        assert_eq!(
+            with_host(request, "rpc.polkadot.io"),
+            "GET / HTTP/1.1\r\nHost: rpc.polkadot.io\r\nUpgrade: websocket\r\n\r\n"
+        );
+        assert_eq!(
+            host_header("wss://rpc.polkadot.io:443").unwrap(),
+            "rpc.polkadot.io"
+        );
+        assert_eq!(
+            host_header("ws://127.0.0.1:9944").unwrap(),
+            "127.0.0.1:9944"
+        );
+    }
+
+    #[test]
+    fn it_connects_through_an_http_proxy() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let proxy = format!("http://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 1024];
+            let n = stream.read(&mut buf).unwrap();
+            let request = String::from_utf8_lossy(&buf[..n]).to_string();
+            stream
+                .write_all(b"HTTP/1.1 200 Connection established\r\n\r\nhello")
+                .unwrap();
+            request
+        });
+        let mut stream = connect(&proxy, "rpc.polkadot.io", 443, "scouty/0.3.1").unwrap();
+        let mut hello = [0u8; 5];
+        stream.read_exact(&mut hello).unwrap();
+        assert_eq!(&hello, b"hello");
+        let request = handle.join().unwrap();
+        assert!(request.starts_with("CONNECT rpc.polkadot.io:443 HTTP/1.1\r\n"));
+        assert!(request.contains("\r\nUser-Agent: scouty/0.3.1\r\n"));
+    }
+}
@@ -0,0 +1,144 @@
+// The MIT License (MIT)
+// Copyright © 2021 Aukbit Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+use crate::config::CONFIG;
+use lazy_static::lazy_static;
+use log::{info, warn};
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+lazy_static! {
+    // A single bucket shared by all notification backends and kept across
+    // subscription restarts
+    pub static ref NOTIFICATIONS: Mutex<Option<TokenBucket>> = Mutex::new(None);
+}
+
+/// Token bucket allowing up to `capacity` notifications per minute, tokens are
+/// refilled continuously so that bursts are limited to the capacity
+#[derive(Debug)]
+pub struct TokenBucket {
+    capacity: u64,
+    tokens: f64,
+    last_refill: Instant,
+    suppressed: u64,
+}
+
+impl TokenBucket {
+    pub fn new(capacity: u64, now: Instant) -> TokenBucket {
+        TokenBucket {
+            capacity,
+            tokens: capacity as f64,
+            last_refill: now,
+            suppressed: 0,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        let refill_rate = self.capacity as f64 / Duration::from_secs(60).as_secs_f64();
+        self.tokens =
+            (self.tokens + elapsed.as_secs_f64() * refill_rate).min(self.capacity as f64);
+        self.last_refill = now;
+    }
+
+    /// Takes a token if available, otherwise the notification is counted as
+    /// suppressed
+    pub fn try_acquire(&mut self, now: Instant) -> bool {
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            self.suppressed += 1;
+            false
+        }
+    }
+
+    /// Returns and resets the number of notifications suppressed
+    pub fn take_suppressed(&mut self) -> u64 {
+        std::mem::take(&mut self.suppressed)
+    }
+}
+
+/// Verifies if a notification can be sent under the configured rate limit. The
+/// number of notifications suppressed is logged once they are allowed again.
+pub fn allow_notification() -> bool {
+    let limit = CONFIG.load().notification_rate_limit_per_minute;
+    if limit == 0 {
+        return true;
+    }
+    let now = Instant::now();
+    let mut bucket = NOTIFICATIONS.lock().unwrap();
+    // Start over if the limit changed since the last notification (e.g. reload)
+    if bucket
+        .as_ref()
+        .map_or(true, |bucket| bucket.capacity != limit)
+    {
+        *bucket = Some(TokenBucket::new(limit, now));
+    }
+    let bucket = bucket.as_mut().unwrap();
+    if bucket.try_acquire(now) {
+        let suppressed = bucket.take_suppressed();
+        if suppressed > 0 {
+            info!(
+                "{} notification(s) suppressed by the rate limit of {} per minute",
+                suppressed, limit
+            );
+        }
+        true
+    } else {
+        if bucket.suppressed == 1 {
+            warn!(
+                "Notification rate limit of {} per minute reached -> Suppressing notifications",
+                limit
+            );
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_allows_n_notifications_per_minute() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(3, now);
+        assert!(bucket.try_acquire(now));
+        assert!(bucket.try_acquire(now));
+        assert!(bucket.try_acquire(now));
+        assert!(!bucket.try_acquire(now));
+        assert_eq!(bucket.take_suppressed(), 1);
+        assert_eq!(bucket.take_suppressed(), 0);
+        // a token is refilled every 20 seconds
+        let later = now + Duration::from_secs(20);
+        assert!(bucket.try_acquire(later));
+        assert!(!bucket.try_acquire(later));
+        // the bucket never holds more than its capacity
+        let much_later = later + Duration::from_secs(3600);
+        assert!(bucket.try_acquire(much_later));
+        assert!(bucket.try_acquire(much_later));
+        assert!(bucket.try_acquire(much_later));
+        assert!(!bucket.try_acquire(much_later));
+    }
+}
@@ -18,13 +18,30 @@
 // LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
+use crate::commission::format_commission;
+use crate::concurrency::timed;
 use crate::config::CONFIG;
+use crate::digest::StashSummary;
 use crate::errors::ScoutyError;
-use crate::hooks::Hook;
-use log::info;
+use crate::hooks::{
+    Hook, HOOK_VALIDATOR_STARTS_ACTIVE_NEXT_ERA, HOOK_VALIDATOR_STARTS_INACTIVE_NEXT_ERA,
+};
+use crate::metrics::METRICS;
+use crate::rewards::{format_eras, EraPerformance};
+use crate::stake::{format_change, Stake};
+use crate::templates::{
+    Templates, TEMPLATE_ACTIVE_NEXT_ERA, TEMPLATE_INACTIVE_NEXT_ERA, TEMPLATE_NEW_SESSION,
+};
+use chrono::{TimeZone, Utc};
+use chrono_tz::Tz;
+use log::{debug, info, warn};
 use serde::Deserialize;
-use std::{convert::TryInto, result::Result};
-use subxt::{sp_runtime::AccountId32, Client, DefaultConfig};
+use std::{convert::TryInto, fmt, result::Result};
+use subxt::{
+    rpc::{rpc_params, ClientT},
+    sp_runtime::AccountId32,
+    Client, DefaultConfig,
+};
 
 #[derive(Debug, Default)]
 pub struct Init {
@@ -44,27 +61,36 @@ impl Network {
         let properties = client.properties();
 
         // Get Network name
-        let chain_name = client.rpc().system_chain().await?;
-
-        // Get Token symbol
-        let token_symbol: String =
-            if let Some(token_symbol) = properties.get("tokenSymbol") {
-                token_symbol.as_str().unwrap_or_default().to_string()
-            } else {
-                "ND".to_string()
-            };
+        let chain_name =
+            timed(&METRICS, "system_chain", client.rpc().system_chain()).await?;
+
+        let config = CONFIG.load_full();
+
+        // Get Token symbol, the one defined in config takes precedence
+        let token_symbol: String = if let Some(token_symbol) = &config.token_symbol {
+            token_symbol.to_string()
+        } else if let Some(token_symbol) = properties.get("tokenSymbol") {
+            token_symbol.as_str().unwrap_or_default().to_string()
+        } else if let Some(chain) = config.chain {
+            chain.token_symbol().to_string()
+        } else {
+            "ND".to_string()
+        };
 
-        // Get Token decimals
-        let token_decimals: u8 =
-            if let Some(token_decimals) = properties.get("tokenDecimals") {
-                token_decimals
-                    .as_u64()
-                    .unwrap_or_default()
-                    .try_into()
-                    .unwrap()
-            } else {
-                12
-            };
+        // Get Token decimals, the ones defined in config take precedence
+        let token_decimals: u8 = if let Some(token_decimals) = config.token_decimals {
+            token_decimals
+        } else if let Some(token_decimals) = properties.get("tokenDecimals") {
+            token_decimals
+                .as_u64()
+                .unwrap_or_default()
+                .try_into()
+                .unwrap()
+        } else if let Some(chain) = config.chain {
+            chain.token_decimals()
+        } else {
+            12
+        };
 
         Ok(Network {
             name: chain_name,
@@ -74,6 +100,39 @@ impl Network {
     }
 }
 
+/// Health of the substrate node as returned by the `system_health` RPC
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeHealth {
+    pub peers: u64,
+    pub is_syncing: bool,
+    #[serde(default)]
+    pub should_have_peers: bool,
+}
+
+impl NodeHealth {
+    /// Returns None if the endpoint does not support `system_health`
+    pub async fn load(client: &Client<DefaultConfig>) -> Option<NodeHealth> {
+        let request = client.rpc().client.request("system_health", rpc_params![]);
+        match timed(&METRICS, "system_health", request).await {
+            Ok(health) => Some(health),
+            Err(e) => {
+                debug!("Node health skipped! {}", e);
+                None
+            }
+        }
+    }
+
+    /// Returns the peer count and sync state (e.g. 42 peers * synced)
+    pub fn status(&self) -> String {
+        format!(
+            "{} peers * {}",
+            self.peers,
+            if self.is_syncing { "syncing" } else { "synced" }
+        )
+    }
+}
+
 #[derive(Debug)]
 pub struct Points {
     pub validator: u32,
@@ -90,6 +149,36 @@ pub struct Session {
     pub queued_session_keys_changed: bool,
 }
 
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+pub enum ChillKind {
+    // chilled by the stash controller
+    Voluntary,
+    // chilled while disabled or slashed in the same block
+    Forced,
+    // disabled mid-era without being chilled
+    Disabled,
+}
+
+impl ChillKind {
+    pub fn from_state(is_disabled: bool, is_slashed: bool) -> ChillKind {
+        if is_disabled || is_slashed {
+            ChillKind::Forced
+        } else {
+            ChillKind::Voluntary
+        }
+    }
+}
+
+impl fmt::Display for ChillKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ChillKind::Voluntary => write!(f, "voluntary"),
+            ChillKind::Forced => write!(f, "forced"),
+            ChillKind::Disabled => write!(f, "disabled"),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Validator {
     pub stash: AccountId32,
@@ -106,7 +195,23 @@ pub struct Validator {
     #[serde(default)]
     pub is_chilled: bool,
     #[serde(default)]
+    pub is_disabled: bool,
+    #[serde(default)]
+    pub chill_kind: Option<ChillKind>,
+    #[serde(default)]
     pub is_offline: bool,
+    // old and new commission if it changed since the previous era
+    #[serde(default)]
+    pub commission_change: Option<(u32, u32)>,
+    // old and new stake if it shifted above the threshold since the previous era
+    #[serde(default)]
+    pub stake_change: Option<(Stake, Stake)>,
+    // eras within the lookback with rewards left to claim
+    #[serde(default)]
+    pub unclaimed_eras: Vec<u32>,
+    // points and rank in the era that just ended, only at an era boundary
+    #[serde(default)]
+    pub previous_era: Option<EraPerformance>,
     #[serde(default)]
     pub hooks: Vec<Hook>,
 }
@@ -121,14 +226,49 @@ impl Validator {
             queued_session_keys: Vec::new(),
             is_slashed: false,
             is_chilled: false,
+            is_disabled: false,
+            chill_kind: None,
             is_offline: false,
+            commission_change: None,
+            stake_change: None,
+            unclaimed_eras: Vec::new(),
+            previous_era: None,
             hooks: Vec::new(),
         }
     }
+
+    /// Returns the active status and whether the session keys are queued for the
+    /// next era, as printed by the `view` command
+    pub fn status(&self) -> String {
+        format!(
+            "{} {} -> {}, {} next era",
+            self.name,
+            self.stash,
+            if self.is_active { "active" } else { "inactive" },
+            if self.is_queued {
+                "queued"
+            } else {
+                "not queued"
+            }
+        )
+    }
 }
 
 pub type Validators = Vec<Validator>;
 
+/// Marks the validator of the chilled stash, only if it is one of the stashes
+/// defined. The chill is forced if the stash is disabled or has been slashed.
+pub fn mark_chilled<'a>(
+    validators: &'a mut Validators,
+    who: &AccountId32,
+    is_slashed: bool,
+) -> Option<&'a mut Validator> {
+    let v = validators.iter_mut().find(|v| &v.stash == who)?;
+    v.is_chilled = true;
+    v.chill_kind = Some(ChillKind::from_state(v.is_disabled, is_slashed));
+    Some(v)
+}
+
 #[derive(Debug, Deserialize, Default)]
 pub struct Referendum {
     #[serde(default)]
@@ -156,6 +296,32 @@ pub enum Section {
     Chill,
     Offline,
     Democracy,
+    Heartbeat,
+    Commission,
+    Stake,
+    StashSlashed,
+    Rewards,
+    Daily,
+}
+
+impl Section {
+    /// Returns the event name the matrix rooms are filtered by
+    pub fn event(&self) -> &'static str {
+        match self {
+            Section::Init => "init",
+            Section::Session => "session",
+            Section::Slash => "slash",
+            Section::Chill => "chill",
+            Section::Offline => "offline",
+            Section::Democracy => "democracy",
+            Section::Heartbeat => "heartbeat",
+            Section::Commission => "commission",
+            Section::Stake => "stake",
+            Section::StashSlashed => "stash_slashed",
+            Section::Rewards => "rewards",
+            Section::Daily => "daily",
+        }
+    }
 }
 
 impl Default for Section {
@@ -172,6 +338,8 @@ pub struct RawData {
     pub session: Session,
     pub referendum: Referendum,
     pub slash: Slash,
+    pub health: Option<NodeHealth>,
+    pub daily: Vec<StashSummary>,
     pub section: Section,
 }
 
@@ -180,14 +348,19 @@ type Body = Vec<String>;
 pub struct Report {
     body: Body,
     is_short: bool,
+    // identifies the section and stashes the report is about
+    target: String,
+    section: Section,
 }
 
 impl Report {
     pub fn new() -> Report {
-        let config = CONFIG.clone();
+        let config = CONFIG.load_full();
         Report {
             body: Vec::new(),
             is_short: config.is_short,
+            target: String::new(),
+            section: Section::default(),
         }
     }
 
@@ -212,6 +385,15 @@ impl Report {
         self.body.join("<br/>")
     }
 
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// Returns the event name of the report section
+    pub fn event(&self) -> &'static str {
+        self.section.event()
+    }
+
     pub fn log(&self) {
         info!("__START__");
         for t in &self.body {
@@ -225,6 +407,16 @@ impl From<RawData> for Report {
     /// Converts a Scouty `RawData` into a [`Report`].
     fn from(data: RawData) -> Report {
         let mut report = Report::new();
+        report.section = data.section;
+        report.target = format!(
+            "{:?}:{}",
+            data.section,
+            data.validators
+                .iter()
+                .map(|v| v.stash.to_string())
+                .collect::<Vec<String>>()
+                .join(",")
+        );
 
         // Scouty package
         report.add_raw_text(format!(
@@ -242,6 +434,12 @@ impl From<RawData> for Report {
             Section::Slash => section_slash(&mut report, data),
             Section::Chill => section_chill(&mut report, data),
             Section::Offline => section_offline(&mut report, data),
+            Section::Heartbeat => section_heartbeat(&mut report, data),
+            Section::Commission => section_commission(&mut report, data),
+            Section::Stake => section_stake(&mut report, data),
+            Section::StashSlashed => section_stash_slashed(&mut report, data),
+            Section::Rewards => section_rewards(&mut report, data),
+            Section::Daily => section_daily(&mut report, data),
         };
 
         // --- Specific report section here [END] ---|
@@ -258,23 +456,149 @@ impl From<RawData> for Report {
     }
 }
 
+/// Loads the message templates defined in config, falls back to the built-in
+/// defaults if the file is not valid anymore
+fn templates() -> Templates {
+    let config = CONFIG.load_full();
+    match Templates::load(config.message_templates_path.as_deref()) {
+        Ok(templates) => templates,
+        Err(e) => {
+            warn!("Message templates skipped! {}", e);
+            Templates::default()
+        }
+    }
+}
+
+/// Escapes the characters with a special meaning in HTML
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Removes the HTML tags from a report message and unescapes the entities, so
+/// that it can be sent as plain text
+pub fn strip_html(message: &str) -> String {
+    let mut text = String::with_capacity(message.len());
+    let mut is_tag = false;
+    for c in message.chars() {
+        match c {
+            '<' => is_tag = true,
+            '>' if is_tag => is_tag = false,
+            _ if !is_tag => text.push(c),
+            _ => (),
+        }
+    }
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Formats a block timestamp in milliseconds in the timezone given, followed by
+/// the timezone abbreviation (e.g. 2022-04-15 06:20:00 WEST)
+pub fn format_timestamp(millis: u64, timezone: Tz) -> String {
+    Utc.timestamp_millis(millis as i64)
+        .with_timezone(&timezone)
+        .format("%Y-%m-%d %H:%M:%S %Z")
+        .to_string()
+}
+
+/// Formats an amount of planck into tokens rounded to 4 decimal places, followed
+/// by the token symbol (e.g. 1.2346 KSM)
+pub fn format_balance(planck: u128, decimals: u8, symbol: &str) -> String {
+    // u128 holds up to 38 decimal places
+    let decimals = u32::from(decimals.min(38));
+    let precision = decimals.min(4);
+    let scale = 10u128.pow(decimals - precision);
+    let mut rounded = planck / scale;
+    if planck % scale >= scale - scale / 2 {
+        rounded += 1;
+    }
+    let unit = 10u128.pow(precision);
+    let amount = if precision == 0 {
+        rounded.to_string()
+    } else {
+        format!(
+            "{}.{:0width$}",
+            rounded / unit,
+            rounded % unit,
+            width = precision as usize
+        )
+    };
+    if symbol.is_empty() {
+        amount
+    } else {
+        format!("{} {}", amount, symbol)
+    }
+}
+
+/// Returns the block explorer base url used for the links of the network given
+fn explorer_url(network: &str) -> Option<String> {
+    CONFIG.load().explorer_base_url(network)
+}
+
+/// Formats the validator stash in bold with a link to the block explorer, if
+/// any, and the stash label escaped since it is user defined
+fn format_validator_html(explorer_url: Option<&str>, validator: &Validator) -> String {
+    let is_active_desc = if validator.is_active { "🟢" } else { "🔴" };
+    match explorer_url {
+        Some(explorer_url) => format!(
+            "{} <b><a href=\"{}/validator/{}\">{}</a></b>",
+            is_active_desc,
+            explorer_url,
+            validator.stash,
+            escape_html(&validator.name),
+        ),
+        None => format!("{} <b>{}</b>", is_active_desc, escape_html(&validator.name)),
+    }
+}
+
 fn sub_section_validators(report: &mut Report, data: RawData) -> &Report {
+    let templates = templates();
     // Validators info
     for validator in data.validators {
         report.add_break();
 
-        let is_active_desc = if validator.is_active { "🟢" } else { "🔴" };
-        report.add_raw_text(format!(
-            "{} <b><a href=\"https://{}.subscan.io/validator/{}\">{}</a></b>",
-            is_active_desc,
-            data.network.name.to_lowercase(),
-            validator.stash,
-            validator.name,
+        report.add_raw_text(format_validator_html(
+            explorer_url(&data.network.name).as_deref(),
+            &validator,
         ));
+        if let Some(previous_era) = &validator.previous_era {
+            report.add_raw_text(format_era_performance(previous_era));
+        }
         for (i, hook) in validator.hooks.into_iter().enumerate() {
             if i != 0 {
                 report.add_break();
             }
+            let template = match hook.name.as_str() {
+                HOOK_VALIDATOR_STARTS_ACTIVE_NEXT_ERA => Some(TEMPLATE_ACTIVE_NEXT_ERA),
+                HOOK_VALIDATOR_STARTS_INACTIVE_NEXT_ERA => {
+                    Some(TEMPLATE_INACTIVE_NEXT_ERA)
+                }
+                _ => None,
+            };
+            if let Some(template) = template {
+                let vars = vec![
+                    ("network", data.network.name.to_string()),
+                    ("stash", validator.stash.to_string()),
+                    ("stash_name", escape_html(&validator.name)),
+                    ("era", data.session.active_era_index.to_string()),
+                    ("next_era", (data.session.active_era_index + 1).to_string()),
+                    ("session", data.session.current_session_index.to_string()),
+                ];
+                report.add_raw_text(templates.render(template, &vars));
+            }
             let exists_desc = if !hook.filename_exists { "❌" } else { "" };
             report.add_text(format!("🪝 <code>{}</code> {}", hook.filename, exists_desc));
 
@@ -288,15 +612,38 @@ fn sub_section_validators(report: &mut Report, data: RawData) -> &Report {
     report
 }
 
+/// Formats the points and rank of the stash in the era that just ended
+fn format_era_performance(perf: &EraPerformance) -> String {
+    match perf.rank {
+        Some(rank) => format!(
+            "📊 Era {} -> {} points, ranked {} of {}",
+            perf.era, perf.points, rank, perf.active_set
+        ),
+        None => format!("📊 Era {} -> not in the active set", perf.era),
+    }
+}
+
 fn section_init(report: &mut Report, data: RawData) -> &Report {
     report.add_break();
-    report.add_raw_text(format!(
-        "⛓️ <b>{}</b> -> current block <a href=\"https://{}.subscan.io/block/{}\">#{}</a>",
-        data.network.name,
-        data.network.name.to_lowercase(),
-        data.init.block_number,
-        data.init.block_number
-    ));
+    match explorer_url(&data.network.name) {
+        Some(explorer_url) => report.add_raw_text(format!(
+            "⛓️ <b>{}</b> -> current block <a href=\"{}/block/{}\">#{}</a>",
+            data.network.name,
+            explorer_url,
+            data.init.block_number,
+            data.init.block_number
+        )),
+        None => report.add_raw_text(format!(
+            "⛓️ <b>{}</b> -> current block #{}",
+            data.network.name, data.init.block_number
+        )),
+    }
+    if data.init.now > 0 {
+        report.add_raw_text(format!(
+            "🕰️ {}",
+            format_timestamp(data.init.now, CONFIG.load().timezone())
+        ));
+    }
 
     sub_section_validators(report, data)
 }
@@ -304,14 +651,20 @@ fn section_init(report: &mut Report, data: RawData) -> &Report {
 fn section_session(report: &mut Report, data: RawData) -> &Report {
     // Network info
     report.add_break();
-    report.add_raw_text(format!(
-        "⛓️ <b>{}</b> -> {} {} session ({}) of era <b>{}</b>",
-        data.network.name,
-        session_flag(data.session.eras_session_index),
-        session_ordinal_number(data.session.eras_session_index),
-        data.session.current_session_index,
-        data.session.active_era_index
-    ));
+    let vars = vec![
+        ("network", data.network.name.to_string()),
+        ("era", data.session.active_era_index.to_string()),
+        ("session", data.session.current_session_index.to_string()),
+        (
+            "session_flag",
+            session_flag(data.session.eras_session_index),
+        ),
+        (
+            "session_ordinal",
+            session_ordinal_number(data.session.eras_session_index),
+        ),
+    ];
+    report.add_raw_text(templates().render(TEMPLATE_NEW_SESSION, &vars));
 
     sub_section_validators(report, data)
 }
@@ -370,10 +723,10 @@ fn section_slash(report: &mut Report, data: RawData) -> &Report {
         data.network.name.to_lowercase(),
     ));
 
-    let slashed_amount = format!(
-        "{:.4} {}",
-        (data.slash.amount_value) as f64 / 10f64.powi(data.network.token_decimals.into()),
-        data.network.token_symbol
+    let slashed_amount = format_balance(
+        data.slash.amount_value,
+        data.network.token_decimals,
+        &data.network.token_symbol,
     );
 
     // Validators info
@@ -381,13 +734,9 @@ fn section_slash(report: &mut Report, data: RawData) -> &Report {
         if validator.is_slashed {
             report.add_break();
 
-            let is_active_desc = if validator.is_active { "🟢" } else { "🔴" };
-            report.add_raw_text(format!(
-                "{} <b><a href=\"https://{}.subscan.io/validator/{}\">{}</a></b>",
-                is_active_desc,
-                data.network.name.to_lowercase(),
-                validator.stash,
-                validator.name,
+            report.add_raw_text(format_validator_html(
+                explorer_url(&data.network.name).as_deref(),
+                &validator,
             ));
 
             report.add_raw_text(format!(
@@ -418,6 +767,64 @@ fn section_slash(report: &mut Report, data: RawData) -> &Report {
     report
 }
 
+fn section_heartbeat(report: &mut Report, data: RawData) -> &Report {
+    let config = CONFIG.load_full();
+
+    // Network info
+    report.add_break();
+    report.add_raw_text(format!(
+        "⛓️ <b>{}</b> -> 💓 <code>scouty</code> is alive, monitoring {} stash(es).",
+        data.network.name,
+        config.stashes.len()
+    ));
+    report.add_raw_text(format!(
+        "Era {} * Session {}",
+        data.session.active_era_index, data.session.current_session_index
+    ));
+    if let Some(health) = data.health {
+        report.add_raw_text(format!("🌐 Node {}", health.status()));
+    }
+
+    report
+}
+
+fn section_daily(report: &mut Report, data: RawData) -> &Report {
+    // Network info
+    report.add_break();
+    report.add_raw_text(format!(
+        "⛓️ <b>{}</b> -> 📅 Daily report of the last 24h, {} stash(es) monitored.",
+        data.network.name,
+        data.daily.len()
+    ));
+
+    // Stashes activity
+    for summary in data.daily.iter() {
+        report.add_break();
+        let name = if summary.name.is_empty() {
+            &summary.stash
+        } else {
+            &summary.name
+        };
+        report.add_raw_text(format!("<b>{}</b>", escape_html(name)));
+        report.add_raw_text(format!(
+            "🟢 Active in {} of {} sessions * 🎯 {} era points",
+            summary.active_sessions, summary.sessions, summary.era_points
+        ));
+        if summary.incidents.is_empty() {
+            report.add_raw_text("✅ No incidents".to_string());
+        } else {
+            let incidents: Vec<String> = summary
+                .incidents
+                .iter()
+                .map(|(event, total)| format!("{} x{}", event.replace('_', " "), total))
+                .collect();
+            report.add_raw_text(format!("⚠️ Incidents: {}", incidents.join(", ")));
+        }
+    }
+
+    report
+}
+
 fn section_chill(report: &mut Report, data: RawData) -> &Report {
     // Network info
     report.add_break();
@@ -428,19 +835,156 @@ fn section_chill(report: &mut Report, data: RawData) -> &Report {
 
     // Validators info
     for validator in data.validators {
-        if validator.is_chilled {
+        if let Some(chill_kind) = validator.chill_kind {
+            report.add_break();
+
+            report.add_raw_text(format_validator_html(
+                explorer_url(&data.network.name).as_deref(),
+                &validator,
+            ));
+
+            match chill_kind {
+                ChillKind::Voluntary => {
+                    report.add_raw_text(format!("👆 Has been chilled -> 🥶"))
+                }
+                ChillKind::Forced => {
+                    report.add_raw_text(format!("👆 Has been forced to chill -> 🥶"))
+                }
+                ChillKind::Disabled => {
+                    report.add_raw_text(format!("👆 Has been disabled -> 🚫"))
+                }
+            }
+
+            for hook in validator.hooks {
+                let exists_desc = if !hook.filename_exists { "❌" } else { "" };
+                report.add_text(format!(
+                    "🪝 <code>{}</code> {}",
+                    hook.filename, exists_desc
+                ));
+
+                let raw_output = String::from_utf8(hook.stdout).unwrap();
+                // filter lines that start by special character '!'
+                for line in raw_output.lines().filter(|line| line.starts_with("!")) {
+                    report.add_raw_text(format!("‣ {}", line.strip_prefix("!").unwrap()));
+                }
+            }
+        }
+    }
+
+    report
+}
+
+fn section_commission(report: &mut Report, data: RawData) -> &Report {
+    // Network info
+    report.add_break();
+    report.add_raw_text(format!(
+        "⛓️ <b>{}</b> -> 💸 Commission change detected.",
+        data.network.name
+    ));
+
+    // Validators info
+    for validator in data.validators {
+        if let Some((old_commission, new_commission)) = validator.commission_change {
             report.add_break();
 
-            let is_active_desc = if validator.is_active { "🟢" } else { "🔴" };
+            report.add_raw_text(format_validator_html(
+                explorer_url(&data.network.name).as_deref(),
+                &validator,
+            ));
+
             report.add_raw_text(format!(
-                "{} <b><a href=\"https://{}.subscan.io/validator/{}\">{}</a></b>",
-                is_active_desc,
-                data.network.name.to_lowercase(),
-                validator.stash,
-                validator.name,
+                "👆 Commission changed from {} to {}",
+                format_commission(old_commission),
+                format_commission(new_commission)
             ));
 
-            report.add_raw_text(format!("👆 Has been chilled -> 🥶"));
+            for hook in validator.hooks {
+                let exists_desc = if !hook.filename_exists { "❌" } else { "" };
+                report.add_text(format!(
+                    "🪝 <code>{}</code> {}",
+                    hook.filename, exists_desc
+                ));
+
+                let raw_output = String::from_utf8(hook.stdout).unwrap();
+                // filter lines that start by special character '!'
+                for line in raw_output.lines().filter(|line| line.starts_with("!")) {
+                    report.add_raw_text(format!("‣ {}", line.strip_prefix("!").unwrap()));
+                }
+            }
+        }
+    }
+
+    report
+}
+
+fn section_stake(report: &mut Report, data: RawData) -> &Report {
+    // Network info
+    report.add_break();
+    report.add_raw_text(format!(
+        "⛓️ <b>{}</b> -> 🗳️ Nominators or stake change detected in era {}.",
+        data.network.name, data.session.active_era_index
+    ));
+
+    // Validators info
+    for validator in data.validators.iter() {
+        if let Some((old_stake, new_stake)) = validator.stake_change {
+            report.add_break();
+
+            report.add_raw_text(format_validator_html(
+                explorer_url(&data.network.name).as_deref(),
+                validator,
+            ));
+
+            report.add_raw_text(format!(
+                "👥 Nominators changed from {} to {} ({})",
+                old_stake.nominators,
+                new_stake.nominators,
+                format_change(old_stake.nominators as u128, new_stake.nominators as u128)
+            ));
+
+            report.add_raw_text(format!(
+                "💰 Total stake changed from {} to {} ({})",
+                format_balance(
+                    old_stake.total,
+                    data.network.token_decimals,
+                    &data.network.token_symbol
+                ),
+                format_balance(
+                    new_stake.total,
+                    data.network.token_decimals,
+                    &data.network.token_symbol
+                ),
+                format_change(old_stake.total, new_stake.total)
+            ));
+        }
+    }
+
+    report
+}
+
+fn section_rewards(report: &mut Report, data: RawData) -> &Report {
+    // Network info
+    report.add_break();
+    report.add_raw_text(format!(
+        "⛓️ <b>{}</b> -> 💰 Unclaimed rewards found in era {}.",
+        data.network.name, data.session.active_era_index
+    ));
+
+    // Validators info
+    for validator in data.validators {
+        if !validator.unclaimed_eras.is_empty() {
+            report.add_break();
+
+            report.add_raw_text(format_validator_html(
+                explorer_url(&data.network.name).as_deref(),
+                &validator,
+            ));
+
+            report.add_raw_text(format!(
+                "⏳ {} eras left to claim -> {}",
+                validator.unclaimed_eras.len(),
+                format_eras(&validator.unclaimed_eras)
+            ));
 
             for hook in validator.hooks {
                 let exists_desc = if !hook.filename_exists { "❌" } else { "" };
@@ -461,6 +1005,58 @@ fn section_chill(report: &mut Report, data: RawData) -> &Report {
     report
 }
 
+fn section_stash_slashed(report: &mut Report, data: RawData) -> &Report {
+    // Network info
+    report.add_break();
+    report.add_raw_text(format!(
+        "⛓️ <b>{}</b> -> 🚨 <b>High priority</b> 🚨 Stash slashed in era {}!",
+        data.network.name, data.session.active_era_index,
+    ));
+
+    let slashed_amount = format_balance(
+        data.slash.amount_value,
+        data.network.token_decimals,
+        &data.network.token_symbol,
+    );
+
+    // Validators info
+    for validator in data.validators {
+        if validator.is_slashed {
+            report.add_break();
+
+            report.add_raw_text(format_validator_html(
+                explorer_url(&data.network.name).as_deref(),
+                &validator,
+            ));
+
+            report.add_raw_text(format!(
+                "🤬 Slashed amount -> 💸 <b>{}</b>",
+                slashed_amount,
+            ));
+        }
+    }
+
+    // Hook
+    report.add_break();
+    let exists_desc = if !data.slash.hook.filename_exists {
+        "❌"
+    } else {
+        ""
+    };
+    report.add_text(format!(
+        "🪝 <code>{}</code> {}",
+        data.slash.hook.filename, exists_desc
+    ));
+
+    let raw_output = String::from_utf8(data.slash.hook.stdout).unwrap();
+    // filter lines that start by special character '!'
+    for line in raw_output.lines().filter(|line| line.starts_with("!")) {
+        report.add_raw_text(format!("‣ {}", line.strip_prefix("!").unwrap()));
+    }
+
+    report
+}
+
 fn section_offline(report: &mut Report, data: RawData) -> &Report {
     // Network info
     report.add_break();
@@ -474,13 +1070,9 @@ fn section_offline(report: &mut Report, data: RawData) -> &Report {
         if validator.is_offline {
             report.add_break();
 
-            let is_active_desc = if validator.is_active { "🟢" } else { "🔴" };
-            report.add_raw_text(format!(
-                "{} <b><a href=\"https://{}.subscan.io/validator/{}\">{}</a></b>",
-                is_active_desc,
-                data.network.name.to_lowercase(),
-                validator.stash,
-                validator.name,
+            report.add_raw_text(format_validator_html(
+                explorer_url(&data.network.name).as_deref(),
+                &validator,
             ));
 
             report.add_raw_text(format!("👆 Has been seen offline -> ⛑️"));
@@ -521,3 +1113,155 @@ fn session_ordinal_number(index: u32) -> String {
         _ => format!("{}th", index),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MATRIX_EVENTS;
+
+    #[test]
+    fn it_deserializes_the_node_health() {
+        let health: NodeHealth = serde_json::from_str(
+            r#"{"peers":42,"isSyncing":false,"shouldHavePeers":true}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            health,
+            NodeHealth {
+                peers: 42,
+                is_syncing: false,
+                should_have_peers: true,
+            }
+        );
+        assert_eq!(health.status(), "42 peers * synced");
+
+        let health: NodeHealth =
+            serde_json::from_str(r#"{"peers":3,"isSyncing":true}"#).unwrap();
+        assert_eq!(health.status(), "3 peers * syncing");
+    }
+
+    #[test]
+    fn it_names_the_section_events() {
+        for section in [
+            Section::Init,
+            Section::Session,
+            Section::Slash,
+            Section::Chill,
+            Section::Offline,
+            Section::Democracy,
+            Section::Heartbeat,
+            Section::Commission,
+            Section::Stake,
+            Section::StashSlashed,
+            Section::Rewards,
+            Section::Daily,
+        ] {
+            assert!(MATRIX_EVENTS.contains(&section.event()));
+        }
+    }
+
+    #[test]
+    fn it_marks_only_the_chilled_stash_defined() {
+        let mut validators: Validators = vec![
+            Validator::new(AccountId32::new([1u8; 32])),
+            Validator::new(AccountId32::new([2u8; 32])),
+        ];
+        validators[1].is_disabled = true;
+
+        let who = AccountId32::new([3u8; 32]);
+        assert!(mark_chilled(&mut validators, &who, false).is_none());
+        assert!(validators.iter().all(|v| !v.is_chilled));
+
+        let who = AccountId32::new([1u8; 32]);
+        assert!(mark_chilled(&mut validators, &who, false).is_some());
+        assert!(validators[0].is_chilled);
+        assert_eq!(validators[0].chill_kind, Some(ChillKind::Voluntary));
+        assert!(!validators[1].is_chilled);
+
+        let who = AccountId32::new([2u8; 32]);
+        assert!(mark_chilled(&mut validators, &who, false).is_some());
+        assert_eq!(validators[1].chill_kind, Some(ChillKind::Forced));
+    }
+
+    #[test]
+    fn it_gets_the_chill_kind() {
+        assert_eq!(ChillKind::from_state(false, false), ChillKind::Voluntary);
+        assert_eq!(ChillKind::from_state(true, false), ChillKind::Forced);
+        assert_eq!(ChillKind::from_state(false, true), ChillKind::Forced);
+        assert_eq!(ChillKind::Forced.to_string(), "forced");
+    }
+
+    #[test]
+    fn it_formats_the_era_performance() {
+        let perf = EraPerformance {
+            era: 4170,
+            points: 3200,
+            rank: Some(3),
+            active_set: 297,
+        };
+        assert_eq!(
+            format_era_performance(&perf),
+            "📊 Era 4170 -> 3200 points, ranked 3 of 297"
+        );
+        let perf = EraPerformance {
+            era: 4170,
+            active_set: 297,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_era_performance(&perf),
+            "📊 Era 4170 -> not in the active set"
+        );
+    }
+
+    #[test]
+    fn it_formats_the_timestamps_in_the_timezone() {
+        // 2022-04-15 05:20:00 UTC
+        let now = 1_650_000_000_000;
+        assert_eq!(format_timestamp(now, Tz::UTC), "2022-04-15 05:20:00 UTC");
+        assert_eq!(
+            format_timestamp(now, Tz::Europe__Lisbon),
+            "2022-04-15 06:20:00 WEST"
+        );
+        assert_eq!(
+            format_timestamp(now, Tz::Europe__Berlin),
+            "2022-04-15 07:20:00 CEST"
+        );
+        // in winter time
+        assert_eq!(
+            format_timestamp(1_640_995_200_000, Tz::Europe__Berlin),
+            "2022-01-01 01:00:00 CET"
+        );
+    }
+
+    #[test]
+    fn it_formats_the_balances() {
+        assert_eq!(format_balance(1_234_567_890_000, 12, "KSM"), "1.2346 KSM");
+        assert_eq!(format_balance(1_234_549_999_999, 12, "KSM"), "1.2345 KSM");
+        assert_eq!(format_balance(999_950_000_000, 12, "KSM"), "1.0000 KSM");
+        assert_eq!(format_balance(10_000_000_000, 10, "DOT"), "1.0000 DOT");
+        assert_eq!(format_balance(0, 12, "WND"), "0.0000 WND");
+        assert_eq!(format_balance(1234, 2, ""), "12.34");
+        assert_eq!(format_balance(5, 0, "UNIT"), "5 UNIT");
+    }
+
+    #[test]
+    fn it_escapes_the_stash_labels_in_html() {
+        let mut validator = Validator::new(AccountId32::new([1u8; 32]));
+        validator.name = "<b>Bob</b> & \"Co\"".to_string();
+        validator.is_active = true;
+        let html = format_validator_html(Some("https://kusama.subscan.io"), &validator);
+        assert!(html.starts_with("🟢 <b><a href=\"https://kusama.subscan.io/validator/"));
+        assert!(html.ends_with("\">&lt;b&gt;Bob&lt;/b&gt; &amp; &quot;Co&quot;</a></b>"));
+        // the plain text keeps the label as defined
+        assert_eq!(
+            strip_html(&html).split_once(' ').unwrap().1,
+            "<b>Bob</b> & \"Co\""
+        );
+        // custom chains without a block explorer have no link
+        assert_eq!(
+            format_validator_html(None, &validator),
+            "🟢 <b>&lt;b&gt;Bob&lt;/b&gt; &amp; &quot;Co&quot;</b>"
+        );
+    }
+}
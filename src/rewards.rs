@@ -0,0 +1,150 @@
+// The MIT License (MIT)
+// Copyright © 2021 Aukbit Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+use serde::Deserialize;
+
+/// Points earned by the stash in an era that ended and its rank among the
+/// validators of the active set. The stash has no rank if it was not active.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct EraPerformance {
+    pub era: u32,
+    pub points: u32,
+    pub rank: Option<u32>,
+    pub active_set: u32,
+}
+
+impl EraPerformance {
+    /// Ranks the stash by the era reward points of each validator, validators
+    /// with the same points share the same rank (e.g. 1, 2, 2, 4)
+    pub fn from_points<T: PartialEq>(
+        era: u32,
+        stash: &T,
+        points: &[(T, u32)],
+    ) -> EraPerformance {
+        let active_set = points.len() as u32;
+        match points.iter().find(|(s, _)| s == stash) {
+            Some((_, stash_points)) => EraPerformance {
+                era,
+                points: *stash_points,
+                rank: Some(
+                    1 + points.iter().filter(|(_, p)| p > stash_points).count() as u32,
+                ),
+                active_set,
+            },
+            None => EraPerformance {
+                era,
+                active_set,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Returns the eras within the lookback window before the active era in which
+/// the stash earned reward points but its rewards were not claimed yet. The
+/// active era is excluded since its rewards can only be claimed once it ends.
+pub fn unclaimed_eras(
+    active_era: u32,
+    lookback_eras: u32,
+    claimed_eras: &[u32],
+    rewarded_eras: &[u32],
+) -> Vec<u32> {
+    lookback_window(active_era, lookback_eras)
+        .filter(|era| rewarded_eras.contains(era) && !claimed_eras.contains(era))
+        .collect()
+}
+
+/// Eras to look back from the active era, oldest first
+pub fn lookback_window(active_era: u32, lookback_eras: u32) -> std::ops::Range<u32> {
+    active_era.saturating_sub(lookback_eras)..active_era
+}
+
+/// Formats the eras as a comma separated list (e.g. 1001, 1002)
+pub fn format_eras(eras: &[u32]) -> String {
+    eras.iter()
+        .map(|era| era.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_detects_unclaimed_eras() {
+        // stash rewarded in every era but the 996, with the era 998 claimed
+        let rewarded_eras = vec![994, 995, 997, 998, 999, 1000];
+        let claimed_eras = vec![990, 994, 995, 998];
+        assert_eq!(
+            unclaimed_eras(1000, 6, &claimed_eras, &rewarded_eras),
+            vec![997, 999]
+        );
+        // eras out of the lookback window are ignored
+        assert_eq!(
+            unclaimed_eras(1000, 2, &claimed_eras, &rewarded_eras),
+            vec![999]
+        );
+        // nothing to claim if every rewarded era has been claimed
+        assert!(unclaimed_eras(1000, 6, &rewarded_eras, &rewarded_eras).is_empty());
+        // the lookback window never goes below era 0
+        assert_eq!(unclaimed_eras(2, 84, &[], &[0, 1, 2]), vec![0, 1]);
+        assert_eq!(format_eras(&[997, 999]), "997, 999");
+    }
+
+    #[test]
+    fn it_ranks_the_era_points() {
+        let points = vec![
+            ("alice", 3200),
+            ("bob", 4800),
+            ("charlie", 3200),
+            ("dave", 5600),
+            ("eve", 1400),
+        ];
+        let perf = EraPerformance::from_points(4170, &"alice", &points);
+        assert_eq!(
+            perf,
+            EraPerformance {
+                era: 4170,
+                points: 3200,
+                rank: Some(3),
+                active_set: 5,
+            }
+        );
+        // stashes with the same points share the rank
+        assert_eq!(
+            EraPerformance::from_points(4170, &"charlie", &points).rank,
+            Some(3)
+        );
+        assert_eq!(
+            EraPerformance::from_points(4170, &"dave", &points).rank,
+            Some(1)
+        );
+        assert_eq!(
+            EraPerformance::from_points(4170, &"eve", &points).rank,
+            Some(5)
+        );
+        // a stash that was not active is not ranked
+        let perf = EraPerformance::from_points(4170, &"ferdie", &points);
+        assert_eq!(perf.rank, None);
+        assert_eq!(perf.points, 0);
+        assert_eq!(perf.active_set, 5);
+    }
+}
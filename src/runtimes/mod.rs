@@ -21,6 +21,23 @@
 
 #![allow(clippy::all)]
 
+/// Queries a storage item, e.g. `storage!(api, session, current_index(None))`, for
+/// at most the request timeout and records how long it took under its name, e.g.
+/// `session.current_index`
+macro_rules! storage {
+    ($api:expr, $pallet:ident, $item:ident($($arg:expr),* $(,)?)) => {
+        crate::concurrency::with_timeout(
+            crate::config::CONFIG.load().request_timeout(),
+            concat!("Storage query ", stringify!($pallet), ".", stringify!($item)),
+            crate::concurrency::timed(
+                &crate::metrics::METRICS,
+                concat!(stringify!($pallet), ".", stringify!($item)),
+                $api.storage().$pallet().$item($($arg),*),
+            ),
+        )
+    };
+}
+
 pub mod kusama;
 pub mod polkadot;
 pub mod support;
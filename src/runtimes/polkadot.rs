@@ -19,26 +19,55 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+use crate::api::MONITORED;
 use crate::authority::{decode_authority_index, AuthorityIndex, AuthorityRecords};
-use crate::config::CONFIG;
+use crate::backoff;
+use crate::changes::{self, LAST_ACTIVE};
+use crate::commission::CommissionRecords;
+use crate::concurrency::{map_bounded, timed, with_timeout};
+use crate::config::{EventKind, HeadMode, StashConfig, CONFIG};
+use crate::confirmation::Confirmations;
+use crate::dedup::{FIRED_HOOKS, STARTUP_CYCLE};
+use crate::digest::{DailySchedule, DAILY_REPORT};
 use crate::errors::ScoutyError;
+use crate::health;
+use crate::heartbeat::Heartbeat;
 use crate::hooks::{
-    Hook, HOOK_DEMOCRACY_STARTED, HOOK_INIT, HOOK_NEW_ERA, HOOK_NEW_SESSION,
-    HOOK_VALIDATOR_CHILLED, HOOK_VALIDATOR_OFFLINE, HOOK_VALIDATOR_SLASHED,
+    Hook, HookContext, HOOK_DEMOCRACY_STARTED, HOOK_INIT, HOOK_NEW_ERA, HOOK_NEW_SESSION,
+    HOOK_STASH_SLASHED, HOOK_UNCLAIMED_REWARDS, HOOK_VALIDATOR_CHILLED,
+    HOOK_VALIDATOR_COMMISSION_CHANGED, HOOK_VALIDATOR_OFFLINE, HOOK_VALIDATOR_SLASHED,
     HOOK_VALIDATOR_STARTS_ACTIVE_NEXT_ERA, HOOK_VALIDATOR_STARTS_INACTIVE_NEXT_ERA,
 };
+use crate::metrics::{METRICS, RUNTIME_STATS};
+use crate::pagerduty::cleared_incidents;
 use crate::para::ParaRecords;
 use crate::report::{
-    Init, Network, Points, RawData, Referendum, Report, Section, Session, Slash,
-    Validator, Validators,
+    mark_chilled, ChillKind, Init, Network, NodeHealth, Points, RawData, Referendum,
+    Report, Section, Session, Slash, Validator, Validators,
 };
-use crate::scouty::{get_account_id_from_storage_key, Scouty};
+use crate::rewards::{format_eras, lookback_window, unclaimed_eras, EraPerformance};
+use crate::scouty::{
+    get_account_id_from_storage_key, is_preferred_endpoint_available, Scouty,
+};
+use crate::shutdown;
+use crate::stake::{Stake, StakeRecords};
 use crate::stats;
+use crate::webhook::{
+    Notification, EVENT_NEW_SESSION, EVENT_VALIDATOR_CHILLED, EVENT_VALIDATOR_SLASHED,
+    EVENT_VALIDATOR_STARTS_ACTIVE_NEXT_ERA, EVENT_VALIDATOR_STARTS_INACTIVE_NEXT_ERA,
+};
 use async_recursion::async_recursion;
+use chrono::Utc;
 use codec::Encode;
 use futures::StreamExt;
 use log::{debug, info};
-use std::{collections::BTreeMap, convert::TryInto, result::Result, str::FromStr};
+use std::{
+    collections::BTreeMap,
+    convert::TryInto,
+    result::Result,
+    str::FromStr,
+    time::{Duration, Instant},
+};
 use subxt::{
     sp_core::hexdisplay::HexDisplay, sp_runtime::AccountId32, DefaultConfig,
     PolkadotExtrinsicParams,
@@ -53,6 +82,7 @@ mod node_runtime {}
 
 use node_runtime::{
     democracy::events::Started, im_online::events::SomeOffline,
+    offences::events::Offence,
     runtime_types::frame_support::storage::bounded_vec::BoundedVec,
     session::events::NewSession, staking::events::Chilled, staking::events::Slashed,
 };
@@ -76,54 +106,586 @@ pub async fn init_and_subscribe_on_chain_events(
     let mut para_records = ParaRecords::new();
     init_para_records(&scouty, &mut para_records).await?;
 
+    // Initialize commission records
+    let mut commission_records = CommissionRecords::new();
+    init_commission_records(&scouty, &mut commission_records).await?;
+
+    // Stake records are stored every new era
+    let mut stake_records = StakeRecords::new();
+
     // Start by calling init hook
     try_init_hook(&scouty, &authority_records, &para_records).await?;
+    STARTUP_CYCLE.end();
+
+    // A single monitoring pass evaluates the current session once and exits
+    if CONFIG.load().run_once {
+        return try_run_current_session_hooks(
+            &scouty,
+            &mut authority_records,
+            &mut para_records,
+        )
+        .await;
+    }
     //
-    info!("Subscribe on-chain finalized events");
-    let mut sub = api.events().subscribe_finalized().await?;
+    let head_mode = CONFIG.load().head_mode;
+    match head_mode {
+        HeadMode::Finalized => info!("Subscribe on-chain finalized events"),
+        HeadMode::Latest => info!(
+            "Subscribe on-chain latest events (NOTE: events may be reverted by a reorg)"
+        ),
+    }
+    let request_timeout = CONFIG.load().request_timeout();
+    let mut sub = match head_mode {
+        HeadMode::Finalized => with_timeout(
+            request_timeout,
+            "Finalized events subscription",
+            api.events().subscribe_finalized(),
+        )
+        .await?
+        .boxed(),
+        HeadMode::Latest => with_timeout(
+            request_timeout,
+            "Latest events subscription",
+            api.events().subscribe(),
+        )
+        .await?
+        .boxed(),
+    };
+    let mut heartbeat = Heartbeat::new(
+        Duration::from_secs(CONFIG.load().heartbeat_interval_hours * 3600),
+        Instant::now(),
+    );
+    let mut daily_schedule = DailySchedule::new(
+        CONFIG.load().daily_report_hour(),
+        Utc::now().with_timezone(&CONFIG.load().timezone()),
+    );
+    let mut confirmations = Confirmations::new(CONFIG.load().confirmation_blocks);
     while let Some(events) = sub.next().await {
+        // Stop between blocks so that hooks are never interrupted
+        if shutdown::is_requested() {
+            info!(
+                "Shutdown requested, unsubscribe on-chain {} events",
+                head_mode
+            );
+            return Ok(());
+        }
         let events = events?;
+        health::touch();
+        // The connection is healthy again, restart the reconnection delay
+        backoff::reset();
+        // Reconnect once a higher priority endpoint is available again
+        if is_preferred_endpoint_available().await {
+            info!("Unsubscribe on-chain {} events to reconnect", head_mode);
+            return Ok(());
+        }
         let block_hash = events.block_hash();
 
-        if let Some(signed_block) = api.client.rpc().block(Some(block_hash)).await? {
-            if let Some(authority_index) = decode_authority_index(&signed_block) {
-                let block_number = signed_block.block.header.number;
-
-                // Event --> session::NewSession
-                let event = events.find_first::<NewSession>()?;
-                try_run_session_hooks(
-                    &scouty,
-                    event,
-                    &mut authority_records,
-                    block_number,
-                    authority_index,
-                    &mut para_records,
-                )
+        let block = with_timeout(
+            request_timeout,
+            "Block request",
+            timed(
+                &METRICS,
+                "chain_getBlock",
+                api.client.rpc().block(Some(block_hash)),
+            ),
+        );
+        if let Some(signed_block) = block.await? {
+            // Events are only acted on once confirmed by the blocks that follow,
+            // the blocks replaced by a reorg in the meantime are dropped
+            let block_number = signed_block.block.header.number;
+            let confirmed =
+                confirmations.push(block_number, block_hash, (events, signed_block));
+            let confirmed = confirmations
+                .retain_canonical(confirmed, |block_number| {
+                    with_timeout(
+                        request_timeout,
+                        "Block hash request",
+                        timed(
+                            &METRICS,
+                            "chain_getBlockHash",
+                            api.client.rpc().block_hash(Some(block_number.into())),
+                        ),
+                    )
+                })
                 .await?;
+            for (events, signed_block) in confirmed {
+                if let Some(authority_index) = decode_authority_index(&signed_block) {
+                    let block_number = signed_block.block.header.number;
+
+                    // Event --> session::NewSession
+                    let event = events.find_first::<NewSession>()?;
+                    try_run_session_hooks(
+                        &scouty,
+                        event,
+                        &mut authority_records,
+                        block_number,
+                        authority_index,
+                        &mut para_records,
+                    )
+                    .await?;
+
+                    // Event --> session::NewSession (commission changes)
+                    let event = events.find_first::<NewSession>()?;
+                    try_run_commission_changed_hook(
+                        &scouty,
+                        event,
+                        &mut commission_records,
+                    )
+                    .await?;
+
+                    // Event --> session::NewSession (nominators and stake changes)
+                    let event = events.find_first::<NewSession>()?;
+                    try_run_stake_changed_notification(
+                        &scouty,
+                        event,
+                        &mut stake_records,
+                    )
+                    .await?;
+
+                    // Event --> session::NewSession (unclaimed rewards)
+                    let event = events.find_first::<NewSession>()?;
+                    try_run_unclaimed_rewards_hook(&scouty, event).await?;
+
+                    // Event --> staking::Slashed (applied in batches at era start)
+                    let slashed_events: Vec<Slashed> =
+                        events.find::<Slashed>().collect::<Result<_, _>>()?;
+                    let slashed: Vec<AccountId32> =
+                        slashed_events.iter().map(|e| e.0.clone()).collect();
+                    try_run_slashed_hooks(&scouty, slashed_events).await?;
+
+                    // Event --> staking::Chilled
+                    let event = events.find_first::<Chilled>()?;
+                    try_run_staking_chilled_hook(&scouty, event, slashed).await?;
+
+                    // Event --> offences::Offence (disabled stashes)
+                    let event = events.find_first::<Offence>()?;
+                    try_run_validator_disabled_hook(&scouty, event).await?;
+
+                    // Event --> im_online::SomeOffline
+                    let event = events.find_first::<SomeOffline>()?;
+                    try_run_im_online_some_offline_hook(&scouty, event).await?;
+
+                    // Event --> democracy::Started
+                    let event = events.find_first::<Started>()?;
+                    try_run_democracy_started_hook(&scouty, event).await?;
+
+                    // Track authority record
+                    authority_records
+                        .insert_record(block_number, Some(authority_index))?;
+
+                    // Send heartbeat if due
+                    if heartbeat.is_due(Instant::now()) {
+                        try_send_heartbeat(&scouty).await?;
+                    }
+
+                    // Send daily report if due
+                    let now = Utc::now().with_timezone(&CONFIG.load().timezone());
+                    if daily_schedule.is_due(now) {
+                        try_send_daily_report(&scouty).await?;
+                    }
+                }
+            }
+        }
+    }
+    // If subscription has closed for some reason await and subscribe again
+    Err(ScoutyError::SubscriptionFinished)
+}
+
+/// Prints the status of each stash defined in config, used by the `view` command
+pub async fn view(scouty: &Scouty) -> Result<(), ScoutyError> {
+    let client = scouty.client().clone();
+    let api = client.to_runtime_api::<Api>();
+
+    let current_session_index = storage!(api, session, current_index(None)).await?;
+    let validators = collect_validators_data(&scouty).await?;
+
+    println!("Session {}", current_session_index);
+    if let Some(health) = NodeHealth::load(scouty.client()).await {
+        println!("Node {}", health.status());
+    }
+    for v in validators.iter() {
+        println!("{}", v.status());
+    }
+    Ok(())
+}
+
+async fn try_send_daily_report(scouty: &Scouty) -> Result<(), ScoutyError> {
+    let network = Network::load(scouty.client()).await?;
+    debug!("network {:?}", network);
+
+    // Prepare notification report with the activity since the last one
+    let daily = DAILY_REPORT.lock().unwrap().take();
+    let data = RawData {
+        network,
+        daily,
+        section: Section::Daily,
+        ..Default::default()
+    };
+
+    let report = Report::from(data);
+    scouty.send_report(&report).await?;
+
+    Ok(())
+}
+
+async fn try_send_heartbeat(scouty: &Scouty) -> Result<(), ScoutyError> {
+    let client = scouty.client();
+    let api = client.clone().to_runtime_api::<Api>();
+
+    let network = Network::load(client).await?;
+    debug!("network {:?}", network);
+
+    let current_session_index = storage!(api, session, current_index(None)).await?;
+    let session = collect_session_data(&scouty, current_session_index).await?;
+
+    // Node health is only included if enabled and supported by the endpoint
+    let health = if CONFIG.load().heartbeat_node_health {
+        NodeHealth::load(client).await
+    } else {
+        None
+    };
+
+    // Prepare notification report
+    let data = RawData {
+        network,
+        session,
+        health,
+        section: Section::Heartbeat,
+        ..Default::default()
+    };
+
+    let report = Report::from(data);
+    scouty.send_report(&report).await?;
+
+    Ok(())
+}
+
+async fn init_commission_records(
+    scouty: &Scouty,
+    commission_records: &mut CommissionRecords,
+) -> Result<(), ScoutyError> {
+    let config = CONFIG.load_full();
+
+    let stashes = config
+        .stashes
+        .iter()
+        .map(|stash_config| AccountId32::from_str(&stash_config.address))
+        .collect::<Result<Vec<AccountId32>, _>>()?;
+
+    // Query each stash commission concurrently
+    let commissions =
+        map_bounded(stashes.iter(), config.max_concurrent_queries, |stash| {
+            get_commission(&scouty, stash)
+        })
+        .await;
+
+    for (stash, commission) in stashes.iter().zip(commissions) {
+        commission_records.update(&stash.to_string(), commission?);
+    }
+    Ok(())
+}
+
+async fn get_commission(
+    scouty: &Scouty,
+    stash: &AccountId32,
+) -> Result<u32, ScoutyError> {
+    let client = scouty.client().clone();
+    let api = client.to_runtime_api::<Api>();
+
+    let prefs = storage!(api, staking, validators(stash, None)).await?;
+    let node_runtime::runtime_types::sp_arithmetic::per_things::Perbill(c) =
+        prefs.commission;
+    Ok(c)
+}
+
+async fn try_run_commission_changed_hook(
+    scouty: &Scouty,
+    event: Option<NewSession>,
+    commission_records: &mut CommissionRecords,
+) -> Result<(), ScoutyError> {
+    if let Some(event) = event {
+        let client = scouty.client();
+        let config = CONFIG.load_full();
+
+        if !config.is_event_enabled(EventKind::Commission) {
+            return Ok(());
+        }
+
+        // Commission is only verified every new era
+        let session = collect_session_data(&scouty, event.session_index).await?;
+        if session.eras_session_index != 1 {
+            return Ok(());
+        }
+
+        // Collect validators info based on config stashes
+        let mut validators = collect_validators_data(&scouty).await?;
+
+        // Query each stash commission concurrently
+        let commissions =
+            map_bounded(validators.iter(), config.max_concurrent_queries, |v| {
+                get_commission(&scouty, &v.stash)
+            })
+            .await;
+
+        // Try to run hooks for each stash
+        for (v, commission) in validators.iter_mut().zip(commissions) {
+            let c = commission?;
+
+            if let Some(old_commission) =
+                commission_records.update(&v.stash.to_string(), c)
+            {
+                v.commission_change = Some((old_commission, c));
+
+                // Try HOOK_VALIDATOR_COMMISSION_CHANGED
+                let args = vec![
+                    v.stash.to_string(),
+                    v.name.to_string(),
+                    old_commission.to_string(),
+                    c.to_string(),
+                ];
+
+                let hook = Hook::try_run_with_context(
+                    HOOK_VALIDATOR_COMMISSION_CHANGED,
+                    &config.hook_validator_commission_changed_path,
+                    args,
+                    &HookContext {
+                        stash: v.stash.to_string(),
+                        stash_name: v.name.to_string(),
+                        era: session.active_era_index,
+                        session: session.current_session_index,
+                        event: HOOK_VALIDATOR_COMMISSION_CHANGED.to_string(),
+                        is_active: v.is_active,
+                        old_commission: Some(old_commission),
+                        new_commission: Some(c),
+                        ..Default::default()
+                    },
+                )?;
+                v.hooks.push(hook);
+            }
+        }
+
+        debug!("validators {:?}", validators);
+
+        // NOTE: Only send commission message if the commission of one of the
+        // stashes defined in config changed
+        if validators.iter().any(|v| v.commission_change.is_some()) {
+            let network = Network::load(client).await?;
+            debug!("network {:?}", network);
 
-                // Event --> staking::Slashed
-                let event = events.find_first::<Slashed>()?;
-                try_run_staking_slashed_hook(&scouty, event).await?;
+            // Prepare notification report
+            let data = RawData {
+                network,
+                session,
+                validators,
+                section: Section::Commission,
+                ..Default::default()
+            };
 
-                // Event --> staking::Chilled
-                let event = events.find_first::<Chilled>()?;
-                try_run_staking_chilled_hook(&scouty, event).await?;
+            let report = Report::from(data);
+            scouty.send_report(&report).await?;
+        }
+    }
+    Ok(())
+}
 
-                // Event --> im_online::SomeOffline
-                let event = events.find_first::<SomeOffline>()?;
-                try_run_im_online_some_offline_hook(&scouty, event).await?;
+async fn try_run_stake_changed_notification(
+    scouty: &Scouty,
+    event: Option<NewSession>,
+    stake_records: &mut StakeRecords,
+) -> Result<(), ScoutyError> {
+    if let Some(event) = event {
+        let client = scouty.client();
+        let config = CONFIG.load_full();
 
-                // Event --> democracy::Started
-                let event = events.find_first::<Started>()?;
-                try_run_democracy_started_hook(&scouty, event).await?;
+        if config.stake_change_threshold_percent == 0 {
+            return Ok(());
+        }
+
+        // Stake is only verified every new era
+        let session = collect_session_data(&scouty, event.session_index).await?;
+        if session.eras_session_index != 1 {
+            return Ok(());
+        }
 
-                // Track authority record
-                authority_records.insert_record(block_number, Some(authority_index))?;
+        // Collect validators info based on config stashes
+        let mut validators = collect_validators_data(&scouty).await?;
+
+        // Only the active stashes are backed in the era
+        for v in validators.iter().filter(|v| !v.is_active) {
+            stake_records.remove(&v.stash.to_string());
+        }
+
+        // Query each active stash exposure concurrently
+        let exposures = map_bounded(
+            validators.iter().filter(|v| v.is_active),
+            config.max_concurrent_queries,
+            |v| get_active_nominators(&scouty, session.active_era_index, &v.stash),
+        )
+        .await;
+
+        for (v, exposure) in validators.iter_mut().filter(|v| v.is_active).zip(exposures)
+        {
+            let (total, _, nominators, _) = exposure?;
+            let stake = Stake {
+                nominators: nominators.len() as u32,
+                total,
+            };
+            if let Some(old_stake) = stake_records.update(
+                &v.stash.to_string(),
+                stake,
+                config.stake_change_threshold_percent,
+            ) {
+                v.stake_change = Some((old_stake, stake));
             }
         }
+
+        debug!("validators {:?}", validators);
+
+        // NOTE: Only send stake message if the nominators or the total backing of
+        // one of the stashes defined in config shifted above the threshold
+        if validators.iter().any(|v| v.stake_change.is_some()) {
+            let network = Network::load(client).await?;
+            debug!("network {:?}", network);
+
+            // Prepare notification report
+            let data = RawData {
+                network,
+                session,
+                validators,
+                section: Section::Stake,
+                ..Default::default()
+            };
+
+            let report = Report::from(data);
+            scouty.send_report(&report).await?;
+        }
     }
-    // If subscription has closed for some reason await and subscribe again
-    Err(ScoutyError::SubscriptionFinished)
+    Ok(())
+}
+
+async fn get_claimed_eras(
+    scouty: &Scouty,
+    stash: &AccountId32,
+) -> Result<Vec<u32>, ScoutyError> {
+    let client = scouty.client().clone();
+    let api = client.to_runtime_api::<Api>();
+
+    if let Some(controller) = storage!(api, staking, bonded(stash, None)).await? {
+        if let Some(ledger) = storage!(api, staking, ledger(&controller, None)).await? {
+            return Ok(ledger.claimed_rewards);
+        }
+    }
+    Ok(Vec::new())
+}
+
+async fn try_run_unclaimed_rewards_hook(
+    scouty: &Scouty,
+    event: Option<NewSession>,
+) -> Result<(), ScoutyError> {
+    if let Some(event) = event {
+        let client = scouty.client();
+        let api = client.clone().to_runtime_api::<Api>();
+        let config = CONFIG.load_full();
+
+        if !config.is_event_enabled(EventKind::Rewards) {
+            return Ok(());
+        }
+
+        // Unclaimed rewards are only verified every new era
+        let session = collect_session_data(&scouty, event.session_index).await?;
+        if session.eras_session_index != 1 {
+            return Ok(());
+        }
+
+        // Fetch the reward points of each era within the lookback only once
+        let mut eras_reward_points = Vec::new();
+        for era in lookback_window(session.active_era_index, config.rewards_lookback_eras)
+        {
+            let era_reward_points =
+                storage!(api, staking, eras_reward_points(&era, None)).await?;
+            eras_reward_points.push((era, era_reward_points));
+        }
+
+        // Collect validators info based on config stashes
+        let mut validators = collect_validators_data(&scouty).await?;
+
+        // Query each stash claimed eras concurrently
+        let claimed =
+            map_bounded(validators.iter(), config.max_concurrent_queries, |v| {
+                get_claimed_eras(&scouty, &v.stash)
+            })
+            .await;
+
+        // Try to run hooks for each stash
+        for (v, claimed_eras) in validators.iter_mut().zip(claimed) {
+            let claimed_eras = claimed_eras?;
+
+            // Only the eras in which the stash earned points have rewards to claim
+            let rewarded_eras: Vec<u32> = eras_reward_points
+                .iter()
+                .filter(|(_, points)| {
+                    points.individual.iter().any(|(s, _)| s == &v.stash)
+                })
+                .map(|(era, _)| *era)
+                .collect();
+
+            v.unclaimed_eras = unclaimed_eras(
+                session.active_era_index,
+                config.rewards_lookback_eras,
+                &claimed_eras,
+                &rewarded_eras,
+            );
+            if v.unclaimed_eras.is_empty() {
+                continue;
+            }
+
+            // Try HOOK_UNCLAIMED_REWARDS
+            let args = vec![
+                v.stash.to_string(),
+                v.name.to_string(),
+                format_eras(&v.unclaimed_eras),
+            ];
+
+            let hook = Hook::try_run_with_context(
+                HOOK_UNCLAIMED_REWARDS,
+                &config.hook_unclaimed_rewards_path,
+                args,
+                &HookContext {
+                    stash: v.stash.to_string(),
+                    stash_name: v.name.to_string(),
+                    era: session.active_era_index,
+                    session: session.current_session_index,
+                    event: HOOK_UNCLAIMED_REWARDS.to_string(),
+                    is_active: v.is_active,
+                    unclaimed_eras: v.unclaimed_eras.clone(),
+                    ..Default::default()
+                },
+            )?;
+            v.hooks.push(hook);
+        }
+
+        debug!("validators {:?}", validators);
+
+        // NOTE: Only send rewards message if one of the stashes defined in config
+        // has rewards left to claim
+        if validators.iter().any(|v| !v.unclaimed_eras.is_empty()) {
+            let network = Network::load(client).await?;
+            debug!("network {:?}", network);
+
+            // Prepare notification report
+            let data = RawData {
+                network,
+                session,
+                validators,
+                section: Section::Rewards,
+                ..Default::default()
+            };
+
+            let report = Report::from(data);
+            scouty.send_report(&report).await?;
+        }
+    }
+    Ok(())
 }
 
 async fn try_init_hook(
@@ -133,17 +695,17 @@ async fn try_init_hook(
 ) -> Result<(), ScoutyError> {
     let client = scouty.client();
     let api = client.clone().to_runtime_api::<Api>();
-    let config = CONFIG.clone();
+    let config = CONFIG.load_full();
 
     // Get the current block number being processed
-    let block_number = api.storage().system().number(None).await?;
+    let block_number = storage!(api, system, number(None)).await?;
     // timestamp of current block
-    let now = api.storage().timestamp().now(None).await?;
+    let now = storage!(api, timestamp, now(None)).await?;
 
     let init = Init { block_number, now };
 
     // Collect session data
-    let current_session_index = api.storage().session().current_index(None).await?;
+    let current_session_index = storage!(api, session, current_index(None)).await?;
     let session = collect_session_data(&scouty, current_session_index).await?;
 
     let network = Network::load(client).await?;
@@ -157,18 +719,20 @@ async fn try_init_hook(
     };
 
     // Fetch era reward points from previous era
-    let era_reward_points = api
-        .storage()
-        .staking()
-        .eras_reward_points(&(session.active_era_index - 1), None)
-        .await?;
+    let era_reward_points = storage!(
+        api,
+        staking,
+        eras_reward_points(&(session.active_era_index - 1), None)
+    )
+    .await?;
 
     // Collect previusly era reward
-    let era_reward: u128 = if let Some(reward) = api
-        .storage()
-        .staking()
-        .eras_validator_reward(&(session.active_era_index - 1), None)
-        .await?
+    let era_reward: u128 = if let Some(reward) = storage!(
+        api,
+        staking,
+        eras_validator_reward(&(session.active_era_index - 1), None)
+    )
+    .await?
     {
         reward
     } else {
@@ -176,7 +740,7 @@ async fn try_init_hook(
     };
 
     // Collect session active validators
-    let active_validators = api.storage().session().validators(None).await?;
+    let active_validators = storage!(api, session, validators(None)).await?;
 
     // Collect validators info based on config stashes
     let mut validators = collect_validators_data(&scouty).await?;
@@ -304,9 +868,7 @@ async fn try_init_hook(
     };
 
     let report = Report::from(data);
-    scouty
-        .send_message(&report.message(), &report.formatted_message())
-        .await?;
+    scouty.send_report(&report).await?;
 
     Ok(())
 }
@@ -314,10 +876,19 @@ async fn try_init_hook(
 async fn try_run_staking_chilled_hook(
     scouty: &Scouty,
     event: Option<Chilled>,
+    slashed: Vec<AccountId32>,
 ) -> Result<(), ScoutyError> {
     if let Some(event) = event {
         let client = scouty.client();
-        let config = CONFIG.clone();
+        let api = client.clone().to_runtime_api::<Api>();
+        let config = CONFIG.load_full();
+
+        if !config.is_event_enabled(EventKind::Chill) {
+            return Ok(());
+        }
+
+        let current_session_index = storage!(api, session, current_index(None)).await?;
+        let session = collect_session_data(&scouty, current_session_index).await?;
 
         // Collect validators info based on config stashes
         let mut validators = collect_validators_data(&scouty).await?;
@@ -325,40 +896,17 @@ async fn try_run_staking_chilled_hook(
         let network = Network::load(client).await?;
         debug!("network {:?}", network);
 
-        // Try to run hooks for each stash
-        for v in validators.iter_mut() {
-            // Identify if the stash has been chilled
-            if event.0 == v.stash {
-                v.is_chilled = true;
-
-                // Try HOOK_VALIDATOR_CHILLED
-                let mut args = vec![
-                    v.stash.to_string(),
-                    v.name.to_string(),
-                    format!("0x{:?}", HexDisplay::from(&v.queued_session_keys)),
-                    v.is_active.to_string(),
-                    v.is_queued.to_string(),
-                ];
-
-                if config.expose_network || config.expose_all {
-                    args.push(network.name.to_string());
-                    args.push(network.token_symbol.to_string());
-                    args.push(network.token_decimals.to_string());
-                } else {
-                    args.push("-".to_string());
-                    args.push("-".to_string());
-                    args.push("-".to_string());
-                }
+        // Identify if one of the stashes has been chilled, the chill is forced
+        // if the stash is disabled or has been slashed in the same block
+        let is_slashed = slashed.contains(&event.0);
+        if let Some(v) = mark_chilled(&mut validators, &event.0, is_slashed) {
+            // Try HOOK_VALIDATOR_CHILLED
+            let hook = try_run_chilled_hook(v, &network, &session)?;
+            v.hooks.push(hook);
 
-                // Try run hook
-                let hook = Hook::try_run(
-                    HOOK_VALIDATOR_CHILLED,
-                    &config.hook_validator_chilled_path,
-                    args.clone(),
-                )?;
-                v.hooks.push(hook);
-                break;
-            }
+            scouty
+                .trigger_incident(EVENT_VALIDATOR_CHILLED, &v.stash.to_string(), &v.name)
+                .await;
         }
 
         debug!("validators {:?}", validators);
@@ -375,22 +923,134 @@ async fn try_run_staking_chilled_hook(
             };
 
             let report = Report::from(data);
+            scouty.send_report(&report).await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn try_run_validator_disabled_hook(
+    scouty: &Scouty,
+    event: Option<Offence>,
+) -> Result<(), ScoutyError> {
+    if let Some(_event) = event {
+        let client = scouty.client();
+        let api = client.clone().to_runtime_api::<Api>();
+        let config = CONFIG.load_full();
+
+        if !config.is_event_enabled(EventKind::Chill) {
+            return Ok(());
+        }
+
+        let current_session_index = storage!(api, session, current_index(None)).await?;
+        let session = collect_session_data(&scouty, current_session_index).await?;
+
+        // Collect validators info based on config stashes
+        let mut validators = collect_validators_data(&scouty).await?;
+
+        let network = Network::load(client).await?;
+        debug!("network {:?}", network);
+
+        // Identify if one of the stashes has been disabled after the offence, a
+        // stash already disabled by a previous offence in the era is skipped
+        for v in validators.iter_mut().filter(|v| v.is_disabled) {
+            if FIRED_HOOKS.lock().unwrap().is_duplicate(
+                HOOK_VALIDATOR_CHILLED,
+                &v.stash.to_string(),
+                session.active_era_index,
+            ) {
+                continue;
+            }
+            v.chill_kind = Some(ChillKind::Disabled);
+
+            // Try HOOK_VALIDATOR_CHILLED
+            let hook = try_run_chilled_hook(v, &network, &session)?;
+            v.hooks.push(hook);
+
             scouty
-                .send_message(&report.message(), &report.formatted_message())
-                .await?;
+                .trigger_incident(EVENT_VALIDATOR_CHILLED, &v.stash.to_string(), &v.name)
+                .await;
+        }
+
+        debug!("validators {:?}", validators);
+
+        // NOTE: Only send disabled message if one of the stashes defined in
+        // config has just been disabled
+        if validators
+            .iter()
+            .any(|v| v.chill_kind == Some(ChillKind::Disabled))
+        {
+            // Prepare notification report
+            let data = RawData {
+                network,
+                validators,
+                section: Section::Chill,
+                ..Default::default()
+            };
+
+            let report = Report::from(data);
+            scouty.send_report(&report).await?;
         }
     }
 
     Ok(())
 }
 
+fn try_run_chilled_hook(
+    v: &Validator,
+    network: &Network,
+    session: &Session,
+) -> Result<Hook, ScoutyError> {
+    let config = CONFIG.load_full();
+
+    let mut args = vec![
+        v.stash.to_string(),
+        v.name.to_string(),
+        format!("0x{:?}", HexDisplay::from(&v.queued_session_keys)),
+        v.is_active.to_string(),
+        v.is_queued.to_string(),
+    ];
+
+    if config.expose_network || config.expose_all {
+        args.push(network.name.to_string());
+        args.push(network.token_symbol.to_string());
+        args.push(network.token_decimals.to_string());
+    } else {
+        args.push("-".to_string());
+        args.push("-".to_string());
+        args.push("-".to_string());
+    }
+
+    // Try run hook
+    Hook::try_run_with_context(
+        HOOK_VALIDATOR_CHILLED,
+        &config.hook_validator_chilled_path,
+        args,
+        &HookContext {
+            stash: v.stash.to_string(),
+            stash_name: v.name.to_string(),
+            era: session.active_era_index,
+            session: session.current_session_index,
+            event: HOOK_VALIDATOR_CHILLED.to_string(),
+            is_active: v.is_active,
+            chill_kind: v.chill_kind.map(|k| k.to_string()),
+            ..Default::default()
+        },
+    )
+}
+
 async fn try_run_im_online_some_offline_hook(
     scouty: &Scouty,
     event: Option<SomeOffline>,
 ) -> Result<(), ScoutyError> {
     if let Some(event) = event {
         let client = scouty.client();
-        let config = CONFIG.clone();
+        let config = CONFIG.load_full();
+
+        if !config.is_event_enabled(EventKind::Offline) {
+            return Ok(());
+        }
 
         // Collect validators info based on config stashes
         let mut validators = collect_validators_data(&scouty).await?;
@@ -449,79 +1109,167 @@ async fn try_run_im_online_some_offline_hook(
             };
 
             let report = Report::from(data);
-            scouty
-                .send_message(&report.message(), &report.formatted_message())
-                .await?;
+            scouty.send_report(&report).await?;
         }
     }
 
     Ok(())
 }
 
+/// Runs a single path for each slash in the block. The slash of a stash defined in
+/// config is a priority report with its own hook, any other one is the usual slash
+/// report and hook.
+async fn try_run_slashed_hooks(
+    scouty: &Scouty,
+    events: Vec<Slashed>,
+) -> Result<(), ScoutyError> {
+    let config = CONFIG.load_full();
+    for event in events {
+        if config.is_event_enabled(EventKind::StashSlashed)
+            && config.is_stash(&event.0.to_string())
+        {
+            try_run_stash_slashed_hook(scouty, event).await?;
+        } else if config.is_event_enabled(EventKind::Slash) {
+            try_run_staking_slashed_hook(scouty, event).await?;
+        }
+    }
+    Ok(())
+}
+
 async fn try_run_staking_slashed_hook(
     scouty: &Scouty,
-    event: Option<Slashed>,
+    event: Slashed,
 ) -> Result<(), ScoutyError> {
-    if let Some(event) = event {
-        let client = scouty.client().clone();
-        // let _api = client.to_runtime_api::<Api>();
-        let config = CONFIG.clone();
+    let client = scouty.client().clone();
+    // let _api = client.to_runtime_api::<Api>();
+    let config = CONFIG.load_full();
 
-        // Collect validators info based on config stashes
-        let mut validators = collect_validators_data(&scouty).await?;
+    // Collect validators info based on config stashes
+    let mut validators = collect_validators_data(&scouty).await?;
 
-        // Try to run hooks for each stash
-        for v in validators.iter_mut() {
-            if event.0 == v.stash {
-                v.is_slashed = true;
-            }
+    // Try to run hooks for each stash
+    for v in validators.iter_mut() {
+        if event.0 == v.stash {
+            v.is_slashed = true;
+            scouty
+                .trigger_incident(EVENT_VALIDATOR_SLASHED, &v.stash.to_string(), &v.name)
+                .await;
         }
+    }
 
-        debug!("validators {:?}", validators);
+    debug!("validators {:?}", validators);
 
-        let network = Network::load(&client).await?;
-        debug!("network {:?}", network);
+    let network = Network::load(&client).await?;
+    debug!("network {:?}", network);
 
-        let mut args = vec![event.0.to_string(), event.1.to_string()];
+    let mut args = vec![event.0.to_string(), event.1.to_string()];
 
-        if config.expose_network || config.expose_all {
-            args.push(network.name.to_string());
-            args.push(network.token_symbol.to_string());
-            args.push(network.token_decimals.to_string());
-        } else {
-            args.push("-".to_string());
-            args.push("-".to_string());
-            args.push("-".to_string());
-        }
+    if config.expose_network || config.expose_all {
+        args.push(network.name.to_string());
+        args.push(network.token_symbol.to_string());
+        args.push(network.token_decimals.to_string());
+    } else {
+        args.push("-".to_string());
+        args.push("-".to_string());
+        args.push("-".to_string());
+    }
 
-        // Try run hook
-        let hook = Hook::try_run(
-            HOOK_VALIDATOR_SLASHED,
-            &config.hook_validator_slashed_path,
-            args.clone(),
-        )?;
+    // Try run hook
+    let hook = Hook::try_run(
+        HOOK_VALIDATOR_SLASHED,
+        &config.hook_validator_slashed_path,
+        args.clone(),
+    )?;
+
+    // Set slash info
+    let slash = Slash {
+        who: Some(event.0),
+        amount_value: event.1,
+        hook,
+    };
 
-        // Set slash info
-        let slash = Slash {
-            who: Some(event.0),
-            amount_value: event.1,
-            hook,
-        };
+    // Prepare notification report
+    let data = RawData {
+        network,
+        validators,
+        slash,
+        section: Section::Slash,
+        ..Default::default()
+    };
 
-        // Prepare notification report
-        let data = RawData {
-            network,
-            validators,
-            slash,
-            section: Section::Slash,
-            ..Default::default()
-        };
+    let report = Report::from(data);
+    scouty.send_report(&report).await?;
+    Ok(())
+}
 
-        let report = Report::from(data);
-        scouty
-            .send_message(&report.message(), &report.formatted_message())
-            .await?;
+async fn try_run_stash_slashed_hook(
+    scouty: &Scouty,
+    event: Slashed,
+) -> Result<(), ScoutyError> {
+    let client = scouty.client();
+    let api = client.clone().to_runtime_api::<Api>();
+    let config = CONFIG.load_full();
+
+    let current_session_index = storage!(api, session, current_index(None)).await?;
+    let session = collect_session_data(&scouty, current_session_index).await?;
+
+    // Collect validators info based on config stashes
+    let mut validators = collect_validators_data(&scouty).await?;
+
+    let mut hook = Hook::default();
+    for v in validators.iter_mut() {
+        if event.0 == v.stash {
+            v.is_slashed = true;
+
+            // Try HOOK_STASH_SLASHED
+            let args = vec![
+                v.stash.to_string(),
+                event.1.to_string(),
+                session.active_era_index.to_string(),
+            ];
+
+            hook = Hook::try_run_with_context(
+                HOOK_STASH_SLASHED,
+                &config.hook_slash_path,
+                args,
+                &HookContext {
+                    stash: v.stash.to_string(),
+                    stash_name: v.name.to_string(),
+                    era: session.active_era_index,
+                    session: session.current_session_index,
+                    event: HOOK_STASH_SLASHED.to_string(),
+                    is_active: v.is_active,
+                    slash_amount: Some(event.1),
+                    ..Default::default()
+                },
+            )?;
+        }
     }
+
+    debug!("validators {:?}", validators);
+
+    let network = Network::load(client).await?;
+    debug!("network {:?}", network);
+
+    // Set slash info
+    let slash = Slash {
+        who: Some(event.0),
+        amount_value: event.1,
+        hook,
+    };
+
+    // Prepare notification report
+    let data = RawData {
+        network,
+        session,
+        validators,
+        slash,
+        section: Section::StashSlashed,
+        ..Default::default()
+    };
+
+    let report = Report::from(data);
+    scouty.send_priority_report(&report).await?;
     Ok(())
 }
 
@@ -532,7 +1280,11 @@ async fn try_run_democracy_started_hook(
     if let Some(event) = event {
         let client = scouty.client();
         let _api = client.clone().to_runtime_api::<Api>();
-        let config = CONFIG.clone();
+        let config = CONFIG.load_full();
+
+        if !config.is_event_enabled(EventKind::Democracy) {
+            return Ok(());
+        }
 
         let network = Network::load(client).await?;
         debug!("network {:?}", network);
@@ -575,13 +1327,59 @@ async fn try_run_democracy_started_hook(
         };
 
         let report = Report::from(data);
-        scouty
-            .send_message(&report.message(), &report.formatted_message())
-            .await?;
+        scouty.send_report(&report).await?;
     }
     Ok(())
 }
 
+/// Runs the session hooks of the current session at the finalized head, as if the
+/// session had just started, used by a single monitoring pass
+async fn try_run_current_session_hooks(
+    scouty: &Scouty,
+    authority_records: &mut AuthorityRecords,
+    para_records: &mut ParaRecords,
+) -> Result<(), ScoutyError> {
+    let client = scouty.client();
+    let api = client.clone().to_runtime_api::<Api>();
+    let request_timeout = CONFIG.load().request_timeout();
+
+    let block_hash = with_timeout(
+        request_timeout,
+        "Finalized head request",
+        timed(
+            &METRICS,
+            "chain_getFinalizedHead",
+            api.client.rpc().finalized_head(),
+        ),
+    )
+    .await?;
+    let signed_block = with_timeout(
+        request_timeout,
+        "Block request",
+        timed(
+            &METRICS,
+            "chain_getBlock",
+            api.client.rpc().block(Some(block_hash)),
+        ),
+    )
+    .await?
+    .ok_or_else(|| ScoutyError::Other("Finalized block not available".into()))?;
+    let authority_index = decode_authority_index(&signed_block).ok_or_else(|| {
+        ScoutyError::Other("Authority index of the finalized block not available".into())
+    })?;
+    let session_index = storage!(api, session, current_index(None)).await?;
+
+    try_run_session_hooks(
+        scouty,
+        Some(NewSession { session_index }),
+        authority_records,
+        signed_block.block.header.number,
+        authority_index,
+        para_records,
+    )
+    .await
+}
+
 async fn try_run_session_hooks(
     scouty: &Scouty,
     event: Option<NewSession>,
@@ -593,13 +1391,20 @@ async fn try_run_session_hooks(
     if let Some(event) = event {
         let client = scouty.client();
         let api = client.clone().to_runtime_api::<Api>();
-        let config = CONFIG.clone();
+        let config = CONFIG.load_full();
 
         // Collect session data
         let session = collect_session_data(&scouty, event.session_index).await?;
 
+        METRICS.inc_sessions();
+        RUNTIME_STATS.inc_sessions();
+        if (session.eras_session_index) == 1 {
+            METRICS.inc_eras();
+            RUNTIME_STATS.inc_eras();
+        }
+
         // Collect session active validators
-        let active_validators = api.storage().session().validators(None).await?;
+        let active_validators = storage!(api, session, validators(None)).await?;
 
         // Authority records -->
         // Set a new authority set every new era in authority_records
@@ -633,29 +1438,88 @@ async fn try_run_session_hooks(
         };
 
         // Fetch era reward points from previous era
-        let era_reward_points = api
-            .storage()
-            .staking()
-            .eras_reward_points(&(session.active_era_index - 1), None)
-            .await?;
+        let era_reward_points = storage!(
+            api,
+            staking,
+            eras_reward_points(&(session.active_era_index - 1), None)
+        )
+        .await?;
 
         // Collect previusly era reward
-        let era_reward: u128 = if let Some(reward) = api
-            .storage()
-            .staking()
-            .eras_validator_reward(&(session.active_era_index - 1), None)
-            .await?
+        let era_reward: u128 = if let Some(reward) = storage!(
+            api,
+            staking,
+            eras_validator_reward(&(session.active_era_index - 1), None)
+        )
+        .await?
         {
             reward
         } else {
             0
         };
 
+        // Points of each validator in the era that just ended
+        let previous_era_points: Vec<(AccountId32, u32)> = era_reward_points
+            .individual
+            .iter()
+            .map(|(stash, points)| (stash.clone(), *points))
+            .collect();
+
         // Collect validators info based on config stashes
         let mut validators = collect_validators_data(&scouty).await?;
 
         // Try to run hooks for each stash
+        let mut notified: Vec<AccountId32> = Vec::new();
         for v in validators.iter_mut() {
+            METRICS.set_stash_active(&v.stash.to_string(), v.is_active);
+            MONITORED.lock().unwrap().record(
+                &v.stash.to_string(),
+                &v.name,
+                v.is_active,
+                session.active_era_index,
+                session.current_session_index,
+            );
+
+            // Activity summarized in the daily report
+            {
+                let mut daily_report = DAILY_REPORT.lock().unwrap();
+                daily_report.record_session(&v.stash.to_string(), &v.name, v.is_active);
+                if (session.eras_session_index) == 1 {
+                    if let Some((_, points)) = previous_era_points
+                        .iter()
+                        .find(|(stash, _)| stash == &v.stash)
+                    {
+                        daily_report.record_era_points(
+                            &v.stash.to_string(),
+                            &v.name,
+                            *points,
+                        );
+                    }
+                }
+            }
+
+            // Only the stashes whose active state changed are notified if configured
+            let is_changed = LAST_ACTIVE.lock().unwrap().has_changed(
+                &v.stash.to_string(),
+                session.active_era_index,
+                v.is_active,
+            );
+            let is_notified = (is_changed || !config.notify_on_change_only)
+                && config.is_event_allowlisted(EventKind::NewSession);
+            if is_notified {
+                notified.push(v.stash.clone());
+            }
+
+            // Event data exported to the hooks as env vars
+            let context = HookContext {
+                stash: v.stash.to_string(),
+                stash_name: v.name.to_string(),
+                era: session.active_era_index,
+                session: session.current_session_index,
+                is_active: v.is_active,
+                ..Default::default()
+            };
+
             // Try HOOK_NEW_SESSION
             let mut args = vec![
                 v.stash.to_string(),
@@ -747,15 +1611,42 @@ async fn try_run_session_hooks(
             }
 
             // Try run hook
-            let hook = Hook::try_run(
-                HOOK_NEW_SESSION,
-                &config.hook_new_session_path,
-                args.clone(),
-            )?;
-            v.hooks.push(hook);
+            if !config.hook_new_session_disabled && is_notified {
+                let hook = Hook::try_run_with_context(
+                    HOOK_NEW_SESSION,
+                    &config.hook_new_session_path,
+                    args.clone(),
+                    &HookContext {
+                        event: HOOK_NEW_SESSION.to_string(),
+                        ..context.clone()
+                    },
+                )?;
+                v.hooks.push(hook);
+            }
+
+            if is_notified {
+                scouty
+                    .notify(Notification::new(
+                        EVENT_NEW_SESSION,
+                        &v.stash.to_string(),
+                        &v.name,
+                        session.active_era_index,
+                        session.current_session_index,
+                    ))
+                    .await;
+            }
 
             // Try HOOK_NEW_ERA
-            if (session.eras_session_index) == 1 {
+            if (session.eras_session_index) == 1
+                && config.is_event_enabled(EventKind::NewEra)
+            {
+                // Points and rank in the era that just ended
+                v.previous_era = Some(EraPerformance::from_points(
+                    session.active_era_index - 1,
+                    &v.stash,
+                    &previous_era_points,
+                ));
+
                 // Expose validator last era points
                 if config.expose_era_points || config.expose_all {
                     let points =
@@ -768,10 +1659,23 @@ async fn try_run_session_hooks(
                     args.push("-".to_string());
                 }
 
-                // Try run hook
-                let hook =
-                    Hook::try_run(HOOK_NEW_ERA, &config.hook_new_era_path, args.clone())?;
-                v.hooks.push(hook);
+                // Try run hook, once per era
+                if !FIRED_HOOKS.lock().unwrap().is_duplicate(
+                    HOOK_NEW_ERA,
+                    &v.stash.to_string(),
+                    session.active_era_index,
+                ) {
+                    let hook = Hook::try_run_with_context(
+                        HOOK_NEW_ERA,
+                        &config.hook_new_era_path,
+                        args.clone(),
+                        &HookContext {
+                            event: HOOK_NEW_ERA.to_string(),
+                            ..context.clone()
+                        },
+                    )?;
+                    v.hooks.push(hook);
+                }
             }
 
             if (session.eras_session_index) == 6 && session.queued_session_keys_changed {
@@ -787,19 +1691,49 @@ async fn try_run_session_hooks(
 
                 // Try HOOK_VALIDATOR_STARTS_ACTIVE_NEXT_ERA
                 // If stash is not active and keys are queued for next Era -> trigger hook to get ready and warm up
-                if !v.is_active && v.is_queued {
-                    // Try run hook
-                    let hook = Hook::try_run(
-                        HOOK_VALIDATOR_STARTS_ACTIVE_NEXT_ERA,
-                        &config.hook_validator_starts_active_next_era_path,
-                        args.clone(),
-                    )?;
-                    v.hooks.push(hook);
+                if !v.is_active
+                    && v.is_queued
+                    && config.is_event_allowlisted(EventKind::ActiveNextEra)
+                {
+                    // Try run hook, once per era
+                    if !config.hook_active_next_era_disabled
+                        && !FIRED_HOOKS.lock().unwrap().is_duplicate(
+                            HOOK_VALIDATOR_STARTS_ACTIVE_NEXT_ERA,
+                            &v.stash.to_string(),
+                            next_era_index,
+                        )
+                    {
+                        let hook = Hook::try_run_with_context(
+                            HOOK_VALIDATOR_STARTS_ACTIVE_NEXT_ERA,
+                            config.hook_active_next_era_path(&v.stash.to_string()),
+                            args.clone(),
+                            &HookContext {
+                                era: next_era_index,
+                                session: next_session_index,
+                                event: HOOK_VALIDATOR_STARTS_ACTIVE_NEXT_ERA.to_string(),
+                                ..context.clone()
+                            },
+                        )?;
+                        v.hooks.push(hook);
+                    }
+
+                    scouty
+                        .notify(Notification::new(
+                            EVENT_VALIDATOR_STARTS_ACTIVE_NEXT_ERA,
+                            &v.stash.to_string(),
+                            &v.name,
+                            next_era_index,
+                            next_session_index,
+                        ))
+                        .await;
                 }
 
                 // Try HOOK_VALIDATOR_INACTIVE_NEXT_ERA
                 // If stash is active and keys are not queued for next Era trigger hook to inform operator
-                if v.is_active && !v.is_queued {
+                if v.is_active
+                    && !v.is_queued
+                    && config.is_event_allowlisted(EventKind::InactiveNextEra)
+                {
                     let args = vec![
                         v.stash.to_string(),
                         v.name.to_string(),
@@ -808,17 +1742,69 @@ async fn try_run_session_hooks(
                         format!("{}", next_session_index),
                     ];
 
-                    // Try run hook
-                    let hook = Hook::try_run(
-                        HOOK_VALIDATOR_STARTS_INACTIVE_NEXT_ERA,
-                        &config.hook_validator_starts_inactive_next_era_path,
-                        args.clone(),
-                    )?;
-                    v.hooks.push(hook);
+                    // Try run hook, once per era
+                    if !config.hook_inactive_next_era_disabled
+                        && !FIRED_HOOKS.lock().unwrap().is_duplicate(
+                            HOOK_VALIDATOR_STARTS_INACTIVE_NEXT_ERA,
+                            &v.stash.to_string(),
+                            next_era_index,
+                        )
+                    {
+                        let hook = Hook::try_run_with_context(
+                            HOOK_VALIDATOR_STARTS_INACTIVE_NEXT_ERA,
+                            &config.hook_validator_starts_inactive_next_era_path,
+                            args.clone(),
+                            &HookContext {
+                                era: next_era_index,
+                                session: next_session_index,
+                                event: HOOK_VALIDATOR_STARTS_INACTIVE_NEXT_ERA
+                                    .to_string(),
+                                ..context.clone()
+                            },
+                        )?;
+                        v.hooks.push(hook);
+                    }
+
+                    scouty
+                        .notify(Notification::new(
+                            EVENT_VALIDATOR_STARTS_INACTIVE_NEXT_ERA,
+                            &v.stash.to_string(),
+                            &v.name,
+                            next_era_index,
+                            next_session_index,
+                        ))
+                        .await;
+
+                    scouty
+                        .trigger_incident(
+                            EVENT_VALIDATOR_STARTS_INACTIVE_NEXT_ERA,
+                            &v.stash.to_string(),
+                            &v.name,
+                        )
+                        .await;
+                }
+
+                // The chill and inactive next era conditions are cleared unless the
+                // stash is still active without keys queued for next Era
+                for cleared in cleared_incidents(v.is_active, v.is_queued) {
+                    scouty.resolve_incident(cleared, &v.stash.to_string()).await;
                 }
             }
         }
 
+        changes::save_state();
+
+        // Skip the stashes without state changes unless they have hooks to report
+        if config.notify_on_change_only
+            || !config.is_event_allowlisted(EventKind::NewSession)
+        {
+            validators.retain(|v| notified.contains(&v.stash) || !v.hooks.is_empty());
+            if validators.is_empty() {
+                info!("No stash state changes, session notification skipped");
+                return Ok(());
+            }
+        }
+
         // Prepare notification report
         debug!("validators {:?}", validators);
 
@@ -831,9 +1817,7 @@ async fn try_run_session_hooks(
         };
 
         let report = Report::from(data);
-        scouty
-            .send_message(&report.message(), &report.formatted_message())
-            .await?;
+        scouty.send_report(&report).await?;
     }
     Ok(())
 }
@@ -846,11 +1830,7 @@ async fn get_active_nominators(
     let client = scouty.client().clone();
     let api = client.to_runtime_api::<Api>();
 
-    let exposure = api
-        .storage()
-        .staking()
-        .eras_stakers(&era_index, stash, None)
-        .await?;
+    let exposure = storage!(api, staking, eras_stakers(&era_index, stash, None)).await?;
     debug!("__exposure: {:?}", exposure);
     let mut nominators: Vec<String> = vec![];
     let mut nominators_stake: Vec<u128> = vec![];
@@ -867,24 +1847,21 @@ async fn get_nominators(
 ) -> Result<BTreeMap<String, Vec<String>>, ScoutyError> {
     let client = scouty.client().clone();
     let api = client.to_runtime_api::<Api>();
-    let config = CONFIG.clone();
+    let config = CONFIG.load_full();
 
     let mut stashes_nominators: BTreeMap<String, Vec<String>> = BTreeMap::new();
     for stash in config.stashes.iter() {
-        stashes_nominators.insert(stash.to_string(), vec![]);
+        stashes_nominators.insert(stash.address.to_string(), vec![]);
     }
 
     info!("Starting All Nominators - sync");
-    let mut nominators = api.storage().staking().nominators_iter(None).await?;
+    let mut nominators = storage!(api, staking, nominators_iter(None)).await?;
     while let Some((key, nominations)) = nominators.next().await? {
         let nominator_stash = get_account_id_from_storage_key(key);
-        if let Some(_controller) = api
-            .storage()
-            .staking()
-            .bonded(&nominator_stash, None)
-            .await?
+        if let Some(_controller) =
+            storage!(api, staking, bonded(&nominator_stash, None)).await?
         {
-            for stash_str in config.stashes.iter() {
+            for stash_str in config.stashes.iter().map(|stash| &stash.address) {
                 let stash = AccountId32::from_str(stash_str)?;
                 let BoundedVec(targets) = nominations.targets.clone();
                 if targets.contains(&stash) {
@@ -907,7 +1884,7 @@ async fn collect_session_data(
     let api = client.to_runtime_api::<Api>();
 
     // Get Era index
-    let active_era_index = match api.storage().staking().active_era(None).await? {
+    let active_era_index = match storage!(api, staking, active_era(None)).await? {
         Some(info) => info.index,
         None => return Err(ScoutyError::Other("Active era not available".into())),
     };
@@ -916,11 +1893,12 @@ async fn collect_session_data(
     let current_session_index = session_index;
 
     // Get start session index
-    let start_session_index = match api
-        .storage()
-        .staking()
-        .eras_start_session_index(&active_era_index, None)
-        .await?
+    let start_session_index = match storage!(
+        api,
+        staking,
+        eras_start_session_index(&active_era_index, None)
+    )
+    .await?
     {
         Some(index) => index,
         None => {
@@ -935,7 +1913,7 @@ async fn collect_session_data(
 
     // Get session keys queued status
     let queued_session_keys_changed =
-        api.storage().session().queued_changed(None).await?;
+        storage!(api, session, queued_changed(None)).await?;
 
     // Set network info
     let session = Session {
@@ -952,25 +1930,41 @@ async fn collect_session_data(
 async fn collect_validators_data(scouty: &Scouty) -> Result<Validators, ScoutyError> {
     let client = scouty.client().clone();
     let api = client.to_runtime_api::<Api>();
-    let config = CONFIG.clone();
+    let config = CONFIG.load_full();
 
     // Verify session active validators
-    let active_validators = api.storage().session().validators(None).await?;
+    let active_validators = storage!(api, session, validators(None)).await?;
+
+    // Verify session disabled validators, indexes of the active validators
+    let disabled_validators = storage!(api, session, disabled_validators(None)).await?;
 
     // Verify session queued keys
-    let queued_keys = api.storage().session().queued_keys(None).await?;
+    let queued_keys = storage!(api, session, queued_keys(None)).await?;
+
+    // Query each stash name concurrently
+    let names = map_bounded(
+        config.stashes.iter(),
+        config.max_concurrent_queries,
+        |stash_config| get_stash_name(&scouty, stash_config),
+    )
+    .await;
 
     let mut validators: Validators = Vec::new();
-    for stash_str in config.stashes.iter() {
-        let stash = AccountId32::from_str(stash_str)?;
+    for (stash_config, name) in config.stashes.iter().zip(names) {
+        let stash = AccountId32::from_str(&stash_config.address)?;
         let mut v = Validator::new(stash.clone());
 
-        // Get validator name
-        v.name = get_display_name(&scouty, &stash, None).await?;
+        v.name = name?;
 
         // Check if validator is in active set
         v.is_active = active_validators.contains(&v.stash);
 
+        // Check if validator has been disabled in the current era
+        v.is_disabled = active_validators
+            .iter()
+            .position(|a| a == &v.stash)
+            .map_or(false, |i| disabled_validators.contains(&(i as u32)));
+
         // Check if validator session key is queued
         for (account_id, session_keys) in &queued_keys {
             if account_id == &v.stash {
@@ -987,6 +1981,21 @@ async fn collect_validators_data(scouty: &Scouty) -> Result<Validators, ScoutyEr
     Ok(validators)
 }
 
+/// Returns the label defined in config, which takes precedence, or the on-chain
+/// identity of the stash
+async fn get_stash_name(
+    scouty: &Scouty,
+    stash_config: &StashConfig,
+) -> Result<String, ScoutyError> {
+    match &stash_config.name {
+        Some(name) => Ok(name.to_string()),
+        None => {
+            let stash = AccountId32::from_str(&stash_config.address)?;
+            get_display_name(&scouty, &stash, None).await
+        }
+    }
+}
+
 #[async_recursion]
 async fn get_display_name(
     scouty: &Scouty,
@@ -996,7 +2005,7 @@ async fn get_display_name(
     let client = scouty.client();
     let api = client.clone().to_runtime_api::<Api>();
 
-    match api.storage().identity().identity_of(stash, None).await? {
+    match storage!(api, identity, identity_of(stash, None)).await? {
         Some(identity) => {
             debug!("identity {:?}", identity);
             let parent = parse_identity_data(identity.info.display);
@@ -1008,7 +2017,7 @@ async fn get_display_name(
         }
         None => {
             if let Some((parent_account, data)) =
-                api.storage().identity().super_of(stash, None).await?
+                storage!(api, identity, super_of(stash, None)).await?
             {
                 let sub_account_name = parse_identity_data(data);
                 return get_display_name(
@@ -1142,23 +2151,24 @@ async fn init_authority_records(
 ) -> Result<(), ScoutyError> {
     let client = scouty.client().clone();
     let api = client.to_runtime_api::<Api>();
-    let config = CONFIG.clone();
+    let config = CONFIG.load_full();
     // Get current block
-    authority_records.set_block(api.storage().system().number(None).await?);
+    authority_records.set_block(storage!(api, system, number(None)).await?);
     // Get current session
-    let current_session_index = api.storage().session().current_index(None).await?;
+    let current_session_index = storage!(api, session, current_index(None)).await?;
     authority_records.set_session(current_session_index);
     // Get current active authorities
-    authority_records.set_authorities(api.storage().session().validators(None).await?);
+    authority_records.set_authorities(storage!(api, session, validators(None)).await?);
     // Get blocks authored for each stash
-    for stash_str in config.stashes.iter() {
+    for stash_str in config.stashes.iter().map(|stash| &stash.address) {
         let stash = AccountId32::from_str(stash_str)?;
         let key = format!("{}:{}", current_session_index, stash);
-        let blocks = api
-            .storage()
-            .im_online()
-            .authored_blocks(&current_session_index, &stash, None)
-            .await?;
+        let blocks = storage!(
+            api,
+            im_online,
+            authored_blocks(&current_session_index, &stash, None)
+        )
+        .await?;
         authority_records.records.insert(key, blocks);
     }
     Ok(())
@@ -1172,12 +2182,12 @@ async fn init_para_records(
     let api = client.to_runtime_api::<Api>();
 
     // Get current active authorities
-    let active_validators = api.storage().session().validators(None).await?;
+    let active_validators = storage!(api, session, validators(None)).await?;
 
     para_records.reset_config_stashes(active_validators)?;
 
     // Get current session
-    let current_session_index = api.storage().session().current_index(None).await?;
+    let current_session_index = storage!(api, session, current_index(None)).await?;
 
     track_para_records(&scouty, current_session_index, para_records).await?;
 
@@ -1193,11 +2203,8 @@ async fn track_para_records(
     let api = client.to_runtime_api::<Api>();
 
     // Get para active validator indices
-    let para_validators = api
-        .storage()
-        .paras_shared()
-        .active_validator_indices(None)
-        .await?;
+    let para_validators =
+        storage!(api, paras_shared, active_validator_indices(None)).await?;
 
     // Parse Vec<ValidatorIndex> to Vec<u32>
     let active_validator_indices: Vec<u32> = para_validators
@@ -1261,7 +2268,7 @@ async fn calculate_projected_apr(
     let api = client.to_runtime_api::<Api>();
 
     // Get validator prefs
-    let prefs = api.storage().staking().validators(stash, None).await?;
+    let prefs = storage!(api, staking, validators(stash, None)).await?;
 
     let node_runtime::runtime_types::sp_arithmetic::per_things::Perbill(c) =
         prefs.commission;
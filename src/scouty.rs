@@ -19,97 +19,281 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use crate::config::{Config, CONFIG};
-use crate::errors::ScoutyError;
+use crate::backoff;
+use crate::concurrency::{timed, with_timeout};
+use crate::config::{format_duration, Config, CONFIG};
+use crate::dedup::{LAST_SENT, STARTUP_CYCLE};
+use crate::digest::DAILY_REPORT;
+use crate::errors::{ErrorClass, ScoutyError, EXIT_FAILURE, EXIT_SUCCESS};
+use crate::failover::CONNECTED;
+use crate::health;
 use crate::hooks::{
     Hook, HOOK_DEMOCRACY_STARTED, HOOK_INIT, HOOK_NEW_ERA, HOOK_NEW_SESSION,
-    HOOK_VALIDATOR_CHILLED, HOOK_VALIDATOR_OFFLINE, HOOK_VALIDATOR_SLASHED,
+    HOOK_STASH_SLASHED, HOOK_UNCLAIMED_REWARDS, HOOK_VALIDATOR_CHILLED,
+    HOOK_VALIDATOR_COMMISSION_CHANGED, HOOK_VALIDATOR_OFFLINE, HOOK_VALIDATOR_SLASHED,
     HOOK_VALIDATOR_STARTS_ACTIVE_NEXT_ERA, HOOK_VALIDATOR_STARTS_INACTIVE_NEXT_ERA,
 };
 use crate::matrix::Matrix;
+use crate::metrics::{METRICS, RUNTIME_STATS};
+use crate::pagerduty::PagerDuty;
+use crate::proxy;
+use crate::ratelimit;
+use crate::report::Report;
 use crate::runtimes::{
     kusama, polkadot,
     support::{ChainPrefix, SupportedRuntime},
     westend,
 };
+use crate::shutdown;
+use crate::smtp::Smtp;
+use crate::telegram::Telegram;
+use crate::tls;
+use crate::webhook::{Notification, Webhook, EVENT_ERROR, EVENT_TEST};
 
 use async_std::task;
-use log::{error, info, warn};
-use std::{convert::TryInto, result::Result, thread, time};
+use log::{debug, error, info, warn};
+use std::{convert::TryInto, process, result::Result, thread, time};
 use subxt::{
     sp_core::crypto, sp_core::storage::StorageKey, sp_runtime::AccountId32, Client,
     ClientBuilder, DefaultConfig,
 };
 
 pub async fn create_substrate_node_client(
-    config: Config,
+    substrate_ws_url: &str,
 ) -> Result<Client<DefaultConfig>, subxt::BasicError> {
     ClientBuilder::new()
-        .set_url(config.substrate_ws_url)
+        .set_url(substrate_ws_url)
         .build::<DefaultConfig>()
         .await
 }
 
+/// Connect to the substrate endpoint, aborting the connection if the server
+/// certificate is not the one pinned and through a local tunnel if a proxy is
+/// defined
+async fn connect_to(
+    config: &Config,
+    substrate_ws_url: &str,
+    client_name: &str,
+) -> Result<Client<DefaultConfig>, ScoutyError> {
+    let proxy = proxy::resolve(substrate_ws_url, config.substrate_ws_proxy.as_deref());
+    let fingerprint = config
+        .substrate_ws_tls_fingerprint
+        .clone()
+        .filter(|_| substrate_ws_url.starts_with("wss://"));
+    let client_ws_url = match (fingerprint, &proxy) {
+        // The pinned TLS session is established by a local tunnel, the handshake
+        // blocks so it runs off the async executor
+        (Some(fingerprint), _) => {
+            let url = substrate_ws_url.to_string();
+            let proxy = proxy.clone();
+            let client_name = client_name.to_string();
+            let connect_timeout = config.connect_timeout();
+            with_timeout(
+                connect_timeout,
+                &format!("TLS handshake with {}", substrate_ws_url),
+                task::spawn_blocking(move || {
+                    tls::pinned_tunnel(
+                        &url,
+                        &fingerprint,
+                        proxy.as_deref(),
+                        &client_name,
+                        connect_timeout,
+                    )
+                }),
+            )
+            .await?
+        }
+        (None, Some(proxy)) => proxy::tunnel(substrate_ws_url, proxy, client_name)?,
+        (None, None) => substrate_ws_url.to_string(),
+    };
+    with_timeout(
+        config.connect_timeout(),
+        &format!("Connection to {}", substrate_ws_url),
+        create_substrate_node_client(&client_ws_url),
+    )
+    .await
+}
+
+/// Try to connect to each substrate endpoint by priority and then in order,
+/// rotating to the next one on connection failure until one of them is available
 pub async fn create_or_await_substrate_node_client(
     config: Config,
 ) -> Client<DefaultConfig> {
+    let client_name = config.rpc_client_name();
+    let mut i: usize = 0;
     loop {
-        match create_substrate_node_client(config.clone()).await {
+        let substrate_ws_url =
+            &config.substrate_ws_urls[i % config.substrate_ws_urls.len()];
+        match connect_to(&config, substrate_ws_url, &client_name).await {
             Ok(client) => {
-                let chain = client
-                    .rpc()
-                    .system_chain()
-                    .await
-                    .unwrap_or_else(|_| "Chain undefined".to_string());
-                let name = client
-                    .rpc()
-                    .system_name()
-                    .await
-                    .unwrap_or_else(|_| "Node name undefined".to_string());
-                let version = client
-                    .rpc()
-                    .system_version()
-                    .await
-                    .unwrap_or_else(|_| "Node version undefined".to_string());
+                let request_timeout = config.request_timeout();
+                let chain = with_timeout(
+                    request_timeout,
+                    "Chain request",
+                    timed(&METRICS, "system_chain", client.rpc().system_chain()),
+                )
+                .await
+                .unwrap_or_else(|_| "Chain undefined".to_string());
+                let name = with_timeout(
+                    request_timeout,
+                    "Name request",
+                    timed(&METRICS, "system_name", client.rpc().system_name()),
+                )
+                .await
+                .unwrap_or_else(|_| "Node name undefined".to_string());
+                let version = with_timeout(
+                    request_timeout,
+                    "Version request",
+                    timed(&METRICS, "system_version", client.rpc().system_version()),
+                )
+                .await
+                .unwrap_or_else(|_| "Node version undefined".to_string());
 
                 info!(
-                    "Connected to {} network using {} * Substrate node {} v{}",
-                    chain, config.substrate_ws_url, name, version
+                    "Connected to {} network using {} as {} * Substrate node {} v{}",
+                    chain, substrate_ws_url, client_name, name, version
                 );
+                let priority = config
+                    .substrate_ws_endpoints
+                    .get(i % config.substrate_ws_urls.len())
+                    .map_or(0, |endpoint| endpoint.priority);
+                CONNECTED
+                    .lock()
+                    .unwrap()
+                    .record(priority, time::Instant::now());
+                health::touch();
+                RUNTIME_STATS.inc_connections();
                 break client;
             }
             Err(e) => {
                 error!("{}", e);
-                info!("Awaiting for connection using {}", config.substrate_ws_url);
+                // Nothing is in progress while awaiting for a connection
+                if shutdown::is_requested() {
+                    info!("{}", RUNTIME_STATS.summary(time::Instant::now()));
+                    info!("Shutdown completed");
+                    process::exit(EXIT_SUCCESS);
+                }
+                i += 1;
+                info!(
+                    "Awaiting for connection using {}",
+                    config.substrate_ws_urls[i % config.substrate_ws_urls.len()]
+                );
                 thread::sleep(time::Duration::from_secs(6));
             }
         }
     }
 }
 
+/// Verifies if a substrate endpoint with a higher priority than the one connected
+/// is available again, checked at most once every endpoint promotion interval
+pub async fn is_preferred_endpoint_available() -> bool {
+    let config = CONFIG.load_full();
+    let preferred = CONNECTED.lock().unwrap().preferred_endpoints_due(
+        &config.substrate_ws_endpoints,
+        config.endpoint_promotion_interval,
+        time::Instant::now(),
+    );
+    let client_name = config.rpc_client_name();
+    for endpoint in preferred.iter() {
+        match connect_to(&config, &endpoint.url, &client_name).await {
+            // The probe connection is closed once the client is dropped
+            Ok(_) => {
+                info!(
+                    "Preferred substrate endpoint {} available again",
+                    endpoint.url
+                );
+                return true;
+            }
+            Err(e) => debug!(
+                "Preferred substrate endpoint {} still unavailable: {}",
+                endpoint.url, e
+            ),
+        }
+    }
+    false
+}
+
+/// Connect to the substrate node and set the SS58 address format of the chain
+async fn connect() -> (SupportedRuntime, Client<DefaultConfig>, ChainPrefix) {
+    let client = create_or_await_substrate_node_client((**CONFIG.load()).clone()).await;
+
+    // Warn if the chain runs a runtime different from the one expected
+    match timed(
+        &METRICS,
+        "state_getRuntimeVersion",
+        client.rpc().runtime_version(None),
+    )
+    .await
+    {
+        Ok(runtime_version) => {
+            if let Some(warning) = CONFIG
+                .load()
+                .check_spec_version(runtime_version.spec_version)
+            {
+                warn!("{}", warning);
+            }
+        }
+        Err(e) => warn!("Runtime version check skipped! {}", e),
+    }
+
+    // Display SS58 addresses based on the connected chain
+    let chain_prefix = chain_prefix(&client);
+    // unless the prefix is overridden in config
+    let display_prefix = CONFIG.load().ss58_prefix.unwrap_or(chain_prefix);
+    crypto::set_default_ss58_version(crypto::Ss58AddressFormat::custom(display_prefix));
+
+    // Check for supported runtime
+    let runtime = SupportedRuntime::from(chain_prefix);
+
+    (runtime, client, chain_prefix)
+}
+
+/// Returns the SS58 prefix of the connected chain
+fn chain_prefix(client: &Client<DefaultConfig>) -> ChainPrefix {
+    if let Some(ss58_format) = client.properties().get("ss58Format") {
+        ss58_format.as_u64().unwrap_or_default().try_into().unwrap()
+    } else {
+        0
+    }
+}
+
+/// Returns the SS58 prefix used to name the matrix room of the notifications test.
+/// Unless defined in config a single connection to the substrate node is tried,
+/// bounded by the connect timeout, so that the test does not wait for a node that
+/// is down.
+async fn test_chain_prefix(config: &Config) -> ChainPrefix {
+    if let Some(prefix) = config.ss58_prefix() {
+        return prefix;
+    }
+    let substrate_ws_url = match config.substrate_ws_urls.first() {
+        Some(substrate_ws_url) => substrate_ws_url,
+        None => return 0,
+    };
+    match connect_to(config, substrate_ws_url, &config.rpc_client_name()).await {
+        Ok(client) => chain_prefix(&client),
+        Err(e) => {
+            warn!(
+                "SS58 prefix of {} not available, the default one is used! {}",
+                substrate_ws_url, e
+            );
+            0
+        }
+    }
+}
+
 pub struct Scouty {
     runtime: SupportedRuntime,
     client: Client<DefaultConfig>,
     matrix: Matrix,
+    telegram: Telegram,
+    smtp: Smtp,
+    webhook: Webhook,
+    pagerduty: PagerDuty,
 }
 
 impl Scouty {
     async fn new() -> Scouty {
-        let client = create_or_await_substrate_node_client(CONFIG.clone()).await;
-
-        let properties = client.properties();
-
-        // Display SS58 addresses based on the connected chain
-        let chain_prefix: ChainPrefix =
-            if let Some(ss58_format) = properties.get("ss58Format") {
-                ss58_format.as_u64().unwrap_or_default().try_into().unwrap()
-            } else {
-                0
-            };
-        crypto::set_default_ss58_version(crypto::Ss58AddressFormat::custom(chain_prefix));
-
-        // Check for supported runtime
-        let runtime = SupportedRuntime::from(chain_prefix);
+        let (runtime, client, chain_prefix) = connect().await;
 
         // Initialize matrix client
         let mut matrix: Matrix = Matrix::new();
@@ -121,13 +305,131 @@ impl Scouty {
                 Default::default()
             });
 
+        // Initialize telegram client
+        let telegram: Telegram = Telegram::new();
+
+        // Initialize smtp client
+        let smtp: Smtp = Smtp::new();
+
+        // Initialize webhook client
+        let webhook: Webhook = Webhook::new();
+
+        // Initialize pagerduty client
+        let pagerduty: PagerDuty = PagerDuty::new();
+
         Scouty {
             runtime,
             client,
             matrix,
+            telegram,
+            smtp,
+            webhook,
+            pagerduty,
         }
     }
 
+    /// Print the status of each stash and exit, without subscribing to on-chain
+    /// events nor sending notifications
+    pub fn view() -> Result<(), ScoutyError> {
+        task::block_on(async {
+            let (runtime, client, _) = connect().await;
+            let c = Scouty {
+                runtime,
+                client,
+                matrix: Default::default(),
+                telegram: Default::default(),
+                smtp: Default::default(),
+                webhook: Default::default(),
+                pagerduty: Default::default(),
+            };
+            match c.runtime {
+                SupportedRuntime::Polkadot => polkadot::view(&c).await,
+                SupportedRuntime::Kusama => kusama::view(&c).await,
+                SupportedRuntime::Westend => westend::view(&c).await,
+            }
+        })
+    }
+
+    /// Send a test message to every notification backend enabled and return the
+    /// outcome of each one. The substrate node is only used to name the matrix room.
+    pub fn test_notifications() -> TestResults {
+        task::block_on(async {
+            let config = CONFIG.load_full();
+            let chain_prefix = test_chain_prefix(&config).await;
+            let message = format!(
+                "{} v{} test notification",
+                env!("CARGO_PKG_NAME"),
+                env!("CARGO_PKG_VERSION")
+            );
+            let formatted_message = format!(
+                "🤖 <code>{} v{}</code> test notification ✅",
+                env!("CARGO_PKG_NAME"),
+                env!("CARGO_PKG_VERSION")
+            );
+            let message = config.wrap_notification(&message);
+            let formatted_message =
+                config.wrap_formatted_notification(&formatted_message);
+            let mut results = TestResults::new();
+
+            let mut matrix = Matrix::new();
+            if matrix.is_enabled() {
+                let result = match matrix.authenticate(chain_prefix.into()).await {
+                    Ok(()) => {
+                        matrix
+                            .send_message(EVENT_TEST, &message, &formatted_message)
+                            .await
+                    }
+                    Err(e) => Err(e),
+                };
+                results.push(("Matrix", result.map_err(|e| e.to_string())));
+            }
+
+            let telegram = Telegram::new();
+            if telegram.is_enabled() {
+                let result = telegram.send_message(&message, &formatted_message).await;
+                results.push(("Telegram", result.map_err(|e| e.to_string())));
+            }
+
+            let smtp = Smtp::new();
+            if smtp.is_enabled() {
+                let result = smtp.send_message(&message, &formatted_message).await;
+                results.push(("SMTP", result.map_err(|e| e.to_string())));
+            }
+
+            let webhook = Webhook::new();
+            if webhook.is_enabled() {
+                let notification = Notification::new(EVENT_TEST, "", "", 0, 0);
+                let result = webhook.send(&notification).await;
+                results.push(("Webhook", result.map_err(|e| e.to_string())));
+            }
+
+            // The test incident is resolved right away, so that nobody is paged
+            let pagerduty = PagerDuty::new();
+            if pagerduty.is_enabled() {
+                let result = match pagerduty
+                    .trigger(EVENT_TEST, "test-notification", &message)
+                    .await
+                {
+                    Ok(()) => pagerduty.resolve(EVENT_TEST, "test-notification").await,
+                    Err(e) => Err(e),
+                };
+                results.push(("PagerDuty", result.map_err(|e| e.to_string())));
+            }
+
+            results
+        })
+    }
+
+    /// Run a single monitoring pass and exit, the error is returned instead of
+    /// restarting the subscription
+    pub fn run_once() -> Result<(), ScoutyError> {
+        wait_for_startup_delay(CONFIG.load().startup_delay(), shutdown::sleep);
+        task::block_on(async {
+            let c: Scouty = Scouty::new().await;
+            c.subscribe_on_chain_events().await
+        })
+    }
+
     pub fn client(&self) -> &Client<DefaultConfig> {
         &self.client
     }
@@ -137,24 +439,146 @@ impl Scouty {
         &self.matrix
     }
 
+    /// Returns the telegram configuration
+    pub fn telegram(&self) -> &Telegram {
+        &self.telegram
+    }
+
+    /// Returns the smtp configuration
+    pub fn smtp(&self) -> &Smtp {
+        &self.smtp
+    }
+
+    /// Returns the webhook configuration
+    pub fn webhook(&self) -> &Webhook {
+        &self.webhook
+    }
+
+    /// Post the event notification to the webhook url. A webhook failure is only
+    /// logged so that it does not interrupt the remaining hooks.
+    pub async fn notify(&self, notification: Notification) {
+        if let Err(e) = self.webhook().send(&notification).await {
+            warn!("Webhook notification skipped! {}", e);
+        }
+    }
+
+    /// Returns the pagerduty configuration
+    pub fn pagerduty(&self) -> &PagerDuty {
+        &self.pagerduty
+    }
+
+    /// Trigger a PagerDuty incident for the stash and event. A PagerDuty failure
+    /// is only logged so that it does not interrupt the remaining hooks.
+    pub async fn trigger_incident(&self, event: &str, stash: &str, stash_name: &str) {
+        DAILY_REPORT
+            .lock()
+            .unwrap()
+            .record_incident(stash, stash_name, event);
+        if let Err(e) = self.pagerduty().trigger(event, stash, stash_name).await {
+            warn!("PagerDuty incident skipped! {}", e);
+        }
+    }
+
+    /// Resolve the PagerDuty incident of the stash and event once it clears
+    pub async fn resolve_incident(&self, event: &str, stash: &str) {
+        if let Err(e) = self.pagerduty().resolve(event, stash).await {
+            warn!("PagerDuty resolve skipped! {}", e);
+        }
+    }
+
+    /// Send the message to every notification backend enabled. A failure in one
+    /// backend does not prevent the message from being sent to the others.
+    /// Messages above the notifications rate limit are suppressed for all backends.
     pub async fn send_message(
         &self,
+        event: &str,
         message: &str,
         formatted_message: &str,
     ) -> Result<(), ScoutyError> {
-        self.matrix()
-            .send_message(message, formatted_message)
-            .await?;
+        if !ratelimit::allow_notification() {
+            return Ok(());
+        }
+        self.send_message_unlimited(event, message, formatted_message)
+            .await
+    }
+
+    /// Send the message to every notification backend enabled, bypassing the
+    /// notifications rate limit. Only meant for critical messages.
+    async fn send_message_unlimited(
+        &self,
+        event: &str,
+        message: &str,
+        formatted_message: &str,
+    ) -> Result<(), ScoutyError> {
+        let config = CONFIG.load_full();
+        let message = config.wrap_notification(message);
+        let formatted_message = config.wrap_formatted_notification(formatted_message);
+        let matrix_result = self
+            .matrix()
+            .send_message(event, &message, &formatted_message)
+            .await;
+        let telegram_result = self
+            .telegram()
+            .send_message(&message, &formatted_message)
+            .await;
+        let smtp_result = self.smtp().send_message(&message, &formatted_message).await;
+        // Every backend is tried and the notification counted before any error is
+        // returned, so that one failing backend does not hide the others
+        RUNTIME_STATS.inc_notifications();
+        matrix_result?;
+        telegram_result?;
+        smtp_result?;
         Ok(())
     }
 
+    /// Send the report message unless it is identical to the previous one sent
+    /// for the same section and stashes within the deduplication window. During
+    /// the startup cycle the message may only be recorded as sent.
+    pub async fn send_report(&self, report: &Report) -> Result<(), ScoutyError> {
+        let config = CONFIG.load_full();
+        let window = time::Duration::from_secs(config.notification_dedup_window_seconds);
+        let message = report.message();
+        if STARTUP_CYCLE.is_silenced(config.suppress_startup_notifications) {
+            LAST_SENT.lock().unwrap().record(
+                report.target(),
+                &message,
+                time::Instant::now(),
+            );
+            info!("Startup notification suppressed");
+            return Ok(());
+        }
+        let is_duplicate = LAST_SENT.lock().unwrap().is_duplicate(
+            report.target(),
+            &message,
+            window,
+            time::Instant::now(),
+        );
+        if is_duplicate {
+            info!("Duplicate notification suppressed");
+            return Ok(());
+        }
+        self.send_message(report.event(), &message, &report.formatted_message())
+            .await
+    }
+
+    /// Send a high-priority report message, bypassing the notifications rate limit
+    pub async fn send_priority_report(&self, report: &Report) -> Result<(), ScoutyError> {
+        warn!("Sending high-priority notification");
+        self.send_message_unlimited(
+            report.event(),
+            &report.message(),
+            &report.formatted_message(),
+        )
+        .await
+    }
+
     /// Spawn and restart subscription on error
     pub fn subscribe() {
         spawn_and_restart_subscription_on_error();
     }
 
     async fn subscribe_on_chain_events(&self) -> Result<(), ScoutyError> {
-        let config = CONFIG.clone();
+        let config = CONFIG.load_full();
 
         // Verify if hooks scripts are available
         Hook::exists(HOOK_INIT, &config.hook_init_path);
@@ -164,6 +588,11 @@ impl Scouty {
             HOOK_VALIDATOR_STARTS_ACTIVE_NEXT_ERA,
             &config.hook_validator_starts_active_next_era_path,
         );
+        for stash in config.stashes.iter() {
+            if let Some(path) = &stash.hook_active_next_era_path {
+                Hook::exists(HOOK_VALIDATOR_STARTS_ACTIVE_NEXT_ERA, path);
+            }
+        }
         Hook::exists(
             HOOK_VALIDATOR_STARTS_INACTIVE_NEXT_ERA,
             &config.hook_validator_starts_inactive_next_era_path,
@@ -172,6 +601,12 @@ impl Scouty {
         Hook::exists(HOOK_VALIDATOR_CHILLED, &config.hook_validator_chilled_path);
         Hook::exists(HOOK_VALIDATOR_OFFLINE, &config.hook_validator_offline_path);
         Hook::exists(HOOK_DEMOCRACY_STARTED, &config.hook_democracy_started_path);
+        Hook::exists(
+            HOOK_VALIDATOR_COMMISSION_CHANGED,
+            &config.hook_validator_commission_changed_path,
+        );
+        Hook::exists(HOOK_STASH_SLASHED, &config.hook_slash_path);
+        Hook::exists(HOOK_UNCLAIMED_REWARDS, &config.hook_unclaimed_rewards_path);
 
         match self.runtime {
             SupportedRuntime::Polkadot => {
@@ -187,36 +622,167 @@ impl Scouty {
     }
 }
 
+/// Wait before the first connection attempt, so that a local node started at
+/// the same time is ready to accept connections
+fn wait_for_startup_delay<S>(delay: time::Duration, sleep: S)
+where
+    S: FnOnce(time::Duration),
+{
+    if delay.as_secs() > 0 {
+        info!(
+            "Waiting {} before the first connection",
+            format_duration(delay)
+        );
+        sleep(delay);
+    }
+}
+
 fn spawn_and_restart_subscription_on_error() {
+    wait_for_startup_delay(CONFIG.load().startup_delay(), shutdown::sleep);
     let t = task::spawn(async {
-        let config = CONFIG.clone();
-        loop {
-            let c: Scouty = Scouty::new().await;
-            if let Err(e) = c.subscribe_on_chain_events().await {
-                match e {
-                    ScoutyError::SubscriptionFinished => warn!("{}", e),
-                    ScoutyError::MatrixError(_) => warn!("Matrix message skipped!"),
-                    _ => {
-                        error!("{}", e);
-                        let message =
-                            format!("On hold for {} min!", config.error_interval);
-                        let formatted_message = format!("<br/>🚨 An error was raised -> <code>scouty</code> on hold for {} min while rescue is on the way 🚁 🚒 🚑 🚓<br/><br/>", config.error_interval);
-                        c.send_message(&message, &formatted_message).await.unwrap();
-                        thread::sleep(time::Duration::from_secs(
-                            60 * config.error_interval,
-                        ));
-                        continue;
+        shutdown::loop_until_shutdown(&shutdown::SHUTDOWN, || {
+            // Load the latest configuration on every restart
+            let config = CONFIG.load_full();
+            async move {
+                let c: Scouty = Scouty::new().await;
+                if let Err(e) = c.subscribe_on_chain_events().await {
+                    match e {
+                        ScoutyError::SubscriptionFinished => warn!("{}", e),
+                        ScoutyError::MatrixError(_) => warn!("Matrix message skipped!"),
+                        ScoutyError::TelegramError(_) => {
+                            warn!("Telegram message skipped!")
+                        }
+                        ScoutyError::SmtpError(_) => warn!("Email message skipped!"),
+                        _ => {
+                            error!("{}", e);
+                            if let Some(hint) = e.hint() {
+                                error!("{}", hint);
+                            }
+                            // Nothing to recover from by restarting
+                            if e.class() == ErrorClass::Fatal {
+                                error!("Fatal error, 'scouty' exits without restarting!");
+                                info!("{}", RUNTIME_STATS.summary(time::Instant::now()));
+                                process::exit(e.exit_code());
+                            }
+                            let delay = backoff::next_delay(
+                                config.error_interval,
+                                config.error_interval_max,
+                            );
+                            if backoff::is_exhausted(config.max_restarts) {
+                                error!(
+                                    "Giving up after {} consecutive restarts!",
+                                    config.max_restarts.unwrap_or_default()
+                                );
+                                info!("{}", RUNTIME_STATS.summary(time::Instant::now()));
+                                process::exit(e.exit_code());
+                            }
+                            let error_interval = format_duration(delay);
+                            warn!("On hold for {} before restarting", error_interval);
+                            let message = format!("On hold for {}!", error_interval);
+                            let formatted_message = format!("<br/>🚨 An error was raised -> <code>scouty</code> on hold for {} while rescue is on the way 🚁 🚒 🚑 🚓<br/><br/>", error_interval);
+                            if let Err(e) = c
+                                .send_message(EVENT_ERROR, &message, &formatted_message)
+                                .await
+                            {
+                                warn!("Error notification could not be sent: {}", e);
+                            }
+                            shutdown::sleep(delay);
+                            return;
+                        }
                     }
-                }
-                thread::sleep(time::Duration::from_secs(1));
-            };
-        }
+                    shutdown::sleep(time::Duration::from_secs(1));
+                };
+            }
+        })
+        .await;
+        // The websocket connection is closed once the client is dropped
+        info!("{}", RUNTIME_STATS.summary(time::Instant::now()));
+        info!("Shutdown completed");
     });
     task::block_on(t);
 }
 
+/// Outcome of the test message sent to each notification backend
+pub type TestResults = Vec<(&'static str, Result<(), String>)>;
+
+/// Returns the exit code of the notifications test, non-zero if any backend failed
+pub fn test_notifications_exit_code(results: &TestResults) -> i32 {
+    if results.iter().any(|(_, result)| result.is_err()) {
+        EXIT_FAILURE
+    } else {
+        EXIT_SUCCESS
+    }
+}
+
 pub fn get_account_id_from_storage_key(key: StorageKey) -> AccountId32 {
     let s = &key.0[key.0.len() - 32..];
     let v: [u8; 32] = s.try_into().expect("slice with incorrect length");
     AccountId32::new(v)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::RefCell, net::TcpListener};
+
+    #[test]
+    fn it_delays_the_first_iteration() {
+        // The mock clock only advances when slept on
+        let clock = RefCell::new(time::Duration::from_secs(0));
+        let iterations: RefCell<Vec<time::Duration>> = RefCell::new(Vec::new());
+        let run = |delay: u64| {
+            wait_for_startup_delay(time::Duration::from_secs(delay), |d| {
+                *clock.borrow_mut() += d
+            });
+            iterations.borrow_mut().push(*clock.borrow());
+        };
+
+        run(0);
+        assert_eq!(*iterations.borrow(), vec![time::Duration::from_secs(0)]);
+
+        run(20);
+        assert_eq!(
+            *iterations.borrow(),
+            vec![time::Duration::from_secs(0), time::Duration::from_secs(20)]
+        );
+    }
+
+    #[test]
+    fn it_times_out_a_slow_connection() {
+        // The server accepts the connection but never answers the handshake
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            thread::sleep(time::Duration::from_secs(2));
+            drop(stream);
+        });
+
+        let result = task::block_on(with_timeout(
+            time::Duration::from_millis(200),
+            "Connection",
+            create_substrate_node_client(&url),
+        ));
+        match result {
+            Err(ScoutyError::TimeoutError(msg)) => {
+                assert_eq!(msg, "Connection did not complete within 200ms")
+            }
+            Err(e) => panic!("unexpected error: {}", e),
+            Ok(_) => panic!("connection should time out"),
+        }
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn it_exits_with_non_zero_if_any_backend_failed() {
+        let results: TestResults = vec![("Matrix", Ok(())), ("Webhook", Ok(()))];
+        assert_eq!(test_notifications_exit_code(&results), 0);
+        assert_eq!(test_notifications_exit_code(&TestResults::new()), 0);
+
+        let results: TestResults = vec![
+            ("Matrix", Ok(())),
+            ("Telegram", Err("Unauthorized".to_string())),
+        ];
+        assert_eq!(test_notifications_exit_code(&results), 1);
+    }
+}
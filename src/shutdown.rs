@@ -0,0 +1,98 @@
+// The MIT License (MIT)
+// Copyright © 2021 Aukbit Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+use lazy_static::lazy_static;
+use signal_hook::consts::{SIGINT, SIGTERM};
+use std::{
+    future::Future,
+    io,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+lazy_static! {
+    pub static ref SHUTDOWN: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+}
+
+/// Set the shutdown flag on SIGTERM or SIGINT instead of terminating the process, so
+/// that any in-flight hook finishes before 'scouty' exits. A second signal
+/// terminates the process immediately.
+pub fn register() -> Result<(), io::Error> {
+    for signal in [SIGTERM, SIGINT] {
+        signal_hook::flag::register_conditional_shutdown(signal, 1, SHUTDOWN.clone())?;
+        signal_hook::flag::register(signal, SHUTDOWN.clone())?;
+    }
+    Ok(())
+}
+
+/// Returns true if a shutdown signal has been received
+pub fn is_requested() -> bool {
+    SHUTDOWN.load(Ordering::SeqCst)
+}
+
+/// Sleep for the given duration, returning earlier if a shutdown is requested
+pub fn sleep(duration: Duration) {
+    let start = Instant::now();
+    while !is_requested() && start.elapsed() < duration {
+        thread::sleep(Duration::from_millis(200).min(duration));
+    }
+}
+
+/// Run the iteration repeatedly until the shutdown flag is set. The flag is only
+/// checked between iterations, an iteration in progress is always completed.
+pub async fn loop_until_shutdown<F, Fut>(shutdown: &AtomicBool, mut iteration: F)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = ()>,
+{
+    while !shutdown.load(Ordering::SeqCst) {
+        iteration().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_std::task;
+    use std::sync::atomic::AtomicU32;
+
+    #[test]
+    fn it_exits_the_loop_once_shutdown_is_requested() {
+        let shutdown = AtomicBool::new(false);
+        let iterations = AtomicU32::new(0);
+        task::block_on(loop_until_shutdown(&shutdown, || async {
+            // simulate a shutdown signal received during the 3rd iteration
+            if iterations.fetch_add(1, Ordering::SeqCst) == 2 {
+                shutdown.store(true, Ordering::SeqCst);
+            }
+        }));
+        assert_eq!(iterations.load(Ordering::SeqCst), 3);
+
+        // no new iteration is started once the flag is set
+        task::block_on(loop_until_shutdown(&shutdown, || async {
+            iterations.fetch_add(1, Ordering::SeqCst);
+        }));
+        assert_eq!(iterations.load(Ordering::SeqCst), 3);
+    }
+}
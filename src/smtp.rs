@@ -0,0 +1,110 @@
+// The MIT License (MIT)
+// Copyright © 2021 Aukbit Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+use crate::config::CONFIG;
+use crate::errors::SmtpError;
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    transport::smtp::authentication::Credentials,
+    Message, SmtpTransport, Transport,
+};
+use log::debug;
+use std::result::Result;
+
+#[derive(Clone, Debug)]
+pub struct Smtp {
+    host: String,
+    port: u16,
+    user: String,
+    password: String,
+    from: String,
+    to: Vec<String>,
+    disabled: bool,
+}
+
+impl Default for Smtp {
+    fn default() -> Smtp {
+        Smtp {
+            host: String::from(""),
+            port: 587,
+            user: String::from(""),
+            password: String::from(""),
+            from: String::from(""),
+            to: Vec::new(),
+            disabled: true,
+        }
+    }
+}
+
+impl Smtp {
+    pub fn new() -> Smtp {
+        let config = CONFIG.load_full();
+        Smtp {
+            host: config.smtp_host.to_string(),
+            port: config.smtp_port,
+            user: config.smtp_user.to_string(),
+            password: config.smtp_password.expose().to_string(),
+            from: config.smtp_from.to_string(),
+            to: config.smtp_to.clone(),
+            disabled: !config.is_smtp_enabled(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.disabled
+    }
+
+    pub async fn send_message(
+        &self,
+        message: &str,
+        formatted_message: &str,
+    ) -> Result<(), SmtpError> {
+        if self.disabled {
+            return Ok(());
+        }
+        let mut builder = Message::builder()
+            .from(self.from.parse::<Mailbox>()?)
+            .subject(subject(message));
+        for to in self.to.iter() {
+            builder = builder.to(to.trim().parse::<Mailbox>()?);
+        }
+        let email = builder
+            .header(ContentType::TEXT_HTML)
+            .body(formatted_message.to_string())?;
+
+        let mut transport = SmtpTransport::starttls_relay(&self.host)?.port(self.port);
+        if !self.user.is_empty() {
+            transport = transport.credentials(Credentials::new(
+                self.user.to_string(),
+                self.password.to_string(),
+            ));
+        }
+        let res = transport.build().send(&email)?;
+        debug!("response {:?}", res);
+        debug!("Email message dispatched");
+        Ok(())
+    }
+}
+
+/// The first line of the plain text message is used as the email subject
+fn subject(message: &str) -> String {
+    let line = message.lines().find(|line| !line.trim().is_empty());
+    format!("scouty: {}", line.unwrap_or("notification").trim())
+}
@@ -0,0 +1,149 @@
+// The MIT License (MIT)
+// Copyright © 2021 Aukbit Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Nominators count and total backing (in plancks) of a stash in an era
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+pub struct Stake {
+    pub nominators: u32,
+    pub total: u128,
+}
+
+impl Stake {
+    /// Verifies if the nominators count or the total backing changed by more
+    /// than the threshold percent
+    pub fn has_shifted(&self, new: &Stake, threshold_percent: u32) -> bool {
+        exceeds_threshold(
+            self.nominators as u128,
+            new.nominators as u128,
+            threshold_percent,
+        ) || exceeds_threshold(self.total, new.total, threshold_percent)
+    }
+}
+
+/// Last stake seen for each active stash
+#[derive(Debug, Default)]
+pub struct StakeRecords {
+    stakes: BTreeMap<String, Stake>,
+}
+
+impl StakeRecords {
+    pub fn new() -> StakeRecords {
+        StakeRecords::default()
+    }
+
+    /// Stores the stake of the stash for the new era and returns the one of the
+    /// previous era if either the nominators count or the total backing changed by
+    /// more than the threshold. The first stake stored is never a change, and a
+    /// zero threshold disables the comparison.
+    pub fn update(
+        &mut self,
+        stash: &str,
+        stake: Stake,
+        threshold_percent: u32,
+    ) -> Option<Stake> {
+        match self.stakes.insert(stash.to_string(), stake) {
+            Some(previous) if previous.has_shifted(&stake, threshold_percent) => {
+                Some(previous)
+            }
+            _ => None,
+        }
+    }
+
+    /// Forgets the stake of the stash, e.g. once it is no longer active, so that
+    /// it becomes a fresh baseline when it is active again
+    pub fn remove(&mut self, stash: &str) {
+        self.stakes.remove(stash);
+    }
+}
+
+/// Verifies if the value changed by strictly more than the threshold percent of
+/// the old value. Any change from zero exceeds the threshold.
+pub fn exceeds_threshold(old: u128, new: u128, threshold_percent: u32) -> bool {
+    if threshold_percent == 0 || old == new {
+        return false;
+    }
+    if old == 0 {
+        return true;
+    }
+    let diff = if new > old { new - old } else { old - new };
+    // diff / old > threshold / 100, without losing precision
+    diff.saturating_mul(100) > old.saturating_mul(threshold_percent as u128)
+}
+
+/// Formats the relative change from the old value (e.g. 100 -> 85 as -15.00%)
+pub fn format_change(old: u128, new: u128) -> String {
+    if old == 0 {
+        return "new".to_string();
+    }
+    let change = (new as f64 - old as f64) * 100.0 / old as f64;
+    format!("{:+.2}%", change)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_compares_the_stake_change_with_the_threshold() {
+        // exactly at the threshold is not a change
+        assert!(!exceeds_threshold(1000, 1100, 10));
+        assert!(!exceeds_threshold(1000, 900, 10));
+        // just above the threshold in both directions
+        assert!(exceeds_threshold(1000, 1101, 10));
+        assert!(exceeds_threshold(1000, 899, 10));
+        // large balances in plancks do not overflow nor lose precision
+        let total = 5_000_000 * 10u128.pow(10);
+        assert!(!exceeds_threshold(total, total + total / 20, 5));
+        assert!(exceeds_threshold(total, total + total / 20 + 1, 5));
+        // zero threshold is off, any change from zero exceeds it
+        assert!(!exceeds_threshold(1000, 2000, 0));
+        assert!(exceeds_threshold(0, 1, 10));
+        assert!(!exceeds_threshold(0, 0, 10));
+        assert_eq!(format_change(200, 170), "-15.00%");
+        assert_eq!(format_change(200, 210), "+5.00%");
+    }
+
+    #[test]
+    fn it_detects_nominators_or_total_stake_changes() {
+        let mut records = StakeRecords::new();
+        let stash = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+        let stake = Stake {
+            nominators: 100,
+            total: 1_000_000,
+        };
+        assert_eq!(records.update(stash, stake, 10), None);
+        // 10% less nominators is not above the threshold
+        let fewer = Stake {
+            nominators: 90,
+            ..stake
+        };
+        assert_eq!(records.update(stash, fewer, 10), None);
+        // compared with the previous era, not with the first one
+        let less_backed = Stake {
+            nominators: 90,
+            total: 800_000,
+        };
+        assert_eq!(records.update(stash, less_backed, 10), Some(fewer));
+        assert_eq!(records.update(stash, stake, 0), None);
+    }
+}
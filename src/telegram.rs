@@ -0,0 +1,145 @@
+// The MIT License (MIT)
+// Copyright © 2021 Aukbit Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+use crate::config::CONFIG;
+use crate::errors::TelegramError;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::{result::Result, thread, time};
+
+const TELEGRAM_URL: &str = "https://api.telegram.org";
+
+#[derive(Debug, Serialize)]
+struct SendMessageRequest {
+    chat_id: String,
+    text: String,
+    parse_mode: String,
+    disable_web_page_preview: bool,
+}
+
+#[derive(Deserialize, Debug)]
+struct ResponseParameters {
+    #[serde(default)]
+    retry_after: Option<u64>,
+}
+
+#[derive(Deserialize, Debug)]
+struct SendMessageResponse {
+    ok: bool,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    parameters: Option<ResponseParameters>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Telegram {
+    pub client: reqwest::Client,
+    bot_token: String,
+    chat_id: String,
+    disabled: bool,
+}
+
+impl Default for Telegram {
+    fn default() -> Telegram {
+        Telegram {
+            client: reqwest::Client::new(),
+            bot_token: String::from(""),
+            chat_id: String::from(""),
+            disabled: true,
+        }
+    }
+}
+
+impl Telegram {
+    pub fn new() -> Telegram {
+        let config = CONFIG.load_full();
+        Telegram {
+            bot_token: config.telegram_bot_token.expose().to_string(),
+            chat_id: config.telegram_chat_id.to_string(),
+            disabled: !config.is_telegram_enabled(),
+            ..Default::default()
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.disabled
+    }
+
+    pub async fn send_message(
+        &self,
+        _message: &str,
+        formatted_message: &str,
+    ) -> Result<(), TelegramError> {
+        if self.disabled {
+            return Ok(());
+        }
+        // Telegram HTML parse mode does not support <br/> tags, line breaks are
+        // sent as new lines instead
+        let req = SendMessageRequest {
+            chat_id: self.chat_id.to_string(),
+            text: formatted_message.replace("<br/>", "\n"),
+            parse_mode: "HTML".to_string(),
+            disable_web_page_preview: true,
+        };
+        loop {
+            let res = self
+                .client
+                .post(format!(
+                    "{}/bot{}/sendMessage",
+                    TELEGRAM_URL, self.bot_token
+                ))
+                .json(&req)
+                .send()
+                .await
+                .map_err(|e| e.without_url())?;
+
+            // The request url holds the bot token, so only the status is logged
+            let status = res.status();
+            debug!("response status {}", status);
+            let response = res
+                .json::<SendMessageResponse>()
+                .await
+                .map_err(|e| e.without_url())?;
+            match status {
+                reqwest::StatusCode::OK if response.ok => {
+                    debug!("Telegram message dispatched");
+                    return Ok(());
+                }
+                reqwest::StatusCode::TOO_MANY_REQUESTS => {
+                    let retry_after =
+                        response.parameters.and_then(|p| p.retry_after).unwrap_or(5);
+                    warn!(
+                        "Telegram too many requests -> Wait {} seconds and try again",
+                        retry_after
+                    );
+                    thread::sleep(time::Duration::from_secs(retry_after));
+                }
+                _ => {
+                    return Err(TelegramError::Other(
+                        response
+                            .description
+                            .unwrap_or_else(|| format!("status code {}", status)),
+                    ))
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,188 @@
+// The MIT License (MIT)
+// Copyright © 2021 Aukbit Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+use crate::errors::ConfigError;
+use serde::Deserialize;
+use std::fs;
+
+pub const TEMPLATE_NEW_SESSION: &'static str = "new_session";
+pub const TEMPLATE_ACTIVE_NEXT_ERA: &'static str = "active_next_era";
+pub const TEMPLATE_INACTIVE_NEXT_ERA: &'static str = "inactive_next_era";
+
+const DEFAULT_NEW_SESSION: &'static str = "⛓️ <b>{{network}}</b> -> {{session_flag}} {{session_ordinal}} session ({{session}}) of era <b>{{era}}</b>";
+const DEFAULT_ACTIVE_NEXT_ERA: &'static str =
+    "👆 Starts active in era {{next_era}} -> 🟢";
+const DEFAULT_INACTIVE_NEXT_ERA: &'static str =
+    "👆 Starts inactive in era {{next_era}} -> 🔴";
+
+/// Named message templates, loaded from a TOML file. Templates not defined in
+/// the file fall back to the built-in defaults.
+#[derive(Debug, Deserialize, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Templates {
+    new_session: Option<String>,
+    active_next_era: Option<String>,
+    inactive_next_era: Option<String>,
+}
+
+impl Templates {
+    /// Loads the templates file, the built-in defaults are used if no path is defined
+    pub fn load(path: Option<&str>) -> Result<Templates, ConfigError> {
+        match path {
+            Some(path) => {
+                let content = fs::read_to_string(path).map_err(|e| {
+                    ConfigError::Other(format!(
+                        "message templates file '{}' not read: {}",
+                        path, e
+                    ))
+                })?;
+                Templates::parse(&content)
+            }
+            None => Ok(Templates::default()),
+        }
+    }
+
+    pub fn parse(content: &str) -> Result<Templates, ConfigError> {
+        Ok(toml::from_str(content)?)
+    }
+
+    pub fn get(&self, name: &str) -> &str {
+        match name {
+            TEMPLATE_NEW_SESSION => {
+                self.new_session.as_deref().unwrap_or(DEFAULT_NEW_SESSION)
+            }
+            TEMPLATE_ACTIVE_NEXT_ERA => self
+                .active_next_era
+                .as_deref()
+                .unwrap_or(DEFAULT_ACTIVE_NEXT_ERA),
+            TEMPLATE_INACTIVE_NEXT_ERA => self
+                .inactive_next_era
+                .as_deref()
+                .unwrap_or(DEFAULT_INACTIVE_NEXT_ERA),
+            _ => "",
+        }
+    }
+
+    pub fn render(&self, name: &str, vars: &[(&str, String)]) -> String {
+        render(self.get(name), vars)
+    }
+}
+
+/// Replaces every `{{variable}}` in the template by its value, variables not
+/// defined are rendered empty
+pub fn render(template: &str, vars: &[(&str, String)]) -> String {
+    let mut output = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        match rest[start..].find("}}") {
+            Some(end) => {
+                output.push_str(&rest[..start]);
+                let key = rest[start + 2..start + end].trim();
+                if let Some((_, value)) = vars.iter().find(|(k, _)| *k == key) {
+                    output.push_str(value);
+                }
+                rest = &rest[start + end + 2..];
+            }
+            None => break,
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_vars() -> Vec<(&'static str, String)> {
+        vec![
+            ("network", "Kusama".to_string()),
+            (
+                "stash",
+                "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY".to_string(),
+            ),
+            ("stash_name", "Alice".to_string()),
+            ("era", "3587".to_string()),
+            ("next_era", "3588".to_string()),
+            ("session", "25021".to_string()),
+            ("session_flag", "🕚".to_string()),
+            ("session_ordinal", "6th".to_string()),
+        ]
+    }
+
+    #[test]
+    fn it_renders_the_default_templates() {
+        let templates = Templates::load(None).unwrap();
+        assert_eq!(
+            templates.render(TEMPLATE_NEW_SESSION, &sample_vars()),
+            "⛓️ <b>Kusama</b> -> 🕚 6th session (25021) of era <b>3587</b>"
+        );
+        assert_eq!(
+            templates.render(TEMPLATE_ACTIVE_NEXT_ERA, &sample_vars()),
+            "👆 Starts active in era 3588 -> 🟢"
+        );
+        assert_eq!(
+            templates.render(TEMPLATE_INACTIVE_NEXT_ERA, &sample_vars()),
+            "👆 Starts inactive in era 3588 -> 🔴"
+        );
+    }
+
+    #[test]
+    fn it_renders_the_templates_defined() {
+        let templates = Templates::parse(
+            r#"
+            new_session = "Sessão {{ session }} da era {{era}}"
+            active_next_era = "{{stash_name}} ativo na era {{next_era}}"
+            inactive_next_era = "{{stash_name}} ({{unknown}}) inativo na era {{next_era}}"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            templates.render(TEMPLATE_NEW_SESSION, &sample_vars()),
+            "Sessão 25021 da era 3587"
+        );
+        assert_eq!(
+            templates.render(TEMPLATE_ACTIVE_NEXT_ERA, &sample_vars()),
+            "Alice ativo na era 3588"
+        );
+        assert_eq!(
+            templates.render(TEMPLATE_INACTIVE_NEXT_ERA, &sample_vars()),
+            "Alice () inativo na era 3588"
+        );
+
+        // templates not defined fall back to the defaults
+        let templates = Templates::parse("new_session = \"{{session}}\"").unwrap();
+        assert_eq!(
+            templates.render(TEMPLATE_ACTIVE_NEXT_ERA, &sample_vars()),
+            "👆 Starts active in era 3588 -> 🟢"
+        );
+    }
+
+    #[test]
+    fn it_fails_with_an_unknown_template() {
+        assert!(Templates::parse("old_session = \"{{session}}\"").is_err());
+        assert!(Templates::load(Some("/tmp/scouty_templates_not_found.toml")).is_err());
+    }
+
+    #[test]
+    fn it_renders_unclosed_variables_as_text() {
+        assert_eq!(render("era {{era", &sample_vars()), "era {{era");
+    }
+}
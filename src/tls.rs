@@ -0,0 +1,134 @@
+// The MIT License (MIT)
+// Copyright © 2021 Aukbit Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+use crate::errors::ScoutyError;
+use crate::proxy;
+use native_tls::TlsConnector;
+use sha2::{Digest, Sha256};
+use std::{result::Result, time::Duration};
+use url::Url;
+
+/// Returns the SHA-256 fingerprint of the DER encoded certificate as lowercase hex
+pub fn fingerprint(der: &[u8]) -> String {
+    hex::encode(Sha256::digest(der))
+}
+
+/// Compares the certificate fingerprint with the expected one. The expected
+/// fingerprint is case insensitive and may be separated by colons
+/// (e.g. AB:CD:...) as printed by openssl.
+pub fn fingerprint_matches(der: &[u8], expected: &str) -> bool {
+    let expected: String = expected
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != ':')
+        .collect::<String>()
+        .to_lowercase();
+    fingerprint(der) == expected
+}
+
+/// Opens a local tunnel to the `wss://` endpoint and returns the ws:// url the
+/// substrate client should connect to. The TLS session is established by the
+/// tunnel and the server certificate is verified against the pinned fingerprint
+/// on that same connection, so the websocket never runs over an unpinned socket.
+/// The certificate chain itself is not verified by a certificate authority since
+/// the pinned fingerprint takes precedence, which also allows self-signed
+/// certificates. A zero connect timeout waits indefinitely.
+pub fn pinned_tunnel(
+    substrate_ws_url: &str,
+    expected: &str,
+    proxy: Option<&str>,
+    client_name: &str,
+    connect_timeout: Duration,
+) -> Result<String, ScoutyError> {
+    let url = Url::parse(substrate_ws_url).map_err(|e| {
+        ScoutyError::Other(format!("Invalid url {}: {}", substrate_ws_url, e))
+    })?;
+    let host = url.host_str().ok_or_else(|| {
+        ScoutyError::Other(format!("Url {} has no host defined", substrate_ws_url))
+    })?;
+    let port = url.port().unwrap_or(443);
+
+    let connector = TlsConnector::builder()
+        .danger_accept_invalid_certs(true)
+        .danger_accept_invalid_hostnames(true)
+        .build()
+        .map_err(|e| ScoutyError::Other(format!("TLS error: {}", e)))?;
+    let stream = match proxy {
+        Some(proxy) => proxy::connect(proxy, host, port, client_name)?,
+        None => proxy::connect_direct(host, port, connect_timeout)?,
+    };
+    // The handshake is bounded by the connect timeout as well
+    let timeout = Some(connect_timeout).filter(|timeout| !timeout.is_zero());
+    stream.set_read_timeout(timeout)?;
+    stream.set_write_timeout(timeout)?;
+    let stream = connector
+        .connect(host, stream)
+        .map_err(|e| ScoutyError::Other(format!("TLS handshake error: {}", e)))?;
+    let certificate = stream
+        .peer_certificate()
+        .map_err(|e| ScoutyError::Other(format!("TLS error: {}", e)))?
+        .ok_or_else(|| {
+            ScoutyError::Other(format!(
+                "No TLS certificate presented by {}",
+                substrate_ws_url
+            ))
+        })?;
+    let der = certificate
+        .to_der()
+        .map_err(|e| ScoutyError::Other(format!("TLS error: {}", e)))?;
+
+    if !fingerprint_matches(&der, expected) {
+        return Err(ScoutyError::Other(format!(
+            "TLS certificate fingerprint mismatch for {}: expected {} but got {}",
+            substrate_ws_url,
+            expected,
+            fingerprint(&der)
+        )));
+    }
+
+    // The read timeout is only shortened once the handshake is completed
+    stream
+        .get_ref()
+        .set_read_timeout(Some(Duration::from_millis(20)))?;
+    stream.get_ref().set_write_timeout(None)?;
+    proxy::tunnel_stream(substrate_ws_url, stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_compares_certificate_fingerprints() {
+        let der = b"not really a certificate";
+        let expected = fingerprint(der);
+        assert_eq!(expected.len(), 64);
+        assert!(fingerprint_matches(der, &expected));
+        assert!(fingerprint_matches(der, &expected.to_uppercase()));
+        let with_colons = expected
+            .as_bytes()
+            .chunks(2)
+            .map(|c| std::str::from_utf8(c).unwrap())
+            .collect::<Vec<&str>>()
+            .join(":");
+        assert!(fingerprint_matches(der, &with_colons));
+        assert!(!fingerprint_matches(b"another certificate", &expected));
+        assert!(!fingerprint_matches(der, ""));
+    }
+}
@@ -0,0 +1,136 @@
+// The MIT License (MIT)
+// Copyright © 2021 Aukbit Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+use crate::config::Secret;
+use crate::errors::VaultError;
+use log::debug;
+use serde::Deserialize;
+use std::{collections::HashMap, result::Result};
+
+// Response of the KV v2 engine to a secret read, e.g.
+// {"data":{"data":{"password":"..."},"metadata":{"version":1}}}
+#[derive(Deserialize, Debug)]
+struct SecretResponse {
+    data: SecretData,
+}
+
+#[derive(Deserialize, Debug)]
+struct SecretData {
+    data: HashMap<String, serde_json::Value>,
+}
+
+/// Splits a secret path given as `<mount>/<path>#<key>` (e.g.
+/// secret/scouty#matrix_bot_password), the key is optional and the default one
+/// used if not given
+pub fn parse_path<'a>(
+    path: &'a str,
+    default_key: &'a str,
+) -> Result<(&'a str, &'a str, &'a str), VaultError> {
+    let (path, key) = match path.split_once('#') {
+        Some((path, key)) => (path, key),
+        None => (path, default_key),
+    };
+    match path.trim_matches('/').split_once('/') {
+        Some((mount, path))
+            if !mount.is_empty() && !path.is_empty() && !key.is_empty() =>
+        {
+            Ok((mount, path, key))
+        }
+        _ => Err(VaultError::Other(format!(
+            "Vault path '{}' is not valid, expected e.g. secret/scouty#{}",
+            path, default_key
+        ))),
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Vault {
+    pub client: reqwest::Client,
+    addr: String,
+    token: Secret,
+}
+
+impl Vault {
+    pub fn new(addr: &str, token: Secret) -> Vault {
+        Vault {
+            client: reqwest::Client::new(),
+            addr: addr.trim_end_matches('/').to_string(),
+            token,
+        }
+    }
+
+    /// Reads the key of a secret stored in the KV v2 engine
+    pub async fn read(
+        &self,
+        path: &str,
+        default_key: &str,
+    ) -> Result<Secret, VaultError> {
+        let (mount, path, key) = parse_path(path, default_key)?;
+        let url = format!("{}/v1/{}/data/{}", self.addr, mount, path);
+        let res = self
+            .client
+            .get(&url)
+            .header("X-Vault-Token", self.token.expose())
+            .send()
+            .await?;
+
+        debug!("response {:?}", res);
+        if !res.status().is_success() {
+            return Err(VaultError::Other(format!(
+                "Vault responded with status code {} to {}",
+                res.status(),
+                url
+            )));
+        }
+        let response = res.json::<SecretResponse>().await?;
+        match response.data.data.get(key) {
+            Some(serde_json::Value::String(value)) if !value.is_empty() => {
+                Ok(Secret::from(value.to_string()))
+            }
+            Some(_) => Err(VaultError::Other(format!(
+                "Vault secret {}/{} key '{}' is not a non-empty string",
+                mount, path, key
+            ))),
+            None => Err(VaultError::Other(format!(
+                "Vault secret {}/{} has no key '{}'",
+                mount, path, key
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_the_secret_path() {
+        assert_eq!(
+            parse_path("secret/scouty#password", "matrix_bot_password").unwrap(),
+            ("secret", "scouty", "password")
+        );
+        assert_eq!(
+            parse_path("/kv/validators/scouty", "smtp_password").unwrap(),
+            ("kv", "validators/scouty", "smtp_password")
+        );
+        assert!(parse_path("scouty", "smtp_password").is_err());
+        assert!(parse_path("secret/scouty#", "smtp_password").is_err());
+    }
+}
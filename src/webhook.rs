@@ -0,0 +1,164 @@
+// The MIT License (MIT)
+// Copyright © 2021 Aukbit Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+use crate::config::CONFIG;
+use crate::errors::WebhookError;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::{
+    result::Result,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+pub const EVENT_NEW_SESSION: &str = "new_session";
+pub const EVENT_VALIDATOR_STARTS_ACTIVE_NEXT_ERA: &str =
+    "validator_starts_active_next_era";
+pub const EVENT_VALIDATOR_STARTS_INACTIVE_NEXT_ERA: &str =
+    "validator_starts_inactive_next_era";
+pub const EVENT_VALIDATOR_SLASHED: &str = "validator_slashed";
+pub const EVENT_VALIDATOR_CHILLED: &str = "validator_chilled";
+pub const EVENT_TEST: &str = "test";
+pub const EVENT_ERROR: &str = "error";
+
+/// JSON payload posted to the webhook url on each monitored event
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Notification {
+    pub event: String,
+    pub stash: String,
+    pub stash_name: Option<String>,
+    pub era: u32,
+    pub session: u32,
+    pub timestamp: u64,
+}
+
+impl Notification {
+    pub fn new(
+        event: &str,
+        stash: &str,
+        stash_name: &str,
+        era: u32,
+        session: u32,
+    ) -> Notification {
+        Notification {
+            event: event.to_string(),
+            stash: stash.to_string(),
+            stash_name: if stash_name.is_empty() {
+                None
+            } else {
+                Some(stash_name.to_string())
+            },
+            era,
+            session,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Webhook {
+    pub client: reqwest::Client,
+    url: String,
+    disabled: bool,
+}
+
+impl Default for Webhook {
+    fn default() -> Webhook {
+        Webhook {
+            client: reqwest::Client::new(),
+            url: String::from(""),
+            disabled: true,
+        }
+    }
+}
+
+impl Webhook {
+    pub fn new() -> Webhook {
+        let config = CONFIG.load_full();
+        Webhook {
+            url: config.webhook_url.to_string(),
+            disabled: config.webhook_disabled || config.webhook_url.is_empty(),
+            ..Default::default()
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.disabled
+    }
+
+    pub async fn send(&self, notification: &Notification) -> Result<(), WebhookError> {
+        if self.disabled {
+            return Ok(());
+        }
+        let res = self
+            .client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .json(notification)
+            .send()
+            .await?;
+
+        debug!("response {:?}", res);
+        if res.status().is_success() {
+            debug!("{:?} * Webhook notification dispatched", notification);
+            Ok(())
+        } else {
+            Err(WebhookError::Other(format!(
+                "webhook {} responded with status code {}",
+                self.url,
+                res.status()
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_serializes_a_notification() {
+        let notification = Notification {
+            event: EVENT_NEW_SESSION.to_string(),
+            stash: "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY".to_string(),
+            stash_name: Some("MyValidator01".to_string()),
+            era: 4170,
+            session: 25021,
+            timestamp: 1650000000,
+        };
+        let json = serde_json::to_string(&notification).unwrap();
+        assert_eq!(
+            json,
+            r#"{"event":"new_session","stash":"5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY","stash_name":"MyValidator01","era":4170,"session":25021,"timestamp":1650000000}"#
+        );
+        let notification = Notification::new(
+            EVENT_VALIDATOR_STARTS_ACTIVE_NEXT_ERA,
+            "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",
+            "",
+            4171,
+            25022,
+        );
+        let json = serde_json::to_string(&notification).unwrap();
+        assert!(json.contains(r#""stash_name":null"#));
+        assert!(notification.timestamp > 0);
+    }
+}